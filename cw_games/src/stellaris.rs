@@ -8,7 +8,8 @@ use std::{
 
 use anyhow::anyhow;
 use cw_model::{
-    CaseInsensitiveInterner, GameMod, LoadMode, ModDefinition, Modifier, parse_modifier_log,
+    CaseInsensitiveInterner, EntityMergeMode, GameMod, LoadMode, ModDefinition, Modifier,
+    parse_modifier_log,
 };
 use lazy_static::lazy_static;
 use winreg::{RegKey, enums::HKEY_CURRENT_USER};
@@ -18,6 +19,38 @@ lazy_static! {
         BaseGame::get_install_directory_windows();
 }
 
+/// How the game resolves entities with the same name in each folder, for the folders that have
+/// been checked
+pub const ENTITY_MERGE_MODES: &[(&str, EntityMergeMode)] = &[
+    ("common/agendas", EntityMergeMode::LIOS),
+    ("common/agreement_term_values", EntityMergeMode::FIOS),
+    ("common/anomalies", EntityMergeMode::LIOS),
+    ("common/armies", EntityMergeMode::LIOS),
+    ("common/artifact_actions", EntityMergeMode::LIOS),
+    ("common/ascension_perks", EntityMergeMode::LIOS),
+    ("common/attitudes", EntityMergeMode::LIOS),
+    ("common/bombardment_stances", EntityMergeMode::LIOS),
+    ("common/component_sets", EntityMergeMode::FIOS),
+    ("common/component_templates", EntityMergeMode::FIOS),
+    ("common/defines", EntityMergeMode::MergeShallow),
+    ("common/event_chains", EntityMergeMode::FIOS),
+    ("common/global_ship_designs", EntityMergeMode::FIOS),
+    // Possibly only whole overrides work here
+    ("common/observation_station_missions", EntityMergeMode::LIOS),
+    ("common/on_actions", EntityMergeMode::Merge),
+    ("common/opinion_modifiers", EntityMergeMode::Duplicate),
+    ("common/planet_classes", EntityMergeMode::Duplicate),
+    ("common/section_templates", EntityMergeMode::No),
+    ("common/ship_behaviors", EntityMergeMode::FIOS),
+    ("common/special_projects", EntityMergeMode::FIOSKeyed("key")),
+    ("common/start_screen_messages", EntityMergeMode::FIOS),
+    ("common/static_modifiers", EntityMergeMode::FIOS),
+    ("common/strategic_resources", EntityMergeMode::FIOS),
+    ("common/terraform", EntityMergeMode::Duplicate),
+    ("common/traits", EntityMergeMode::No),
+    ("events", EntityMergeMode::FIOS),
+];
+
 pub struct BaseGame {}
 
 static BASE_MOD: OnceLock<GameMod> = OnceLock::new();
@@ -139,6 +172,29 @@ impl BaseGame {
         "stellaris.exe"
     }
 
+    /// Get the folder the game keeps its localisation in, relative to the game or mod root
+    pub fn get_localisation_folder() -> &'static str {
+        "localisation"
+    }
+
+    /// Get the characters that can follow `§` in localisation text. These are the `textcolors` of
+    /// `interface/fonts.gfx`, plus `!` to end a colour.
+    pub fn get_text_colour_codes() -> &'static [char] {
+        &[
+            '!', 'B', 'E', 'G', 'H', 'L', 'M', 'P', 'R', 'S', 'T', 'W', 'Y',
+        ]
+    }
+
+    /// Get how the game resolves entities with the same name in a folder like `common/armies`.
+    /// Folders that haven't been checked are `Unknown`.
+    pub fn get_entity_merge_mode(folder: &str) -> EntityMergeMode {
+        ENTITY_MERGE_MODES
+            .iter()
+            .find(|(merge_folder, _)| *merge_folder == folder)
+            .map(|(_, merge_mode)| *merge_mode)
+            .unwrap_or(EntityMergeMode::Unknown)
+    }
+
     /// Detects the base directory (game or mod root) by walking up the directory tree
     /// looking for either Stellaris.exe or descriptor.mod
     pub fn detect_base_directory(path: &Path) -> Option<PathBuf> {
@@ -8,7 +8,8 @@ use std::{
 
 use anyhow::anyhow;
 use cw_model::{
-    CaseInsensitiveInterner, GameMod, LoadMode, ModDefinition, Modifier, parse_modifier_log,
+    CaseInsensitiveInterner, EntityMergeMode, GameMod, LoadMode, ModDefinition, Modifier,
+    parse_modifier_log,
 };
 use lazy_static::lazy_static;
 use winreg::{RegKey, enums::HKEY_CURRENT_USER};
@@ -136,6 +137,24 @@ impl BaseGame {
         "victoria3.exe"
     }
 
+    /// Get the folder the game keeps its localisation in, relative to the game or mod root.
+    /// Victoria 3 uses the American spelling.
+    pub fn get_localisation_folder() -> &'static str {
+        "localization"
+    }
+
+    /// Get the characters that can follow `§` in localisation text. Victoria 3 formats text with
+    /// `#` markup instead, so there are none.
+    pub fn get_text_colour_codes() -> &'static [char] {
+        &[]
+    }
+
+    /// Get how the game resolves entities with the same name in a folder like `common/buildings`.
+    /// The folders haven't been checked for Victoria 3 yet.
+    pub fn get_entity_merge_mode(_folder: &str) -> EntityMergeMode {
+        EntityMergeMode::Unknown
+    }
+
     /// Detects the base directory (game or mod root) by walking up the directory tree
     /// looking for either binaries/victoria3.exe or descriptor.mod
     pub fn detect_base_directory(path: &Path) -> Option<PathBuf> {
@@ -1,6 +1,8 @@
 mod conditional;
+mod date;
 mod entity;
 mod game_mod;
+mod localisation;
 mod mod_definition;
 mod module;
 mod namespace;
@@ -9,8 +11,10 @@ mod property;
 mod value;
 
 pub use conditional::*;
+pub use date::*;
 pub use entity::*;
 pub use game_mod::*;
+pub use localisation::*;
 pub use mod_definition::*;
 pub use module::*;
 pub use namespace::*;
@@ -0,0 +1,261 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The length of each month. The games use a calendar without leap years.
+const DAYS_IN_MONTH: [u8; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+const DAYS_IN_YEAR: i64 = 365;
+
+/// An in-game date like `2200.1.1`, ordered chronologically. The month and day are always
+/// valid, which is why they can only be set through [`GameDate::new`] or parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameDate {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+/// Why a string isn't a valid date
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameDateError {
+    /// Not of the form `YYYY.MM.DD`
+    InvalidFormat,
+    InvalidMonth(u32),
+    InvalidDay {
+        month: u8,
+        day: u32,
+    },
+}
+
+impl fmt::Display for GameDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameDateError::InvalidFormat => write!(f, "Expected a date like YYYY.MM.DD"),
+            GameDateError::InvalidMonth(month) => {
+                write!(f, "Invalid month {}, expected 1 to 12", month)
+            }
+            GameDateError::InvalidDay { month, day } => write!(
+                f,
+                "Invalid day {}, month {} has {} days",
+                day,
+                month,
+                GameDate::days_in_month(*month)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GameDateError {}
+
+impl GameDate {
+    /// Create a date, checking that the month and day exist
+    pub fn new(year: i32, month: u8, day: u8) -> Result<Self, GameDateError> {
+        if !(1..=12).contains(&month) {
+            return Err(GameDateError::InvalidMonth(month as u32));
+        }
+        if day < 1 || day > Self::days_in_month(month) {
+            return Err(GameDateError::InvalidDay {
+                month,
+                day: day as u32,
+            });
+        }
+
+        Ok(Self { year, month, day })
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// The month, from 1 to 12
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// The day of the month, starting at 1
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// The number of days in a month (1-12)
+    pub fn days_in_month(month: u8) -> u8 {
+        DAYS_IN_MONTH[month.clamp(1, 12) as usize - 1]
+    }
+
+    /// The number of days since `0.1.1`
+    pub fn to_days(&self) -> i64 {
+        let days_before_month: i64 = DAYS_IN_MONTH[..self.month as usize - 1]
+            .iter()
+            .map(|days| *days as i64)
+            .sum();
+        self.year as i64 * DAYS_IN_YEAR + days_before_month + self.day as i64 - 1
+    }
+
+    /// The date a number of days after `0.1.1`
+    pub fn from_days(days: i64) -> Self {
+        let year = days.div_euclid(DAYS_IN_YEAR);
+        let mut day_of_year = days.rem_euclid(DAYS_IN_YEAR);
+
+        let mut month = 1;
+        for days_in_month in DAYS_IN_MONTH {
+            if day_of_year < days_in_month as i64 {
+                break;
+            }
+            day_of_year -= days_in_month as i64;
+            month += 1;
+        }
+
+        Self {
+            year: year as i32,
+            month,
+            day: day_of_year as u8 + 1,
+        }
+    }
+
+    /// The date a number of days later, or earlier for negative days
+    pub fn add_days(&self, days: i64) -> Self {
+        Self::from_days(self.to_days() + days)
+    }
+
+    /// The date a number of months later, or earlier for negative months. The day is clamped to
+    /// the length of the resulting month.
+    pub fn add_months(&self, months: i64) -> Self {
+        let total = self.year as i64 * 12 + (self.month as i64 - 1) + months;
+        let month = total.rem_euclid(12) as u8 + 1;
+
+        Self {
+            year: total.div_euclid(12) as i32,
+            month,
+            day: self.day.min(Self::days_in_month(month)),
+        }
+    }
+
+    /// The date a number of years later, or earlier for negative years
+    pub fn add_years(&self, years: i32) -> Self {
+        Self {
+            year: self.year + years,
+            ..*self
+        }
+    }
+
+    /// The number of days from `other` to this date, negative if `other` is later
+    pub fn days_since(&self, other: &GameDate) -> i64 {
+        self.to_days() - other.to_days()
+    }
+}
+
+impl FromStr for GameDate {
+    type Err = GameDateError;
+
+    /// Parse `YYYY.MM.DD`, with or without leading zeros in the month and day
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+        let (Some(year), Some(month), Some(day), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(GameDateError::InvalidFormat);
+        };
+
+        let number = |part: &str| {
+            if part.is_empty() || part.len() > 9 || !part.chars().all(|ch| ch.is_ascii_digit()) {
+                return Err(GameDateError::InvalidFormat);
+            }
+            part.parse::<u32>()
+                .map_err(|_| GameDateError::InvalidFormat)
+        };
+
+        let year = number(year)?;
+        let month = number(month)?;
+        let day = number(day)?;
+
+        if !(1..=12).contains(&month) {
+            return Err(GameDateError::InvalidMonth(month));
+        }
+        let month = month as u8;
+        if day < 1 || day > Self::days_in_month(month) as u32 {
+            return Err(GameDateError::InvalidDay { month, day });
+        }
+
+        Ok(Self {
+            year: year as i32,
+            month,
+            day: day as u8,
+        })
+    }
+}
+
+impl fmt::Display for GameDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}.{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dates() {
+        assert_eq!("2200.01.01".parse(), GameDate::new(2200, 1, 1));
+        assert_eq!("1836.1.1".parse(), GameDate::new(1836, 1, 1));
+        assert_eq!(
+            "2200.13.1".parse::<GameDate>(),
+            Err(GameDateError::InvalidMonth(13))
+        );
+        assert_eq!(
+            "2200.2.29".parse::<GameDate>(),
+            Err(GameDateError::InvalidDay { month: 2, day: 29 })
+        );
+        assert_eq!(
+            "2200.1".parse::<GameDate>(),
+            Err(GameDateError::InvalidFormat)
+        );
+        assert_eq!(
+            "2200.-1.1".parse::<GameDate>(),
+            Err(GameDateError::InvalidFormat)
+        );
+        assert_eq!(GameDate::new(2200, 1, 5).unwrap().to_string(), "2200.01.05");
+    }
+
+    #[test]
+    fn test_date_arithmetic() {
+        let start = GameDate::new(2200, 1, 1).unwrap();
+
+        assert_eq!(start.add_days(365), GameDate::new(2201, 1, 1).unwrap());
+        assert_eq!(start.add_days(59), GameDate::new(2200, 3, 1).unwrap());
+        assert_eq!(start.add_days(-1), GameDate::new(2199, 12, 31).unwrap());
+        assert_eq!(
+            GameDate::new(2200, 1, 31).unwrap().add_months(1),
+            GameDate::new(2200, 2, 28).unwrap()
+        );
+        assert_eq!(start.add_months(-1), GameDate::new(2199, 12, 1).unwrap());
+        assert_eq!(start.add_years(25), GameDate::new(2225, 1, 1).unwrap());
+        assert_eq!(
+            GameDate::new(2201, 3, 1).unwrap().days_since(&start),
+            365 + 59
+        );
+
+        assert!(start < GameDate::new(2200, 1, 2).unwrap());
+        assert!(GameDate::new(2200, 12, 1).unwrap() < GameDate::new(2201, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_dates_cannot_be_built() {
+        assert_eq!(
+            GameDate::new(2200, 0, 1),
+            Err(GameDateError::InvalidMonth(0))
+        );
+        assert_eq!(
+            GameDate::new(2200, 1, 0),
+            Err(GameDateError::InvalidDay { month: 1, day: 0 })
+        );
+        assert_eq!(
+            "2200.0.1".parse::<GameDate>(),
+            Err(GameDateError::InvalidMonth(0))
+        );
+
+        let date = GameDate::new(2200, 12, 31).unwrap();
+        assert_eq!((date.year(), date.month(), date.day()), (2200, 12, 31));
+        assert_eq!(GameDate::from_days(date.to_days()), date);
+    }
+}
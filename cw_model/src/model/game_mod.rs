@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use anyhow::anyhow;
@@ -22,6 +23,24 @@ pub struct GameModLoadResult {
     pub errors: Vec<anyhow::Error>,
 }
 
+/// Files that are never loaded as modules (simple filename matching)
+const IGNORED_FILENAMES: &[&str] = &[
+    "99_README.txt",
+    "HOW_TO_MAKE_NEW_SHIPS.txt",
+    "readme.txt",
+    "Readme.txt",
+    "changelog.txt",
+    "CHANGELOG.txt",
+    "ChangeLog.txt",
+    "license.txt",
+    "LICENSE.txt",
+    "credits.txt",
+    "CREDITS.txt",
+    "TODO.txt",
+    "todo.txt",
+    "info.txt",
+];
+
 pub enum LoadMode {
     Serial,
     Parallel,
@@ -62,6 +81,27 @@ impl GameMod {
         self.namespaces.get(namespace)
     }
 
+    /// Remove a module from its namespace, e.g. when its file was deleted or is about to be
+    /// replaced by a newer version
+    pub fn remove_module(&mut self, namespace: &str, module_name: &str) -> Option<Arc<Module>> {
+        self.namespaces.get_mut(namespace)?.remove(module_name)
+    }
+
+    /// Check if a file, relative to the mod root, would be loaded as a module by [`GameMod::load`]
+    pub fn is_module_path(relative_path: &str, glob_patterns: &[&str]) -> bool {
+        let relative_path = relative_path.replace('\\', "/");
+
+        let is_ignored = Path::new(&relative_path)
+            .file_name()
+            .and_then(|filename| filename.to_str())
+            .is_some_and(|filename| IGNORED_FILENAMES.contains(&filename));
+
+        !is_ignored
+            && glob_patterns.iter().any(|pattern| {
+                Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(&relative_path))
+            })
+    }
+
     fn parse_serial(
         paths: &Vec<PathBuf>,
         interner: &CaseInsensitiveInterner,
@@ -106,23 +146,7 @@ impl GameMod {
             }
         };
 
-        // Define ignore patterns for files to exclude (simple filename matching)
-        let ignore_filenames = vec![
-            "99_README.txt",
-            "HOW_TO_MAKE_NEW_SHIPS.txt",
-            "readme.txt",
-            "Readme.txt",
-            "changelog.txt",
-            "CHANGELOG.txt",
-            "ChangeLog.txt",
-            "license.txt",
-            "LICENSE.txt",
-            "credits.txt",
-            "CREDITS.txt",
-            "TODO.txt",
-            "todo.txt",
-            "info.txt",
-        ];
+        let ignore_filenames = IGNORED_FILENAMES;
 
         let mut paths = vec![];
 
@@ -252,3 +276,121 @@ impl GameMod {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cw_parser::{AstModuleCell, AstVisitor};
+
+    use super::*;
+    use crate::ModuleVisitor;
+
+    fn module(
+        namespace: &str,
+        filename: &str,
+        input: &str,
+        interner: &CaseInsensitiveInterner,
+    ) -> Module {
+        let mut module = Module::new(namespace.to_string(), filename.to_string());
+        let ast = AstModuleCell::from_input(input.to_string());
+        ModuleVisitor::new(&mut module, interner)
+            .visit_module(ast.borrow_dependent().as_ref().unwrap());
+        module
+    }
+
+    #[test]
+    fn test_remove_module() {
+        let interner = CaseInsensitiveInterner::new();
+        let mut game_mod = GameMod::new();
+        game_mod.push(module(
+            "common/buildings",
+            "00_a.txt",
+            "building_a = {}",
+            &interner,
+        ));
+        game_mod.push(module(
+            "common/buildings",
+            "01_b.txt",
+            "building_b = {}",
+            &interner,
+        ));
+        game_mod.push(module(
+            "common/technology",
+            "00_a.txt",
+            "tech_a = {}",
+            &interner,
+        ));
+
+        let removed = game_mod.remove_module("common/buildings", "00_a.txt");
+        assert_eq!(removed.unwrap().namespace, "common/buildings");
+
+        let buildings = game_mod.get_namespace("common/buildings").unwrap();
+        assert!(buildings.get_module("00_a.txt").is_none());
+        assert!(
+            !buildings
+                .properties
+                .kv
+                .contains_key(&interner.get_or_intern("building_a"))
+        );
+        assert!(
+            buildings
+                .properties
+                .kv
+                .contains_key(&interner.get_or_intern("building_b"))
+        );
+
+        // A module with the same name in another namespace is left alone
+        let technology = game_mod.get_namespace("common/technology").unwrap();
+        assert!(technology.get_module("00_a.txt").is_some());
+    }
+
+    #[test]
+    fn test_remove_module_unknown() {
+        let interner = CaseInsensitiveInterner::new();
+        let mut game_mod = GameMod::with_module(module(
+            "common/buildings",
+            "00_a.txt",
+            "building_a = {}",
+            &interner,
+        ));
+
+        assert!(
+            game_mod
+                .remove_module("common/technology", "00_a.txt")
+                .is_none()
+        );
+        assert!(
+            game_mod
+                .remove_module("common/buildings", "missing.txt")
+                .is_none()
+        );
+        assert!(
+            game_mod
+                .get_namespace("common/buildings")
+                .unwrap()
+                .get_module("00_a.txt")
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_is_module_path() {
+        let glob_patterns = ["common/**/*.txt", "events/**/*.txt"];
+
+        assert!(GameMod::is_module_path(
+            "common/technology/00_tech.txt",
+            &glob_patterns
+        ));
+        assert!(GameMod::is_module_path(
+            "events\\my_events.txt",
+            &glob_patterns
+        ));
+        assert!(!GameMod::is_module_path(
+            "common/technology/readme.txt",
+            &glob_patterns
+        ));
+        assert!(!GameMod::is_module_path(
+            "localisation/english/foo_l_english.yml",
+            &glob_patterns
+        ));
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use cw_parser::ParseError;
+use cw_parser::localisation::AstLocalisationFile;
+use lasso::Spur;
+use walkdir::WalkDir;
+
+use crate::{CaseInsensitiveInterner, SpurMap};
+
+/// A localisation key defined in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalisationEntry {
+    /// The text with escape sequences resolved
+    pub text: String,
+    pub path: PathBuf,
+    /// The span of the key within the file
+    pub span: Range<usize>,
+    /// The line of the key within the file (0-based)
+    pub line: usize,
+    /// Whether the file is in a `replace` folder, which overrides every other definition
+    pub replace: bool,
+}
+
+/// Localisation keys and their text per language, across the base game and mods.
+///
+/// Files have to be added in load order, base game first. Like the game, the first definition of
+/// a key wins, except that definitions in a `localisation/replace` folder override everything
+/// else, with the last one loaded winning.
+#[derive(Debug, Clone)]
+pub struct LocalisationIndex {
+    /// The folder the game keeps localisation in, `localisation` or `localization`
+    folder: &'static str,
+    /// language -> key -> every definition of the key, in load order
+    languages: HashMap<String, SpurMap<Vec<LocalisationEntry>>>,
+}
+
+impl LocalisationIndex {
+    pub fn new(folder: &'static str) -> Self {
+        Self {
+            folder,
+            languages: HashMap::new(),
+        }
+    }
+
+    /// Add all localisation files under the localisation folder of `root`, in the order the game
+    /// loads them. With `languages`, files whose name is for another language are skipped.
+    pub fn load_directory(
+        &mut self,
+        root: &Path,
+        languages: Option<&[String]>,
+        interner: &CaseInsensitiveInterner,
+    ) -> Vec<anyhow::Error> {
+        let mut errors = Vec::new();
+
+        let mut paths: Vec<PathBuf> = WalkDir::new(root.join(self.folder))
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|path| path.is_file() && is_localisation_file(path, self.folder))
+            .filter(|path| match (languages, language_from_filename(path)) {
+                (Some(languages), Some(language)) => languages.contains(&language),
+                _ => true,
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    errors.push(anyhow::anyhow!("Failed to read {}: {}", path.display(), e));
+                    continue;
+                }
+            };
+
+            for error in self.add_file(&path, &content, interner) {
+                errors.push(anyhow::anyhow!("{}: {}", path.display(), error));
+            }
+        }
+
+        errors
+    }
+
+    /// Add the keys of a localisation file, returning the lines that couldn't be parsed
+    pub fn add_file(
+        &mut self,
+        path: &Path,
+        content: &str,
+        interner: &CaseInsensitiveInterner,
+    ) -> Vec<ParseError> {
+        let file = AstLocalisationFile::from_input(content);
+
+        let Some(language) = file
+            .language()
+            .map(str::to_string)
+            .or_else(|| language_from_filename(path))
+        else {
+            return file.errors;
+        };
+
+        let replace = is_replace_path(path, self.folder);
+        let keys = self.languages.entry(language).or_default();
+
+        let mut line_starts = vec![0];
+        line_starts.extend(content.match_indices('\n').map(|(index, _)| index + 1));
+
+        for entry in &file.entries {
+            let line = line_starts.partition_point(|start| *start <= entry.key_span.start) - 1;
+            keys.entry(interner.get_or_intern(entry.key))
+                .or_default()
+                .push(LocalisationEntry {
+                    text: entry.text().into_owned(),
+                    path: path.to_path_buf(),
+                    span: entry.key_span.clone(),
+                    line,
+                    replace,
+                });
+        }
+
+        file.errors
+    }
+
+    /// Remove every key defined in a file, e.g. before adding it again after it changed
+    pub fn remove_file(&mut self, path: &Path) {
+        for keys in self.languages.values_mut() {
+            keys.retain(|_, entries| {
+                entries.retain(|entry| entry.path != path);
+                !entries.is_empty()
+            });
+        }
+    }
+
+    /// Add the keys of another index after the keys of this one, as if its files were loaded
+    /// after every file of this index
+    pub fn extend(&mut self, other: LocalisationIndex) {
+        for (language, other_keys) in other.languages {
+            let keys = self.languages.entry(language).or_default();
+            for (key, entries) in other_keys.iter() {
+                keys.entry(key).or_default().extend(entries.iter().cloned());
+            }
+        }
+    }
+
+    /// Remove every key defined in a file under `root`, e.g. before loading a mod again
+    pub fn remove_root(&mut self, root: &Path) {
+        for keys in self.languages.values_mut() {
+            keys.retain(|_, entries| {
+                entries.retain(|entry| !entry.path.starts_with(root));
+                !entries.is_empty()
+            });
+        }
+    }
+
+    /// Get the definition of a key that the game would use for a language
+    pub fn get(&self, language: &str, key: Spur) -> Option<&LocalisationEntry> {
+        let entries = self.languages.get(language)?.get(&key)?;
+
+        entries
+            .iter()
+            .rev()
+            .find(|entry| entry.replace)
+            .or_else(|| entries.first())
+    }
+
+    /// Get every definition of a key for a language, in load order
+    pub fn get_all(&self, language: &str, key: Spur) -> &[LocalisationEntry] {
+        self.languages
+            .get(language)
+            .and_then(|keys| keys.get(&key))
+            .map(|entries| entries.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Check if a key is defined in any language
+    pub fn contains_key(&self, key: Spur) -> bool {
+        self.languages.values().any(|keys| keys.contains_key(&key))
+    }
+
+    /// Get the languages that have at least one key
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.languages.keys().map(|language| language.as_str())
+    }
+
+    /// Get all keys defined for a language
+    pub fn keys(&self, language: &str) -> impl Iterator<Item = Spur> + '_ {
+        self.languages
+            .get(language)
+            .into_iter()
+            .flat_map(|keys| keys.keys())
+    }
+}
+
+/// Check if a path is a localisation file, e.g. `localisation/english/foo_l_english.yml` where
+/// `folder` is the game's localisation folder
+pub fn is_localisation_file(path: &Path, folder: &str) -> bool {
+    path.extension().is_some_and(|extension| extension == "yml")
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.rsplit_once("_l_"))
+            .is_some_and(|(_, language)| !language.is_empty())
+        && path
+            .components()
+            .rev()
+            .skip(1)
+            .any(|component| component.as_os_str() == folder)
+}
+
+/// Get the language from a file name like `foo_l_english.yml`
+pub fn language_from_filename(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let (_, language) = stem.rsplit_once("l_")?;
+    Some(language.to_string())
+}
+
+/// Check if a file is in a `replace` folder of the localisation folder `folder`, e.g.
+/// `localisation/replace/` or `localisation/english/replace/`
+pub fn is_replace_path(path: &Path, folder: &str) -> bool {
+    path.components()
+        .rev()
+        .skip(1)
+        .take_while(|component| component.as_os_str() != folder)
+        .any(|component| component.as_os_str() == "replace")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_overrides_load_order() {
+        let interner = CaseInsensitiveInterner::new();
+        let mut index = LocalisationIndex::new("localisation");
+        let key = interner.get_or_intern("tech_lasers_1");
+
+        index.add_file(
+            Path::new("/game/localisation/english/technology_l_english.yml"),
+            "l_english:\n tech_lasers_1:0 \"Red Lasers\"\n",
+            &interner,
+        );
+        index.add_file(
+            Path::new("/mod/localisation/english/replace/lasers_l_english.yml"),
+            "l_english:\n tech_lasers_1:0 \"Better Lasers\"\n",
+            &interner,
+        );
+        index.add_file(
+            Path::new("/mod/localisation/english/lasers_l_english.yml"),
+            "l_english:\n tech_lasers_1:0 \"Ignored Lasers\"\n",
+            &interner,
+        );
+
+        assert_eq!(index.get("english", key).unwrap().text, "Better Lasers");
+        assert_eq!(index.get_all("english", key).len(), 3);
+        assert!(index.get("german", key).is_none());
+
+        index.remove_file(Path::new(
+            "/mod/localisation/english/replace/lasers_l_english.yml",
+        ));
+        assert_eq!(index.get("english", key).unwrap().text, "Red Lasers");
+    }
+
+    #[test]
+    fn test_first_definition_wins() {
+        let interner = CaseInsensitiveInterner::new();
+        let mut index = LocalisationIndex::new("localisation");
+        let key = interner.get_or_intern("KEY");
+
+        index.add_file(
+            Path::new("/game/localisation/a_l_english.yml"),
+            "l_english:\n\n KEY:0 \"first\"\n",
+            &interner,
+        );
+        index.add_file(
+            Path::new("/game/localisation/b_l_english.yml"),
+            "l_english:\n KEY:0 \"second\"\n",
+            &interner,
+        );
+
+        let entry = index.get("english", key).unwrap();
+        assert_eq!(entry.text, "first");
+        assert_eq!(entry.line, 2);
+        assert!(index.contains_key(key));
+    }
+
+    #[test]
+    fn test_language_from_filename() {
+        assert_eq!(
+            language_from_filename(Path::new("events_l_braz_por.yml")),
+            Some("braz_por".to_string())
+        );
+        assert!(is_replace_path(
+            Path::new("mod/localisation/replace/english/foo_l_english.yml"),
+            "localisation"
+        ));
+        assert!(!is_replace_path(
+            Path::new("replace/localisation/english/foo_l_english.yml"),
+            "localisation"
+        ));
+        assert!(is_replace_path(
+            Path::new("game/localization/replace/foo_l_english.yml"),
+            "localization"
+        ));
+    }
+
+    #[test]
+    fn test_is_localisation_file() {
+        assert!(is_localisation_file(
+            Path::new("mod/localisation/english/foo_l_english.yml"),
+            "localisation"
+        ));
+        assert!(is_localisation_file(
+            Path::new("game/localization/english/foo_l_english.yml"),
+            "localization"
+        ));
+        assert!(!is_localisation_file(
+            Path::new("game/localization/english/foo_l_english.yml"),
+            "localisation"
+        ));
+        assert!(!is_localisation_file(
+            Path::new("mod/.github/workflows/ci.yml"),
+            "localisation"
+        ));
+        assert!(!is_localisation_file(
+            Path::new("mod/localisation/notes.yml"),
+            "localisation"
+        ));
+        assert!(!is_localisation_file(
+            Path::new("mod/localisation/english/foo_l_english.txt"),
+            "localisation"
+        ));
+    }
+}
@@ -44,6 +44,27 @@ impl Namespace {
         self
     }
 
+    /// Remove a module, rebuilding the combined properties and values from the remaining modules
+    pub fn remove(&mut self, module_name: &str) -> Option<Arc<Module>> {
+        let removed = self.modules.remove(module_name)?;
+
+        let mut module_names: Vec<&String> = self.modules.keys().collect();
+        module_names.sort();
+
+        let mut properties = Properties::new_module();
+        let mut values = Vec::new();
+        for module_name in module_names {
+            let module = &self.modules[module_name];
+            properties.kv.extend(module.properties.kv.clone());
+            values.extend(module.values.iter().cloned());
+        }
+
+        self.properties = Arc::new(properties);
+        self.values = values;
+
+        Some(removed)
+    }
+
     pub fn get_module(&self, module_name: &str) -> Option<&Arc<Module>> {
         self.modules.get(module_name)
     }
@@ -57,3 +78,88 @@ impl Namespace {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cw_parser::{AstModuleCell, AstVisitor};
+
+    use super::*;
+    use crate::{CaseInsensitiveInterner, ModuleVisitor};
+
+    fn module(filename: &str, input: &str, interner: &CaseInsensitiveInterner) -> Module {
+        let mut module = Module::new("game/common/buildings".to_string(), filename.to_string());
+        let ast = AstModuleCell::from_input(input.to_string());
+        ModuleVisitor::new(&mut module, interner)
+            .visit_module(ast.borrow_dependent().as_ref().unwrap());
+        module
+    }
+
+    #[test]
+    fn test_remove_rebuilds_properties_and_values() {
+        let interner = CaseInsensitiveInterner::new();
+        let mut namespace = Namespace::new("game/common/buildings", None);
+        namespace.insert(module("00_a.txt", "building_a = {}\nflag_a", &interner));
+        namespace.insert(module("01_b.txt", "building_b = {}\nflag_b", &interner));
+
+        let removed = namespace.remove("00_a.txt").unwrap();
+        assert_eq!(removed.filename, "00_a.txt");
+
+        assert!(namespace.get_module("00_a.txt").is_none());
+        assert!(
+            !namespace
+                .properties
+                .kv
+                .contains_key(&interner.get_or_intern("building_a"))
+        );
+        assert!(
+            namespace
+                .properties
+                .kv
+                .contains_key(&interner.get_or_intern("building_b"))
+        );
+        assert_eq!(
+            namespace.values,
+            vec![Arc::new(Value::String(interner.get_or_intern("flag_b")))]
+        );
+    }
+
+    #[test]
+    fn test_remove_keeps_redefined_keys_of_other_modules() {
+        let interner = CaseInsensitiveInterner::new();
+        let mut namespace = Namespace::new("game/common/buildings", None);
+        namespace.insert(module("00_a.txt", "building_a = { cost = 1 }", &interner));
+        namespace.insert(module("01_b.txt", "building_a = { cost = 2 }", &interner));
+
+        namespace.remove("01_b.txt");
+
+        let building_a = namespace
+            .properties
+            .kv
+            .get(&interner.get_or_intern("building_a"))
+            .unwrap();
+        let remaining = namespace.get_module("00_a.txt").unwrap();
+        assert_eq!(
+            building_a,
+            remaining
+                .properties
+                .kv
+                .get(&interner.get_or_intern("building_a"))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_remove_unknown_module() {
+        let interner = CaseInsensitiveInterner::new();
+        let mut namespace = Namespace::new("game/common/buildings", None);
+        namespace.insert(module("00_a.txt", "building_a = {}", &interner));
+
+        assert!(namespace.remove("missing.txt").is_none());
+        assert!(
+            namespace
+                .properties
+                .kv
+                .contains_key(&interner.get_or_intern("building_a"))
+        );
+    }
+}
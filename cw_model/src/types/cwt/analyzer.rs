@@ -3,7 +3,7 @@
 //! This is the main entry point for CWT analysis, using a visitor pattern
 //! with specialized visitors for different CWT constructs.
 
-use crate::{AliasPattern, CaseInsensitiveInterner, CwtType, SpurMap};
+use crate::{AliasPattern, CaseInsensitiveInterner, CwtType, Modifier, SpurMap};
 
 use super::conversion::ConversionError;
 use super::definitions::*;
@@ -153,6 +153,16 @@ impl CwtAnalyzer {
         self.data.single_aliases.get(&name)
     }
 
+    /// Get all modifiers defined in the config
+    pub fn get_modifiers(&self) -> &SpurMap<Modifier> {
+        &self.data.modifiers
+    }
+
+    /// Get a specific modifier category
+    pub fn get_modifier_category(&self, name: Spur) -> Option<&ModifierCategoryDefinition> {
+        self.data.modifier_categories.get(&name)
+    }
+
     /// Get a specific link definition
     pub fn get_link(&self, name: Spur) -> Option<&LinkDefinition> {
         self.data.links.get(&name)
@@ -228,6 +238,10 @@ impl CwtAnalyzer {
         self.data.aliases.extend(other.data.aliases);
         self.data.single_aliases.extend(other.data.single_aliases);
         self.data.links.extend(other.data.links);
+        self.data.modifiers.extend(other.data.modifiers);
+        self.data
+            .modifier_categories
+            .extend(other.data.modifier_categories);
         self.data.errors.extend(other.data.errors);
 
         // Rebuild category index after merging
@@ -3,7 +3,7 @@
 //! This module contains utilities for converting between CWT AST types and our
 //! CwtType system, as well as error types for conversion failures.
 
-use crate::{CwtType, SimpleType};
+use crate::{CwtType, Range, SimpleType};
 
 use cw_parser::cwt::{CwtSimpleValue, CwtSimpleValueType};
 
@@ -84,6 +84,10 @@ impl ConversionUtils {
             CwtSimpleValueType::Icon => SimpleType::Icon,
         };
 
-        CwtType::Simple(primitive_type)
+        // Numeric values like `int[0..inf]` carry their range inline
+        match &simple.range {
+            Some(range) => CwtType::Ranged(primitive_type, Range::from_cwt(range)),
+            None => CwtType::Simple(primitive_type),
+        }
     }
 }
@@ -30,6 +30,9 @@ pub struct TypeDefinition {
     /// Localisation requirements
     pub localisation: SpurMap<LocalisationRequirement>,
 
+    /// Localisation requirements that only apply to a subtype, subtype -> key -> requirement
+    pub subtype_localisation: SpurMap<SpurMap<LocalisationRequirement>>,
+
     /// Modifiers generated by this type
     pub modifiers: ModifierSpec,
 
@@ -219,6 +222,15 @@ pub struct ScopeGroupDefinition {
     pub members: Vec<Spur>,
 }
 
+/// Definition of a CWT modifier category, e.g. `Pops` or `Military Ships`
+#[derive(Debug, Clone)]
+pub struct ModifierCategoryDefinition {
+    /// Name of the category
+    pub name: Spur,
+    /// Scopes that modifiers of this category apply to
+    pub supported_scopes: Vec<Spur>,
+}
+
 /// Configuration for complex enums
 #[derive(Debug, Clone)]
 pub struct ComplexEnumDefinition {
@@ -296,6 +308,7 @@ impl TypeDefinition {
             skip_root_key: None,
             subtypes: SpurMap::new(),
             localisation: SpurMap::new(),
+            subtype_localisation: SpurMap::new(),
             modifiers: ModifierSpec {
                 modifiers: SpurMap::new(),
                 subtypes: SpurMap::new(),
@@ -402,6 +415,9 @@ impl TypeDefinition {
         for (key, localisation) in other.localisation {
             self.localisation.insert(key, localisation);
         }
+        for (key, subtype_localisation) in other.subtype_localisation {
+            self.subtype_localisation.insert(key, subtype_localisation);
+        }
 
         // Merge modifiers
         for (key, modifier) in other.modifiers.modifiers {
@@ -590,6 +606,16 @@ impl ScopeDefinition {
     }
 }
 
+impl ModifierCategoryDefinition {
+    /// Create a new modifier category definition
+    pub fn new(name: Spur, supported_scopes: Vec<Spur>) -> Self {
+        Self {
+            name,
+            supported_scopes,
+        }
+    }
+}
+
 impl ScopeGroupDefinition {
     /// Create a new scope group definition
     pub fn new(name: Spur, members: Vec<Spur>) -> Self {
@@ -158,8 +158,22 @@ impl<'a, 'interner> AliasVisitor<'a, 'interner> {
                 }
             };
 
-            // Merge options - could be more sophisticated, but for now just use the latest
+            // Merge options - could be more sophisticated, but for now just use the latest.
+            // Scopes are the exception: each definition is valid in its own scopes, and a
+            // definition without a scope constraint makes the alias valid everywhere
+            let scope = match (existing_def.options.scope.take(), &options.scope) {
+                (Some(mut scopes), Some(new_scopes)) => {
+                    for scope in new_scopes {
+                        if !scopes.contains(scope) {
+                            scopes.push(*scope);
+                        }
+                    }
+                    Some(scopes)
+                }
+                _ => None,
+            };
             existing_def.options = options;
+            existing_def.options.scope = scope;
         } else {
             // First definition, insert as normal
             let alias_def = AliasDefinition {
@@ -237,4 +251,41 @@ alias[trigger:exists] = scope[any]
 
         visitor.visit_module(&module);
     }
+
+    #[test]
+    fn overloaded_alias_scopes_are_merged() {
+        let mut data = CwtAnalysisData::new();
+        let interner = CaseInsensitiveInterner::new();
+        let mut visitor = AliasVisitor::new(&mut data, &interner);
+
+        let cwt_text = r#"
+## scope = country
+alias[effect:add_resource] = { resource = int }
+## scope = { planet ship }
+alias[effect:add_resource] = { resource = float }
+## scope = country
+alias[effect:set_name] = scalar
+alias[effect:set_name] = { key = scalar }
+        "#;
+
+        let module = CwtModule::from_input(cwt_text).unwrap();
+        visitor.visit_module(&module);
+
+        let effect = interner.get_or_intern("effect");
+        let add_resource =
+            AliasPattern::new_basic(effect, interner.get_or_intern("add_resource"), &interner);
+        let scopes: Vec<&str> = data.aliases[&add_resource]
+            .options
+            .scope
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|scope| interner.resolve(scope))
+            .collect();
+        assert_eq!(scopes, vec!["country", "planet", "ship"]);
+
+        let set_name =
+            AliasPattern::new_basic(effect, interner.get_or_intern("set_name"), &interner);
+        assert!(data.aliases[&set_name].options.scope.is_none());
+    }
 }
@@ -14,7 +14,7 @@ use std::sync::Arc;
 
 use crate::{
     BlockType, CaseInsensitiveInterner, CwtOptions, CwtType, PatternProperty, PatternType,
-    Property, ReferenceType, SimpleType, SpurMap,
+    Property, Range, ReferenceType, SimpleType, SpurMap,
 };
 
 /// Converter for CWT values to CwtType
@@ -42,7 +42,11 @@ impl CwtConverter {
             CwtSimpleValueType::Icon => SimpleType::Icon,
         };
 
-        CwtType::Simple(primitive_type)
+        // Numeric values like `int[0..inf]` carry their range inline
+        match &simple.range {
+            Some(range) => CwtType::Ranged(primitive_type, Range::from_cwt(range)),
+            None => CwtType::Simple(primitive_type),
+        }
     }
 
     /// Convert a CWT identifier to our type system
@@ -294,7 +298,10 @@ impl CwtConverter {
 
                         let unified_property = Property {
                             property_type: Arc::new(union_type),
-                            options: CwtOptions::default(),
+                            options: existing_property
+                                .options
+                                .clone()
+                                .merge(property_def.options),
                             // If multiple union properties have documentation... we'll take the first one
                             documentation: property_def
                                 .documentation
@@ -7,6 +7,7 @@ pub mod alias_visitor;
 pub mod converter;
 pub mod enum_visitor;
 pub mod links_visitor;
+pub mod modifiers_visitor;
 pub mod registry;
 pub mod rule_visitor;
 pub mod scopes_visitor;
@@ -18,6 +19,7 @@ pub use alias_visitor::AliasVisitor;
 pub use converter::CwtConverter;
 pub use enum_visitor::EnumVisitor;
 pub use links_visitor::LinksVisitor;
+pub use modifiers_visitor::ModifiersVisitor;
 pub use registry::{CwtAnalysisData, CwtVisitorRegistry};
 pub use rule_visitor::RuleVisitor;
 pub use scopes_visitor::ScopesVisitor;
@@ -0,0 +1,221 @@
+//! Specialized visitor for CWT modifiers definitions
+//!
+//! This visitor handles the `modifiers` section of the config, which lists the modifiers the game
+//! defines and the category of each, e.g. `country_admin_cap_add = Countries`, and the
+//! `modifier_categories` section, which lists the scopes each category applies to.
+
+use cw_parser::{AstCwtRule, CwtValue, CwtVisitor};
+
+use crate::{
+    CaseInsensitiveInterner, ConversionError, CwtAnalysisData, Modifier, ModifierCategoryDefinition,
+};
+
+/// Specialized visitor for modifiers definitions
+pub struct ModifiersVisitor<'a, 'interner> {
+    data: &'a mut CwtAnalysisData,
+    interner: &'interner CaseInsensitiveInterner,
+}
+
+impl<'a, 'interner> ModifiersVisitor<'a, 'interner> {
+    /// Create a new modifiers visitor
+    pub fn new(
+        data: &'a mut CwtAnalysisData,
+        interner: &'interner CaseInsensitiveInterner,
+    ) -> Self {
+        Self { data, interner }
+    }
+
+    /// Check if this visitor can handle the given rule
+    fn can_handle_rule(&self, rule: &AstCwtRule) -> bool {
+        let key = rule.key.name();
+        key == "modifiers" || key == "modifier_categories"
+    }
+
+    /// Process a modifiers or modifier_categories section
+    fn process_section(&mut self, rule: &AstCwtRule) {
+        match rule.key.name() {
+            "modifiers" => self.process_modifiers_section(rule),
+            "modifier_categories" => self.process_modifier_categories_section(rule),
+            _ => {}
+        }
+    }
+
+    /// Process a modifiers section
+    fn process_modifiers_section(&mut self, rule: &AstCwtRule) {
+        if let CwtValue::Block(block) = &rule.value {
+            for item in &block.items {
+                if let cw_parser::cwt::AstCwtExpression::Rule(modifier_rule) = item {
+                    self.process_modifier_definition(modifier_rule);
+                }
+            }
+        }
+    }
+
+    /// Process a single modifier definition
+    fn process_modifier_definition(&mut self, rule: &AstCwtRule) {
+        let CwtValue::String(category) = &rule.value else {
+            return;
+        };
+
+        let name = self.interner.get_or_intern(rule.key.name());
+        let category = self.interner.get_or_intern(category.raw_value());
+
+        // A modifier listed more than once belongs to all of its categories
+        let modifier = self
+            .data
+            .modifiers
+            .entry(name)
+            .or_insert_with(|| Modifier::new(name, Vec::new()));
+        if !modifier.categories.contains(&category) {
+            modifier.categories.push(category);
+        }
+    }
+
+    /// Process a modifier_categories section
+    fn process_modifier_categories_section(&mut self, rule: &AstCwtRule) {
+        if let CwtValue::Block(block) = &rule.value {
+            for item in &block.items {
+                if let cw_parser::cwt::AstCwtExpression::Rule(category_rule) = item {
+                    self.process_modifier_category_definition(category_rule);
+                }
+            }
+        }
+    }
+
+    /// Process a single modifier category definition
+    fn process_modifier_category_definition(&mut self, rule: &AstCwtRule) {
+        let category_name = self.interner.get_or_intern(rule.key.name());
+
+        let CwtValue::Block(block) = &rule.value else {
+            self.data
+                .errors
+                .push(ConversionError::InvalidRuleDefinition(format!(
+                    "Modifier category '{}' must have a block value",
+                    self.interner.resolve(&category_name)
+                )));
+            return;
+        };
+
+        let mut supported_scopes = Vec::new();
+        for item in &block.items {
+            if let cw_parser::cwt::AstCwtExpression::Rule(prop_rule) = item
+                && prop_rule.key.name() == "supported_scopes"
+                && let CwtValue::Block(scopes) = &prop_rule.value
+            {
+                for scope in &scopes.items {
+                    if let cw_parser::cwt::AstCwtExpression::Value(CwtValue::String(scope)) = scope
+                    {
+                        supported_scopes.push(self.interner.get_or_intern(scope.raw_value()));
+                    }
+                }
+            }
+        }
+
+        self.data.modifier_categories.insert(
+            category_name,
+            ModifierCategoryDefinition::new(category_name, supported_scopes),
+        );
+    }
+}
+
+impl<'a, 'interner> CwtVisitor<'a> for ModifiersVisitor<'a, 'interner> {
+    fn visit_rule(&mut self, rule: &AstCwtRule<'a>) {
+        if self.can_handle_rule(rule) {
+            self.process_section(rule);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cw_parser::CwtModule;
+
+    #[test]
+    fn test_modifiers_visitor() {
+        let mut data = CwtAnalysisData::new();
+        let interner = CaseInsensitiveInterner::new();
+        let mut visitor = ModifiersVisitor::new(&mut data, &interner);
+
+        let cwt_text = r#"
+modifiers = {
+    country_admin_cap_add = country
+    pop_happiness = pop
+    pop_happiness = planet
+}
+        "#;
+
+        let module = CwtModule::from_input(cwt_text).unwrap();
+        let modifiers_rule = module.find_rule("modifiers").unwrap();
+
+        visitor.visit_rule(modifiers_rule);
+
+        assert_eq!(data.modifiers.len(), 2);
+
+        let admin_cap = data
+            .modifiers
+            .get(&interner.get_or_intern("country_admin_cap_add"))
+            .unwrap();
+        assert_eq!(
+            admin_cap.categories,
+            vec![interner.get_or_intern("country")]
+        );
+
+        let happiness = data
+            .modifiers
+            .get(&interner.get_or_intern("pop_happiness"))
+            .unwrap();
+        assert_eq!(
+            happiness.categories,
+            vec![
+                interner.get_or_intern("pop"),
+                interner.get_or_intern("planet")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modifier_categories_visitor() {
+        let mut data = CwtAnalysisData::new();
+        let interner = CaseInsensitiveInterner::new();
+        let mut visitor = ModifiersVisitor::new(&mut data, &interner);
+
+        let cwt_text = r#"
+modifier_categories = {
+    Pops = {
+        supported_scopes = { species pop planet country }
+    }
+    "Military Ships" = {
+        supported_scopes = { ship fleet country }
+    }
+}
+        "#;
+
+        let module = CwtModule::from_input(cwt_text).unwrap();
+        let categories_rule = module.find_rule("modifier_categories").unwrap();
+
+        visitor.visit_rule(categories_rule);
+
+        assert_eq!(data.modifier_categories.len(), 2);
+
+        let pops = data
+            .modifier_categories
+            .get(&interner.get_or_intern("Pops"))
+            .unwrap();
+        assert_eq!(
+            pops.supported_scopes,
+            vec![
+                interner.get_or_intern("species"),
+                interner.get_or_intern("pop"),
+                interner.get_or_intern("planet"),
+                interner.get_or_intern("country")
+            ]
+        );
+
+        let military_ships = data
+            .modifier_categories
+            .get(&interner.get_or_intern("Military Ships"))
+            .unwrap();
+        assert_eq!(military_ships.supported_scopes.len(), 3);
+    }
+}
@@ -3,13 +3,13 @@
 //! This module provides the main coordinator for all CWT visitors, determining which
 //! visitor should handle each rule based on its type and context.
 
-use crate::{AliasPattern, CaseInsensitiveInterner, CwtType, SpurMap};
+use crate::{AliasPattern, CaseInsensitiveInterner, CwtType, Modifier, SpurMap};
 
 use super::super::conversion::ConversionError;
 use super::super::definitions::*;
 use super::{
-    AliasVisitor, EnumVisitor, LinksVisitor, RuleVisitor, ScopesVisitor, TypeVisitor,
-    ValueSetVisitor,
+    AliasVisitor, EnumVisitor, LinksVisitor, ModifiersVisitor, RuleVisitor, ScopesVisitor,
+    TypeVisitor, ValueSetVisitor,
 };
 use cw_parser::cwt::{
     AstCwtIdentifierOrString, AstCwtRule, CwtModule, CwtReferenceType, CwtValue, CwtVisitor,
@@ -46,6 +46,12 @@ pub struct CwtAnalysisData {
     /// Known scope groups registry
     pub scope_groups: SpurMap<ScopeGroupDefinition>,
 
+    /// Known modifiers registry, with the categories each belongs to
+    pub modifiers: SpurMap<Modifier>,
+
+    /// Known modifier categories registry
+    pub modifier_categories: SpurMap<ModifierCategoryDefinition>,
+
     /// Errors encountered during conversion
     pub errors: Vec<ConversionError>,
 }
@@ -64,6 +70,8 @@ impl CwtAnalysisData {
             links: SpurMap::new(),
             scopes: SpurMap::new(),
             scope_groups: SpurMap::new(),
+            modifiers: SpurMap::new(),
+            modifier_categories: SpurMap::new(),
             errors: Vec::new(),
         }
     }
@@ -78,6 +86,8 @@ impl CwtAnalysisData {
         self.links.clear();
         self.scopes.clear();
         self.scope_groups.clear();
+        self.modifiers.clear();
+        self.modifier_categories.clear();
         self.errors.clear();
     }
 
@@ -96,6 +106,8 @@ impl CwtAnalysisData {
             + self.links.len()
             + self.scopes.len()
             + self.scope_groups.len()
+            + self.modifiers.len()
+            + self.modifier_categories.len()
     }
 
     /// Insert or merge a type definition
@@ -187,6 +199,12 @@ impl<'a, 'interner> CwtRegistryVisitor<'a, 'interner> {
         let mut scopes_visitor = ScopesVisitor::new(self.data, self.interner);
         scopes_visitor.visit_rule(rule);
     }
+
+    /// Handle a modifiers or modifier_categories section
+    fn handle_modifiers_section(&mut self, rule: &AstCwtRule) {
+        let mut modifiers_visitor = ModifiersVisitor::new(self.data, self.interner);
+        modifiers_visitor.visit_rule(rule);
+    }
 }
 
 impl<'a, 'interner> CwtVisitor<'a> for CwtRegistryVisitor<'a, 'interner> {
@@ -207,6 +225,9 @@ impl<'a, 'interner> CwtVisitor<'a> for CwtRegistryVisitor<'a, 'interner> {
             "scopes" | "scope_groups" => {
                 self.handle_scopes_section(rule);
             }
+            "modifiers" | "modifier_categories" => {
+                self.handle_modifiers_section(rule);
+            }
             _ => {
                 // Check for typed identifiers in the rule key
                 match &rule.key {
@@ -71,6 +71,7 @@ impl<'a, 'interner> TypeVisitor<'a, 'interner> {
                 skip_root_key: None,
                 subtypes: SpurMap::new(),
                 localisation: SpurMap::new(),
+                subtype_localisation: SpurMap::new(),
                 modifiers: ModifierSpec {
                     modifiers: SpurMap::new(),
                     subtypes: SpurMap::new(),
@@ -364,40 +365,41 @@ impl<'a, 'interner> TypeVisitor<'a, 'interner> {
 
                     if let CwtValue::String(pattern) = &loc_rule.value {
                         let pattern_str = pattern.raw_value().to_string();
+                        let subtype_key = interner.get_or_intern(subtype_name);
 
-                        // Only add to existing base localisation requirements
+                        // Add to subtype-specific localisation for an existing base requirement
                         if let Some(base_requirement) = type_def
                             .localisation
                             .get_mut(&interner.get_or_intern(loc_key))
                         {
-                            // Add to subtype-specific localisation for existing base requirement
                             let subtype_map = base_requirement
                                 .subtypes
-                                .entry(interner.get_or_intern(subtype_name))
+                                .entry(subtype_key)
                                 .or_insert_with(SpurMap::new);
 
                             subtype_map.insert(
                                 interner.get_or_intern(loc_key),
-                                interner.get_or_intern(pattern_str),
+                                interner.get_or_intern(&pattern_str),
                             );
+                        }
 
-                            // Check for required/primary flags on subtype localisation
-                            for option in &loc_rule.options {
-                                match option.key {
-                                    "required" => {
-                                        // Mark this subtype localisation as required
-                                        // This could be stored in a more complex structure if needed
-                                    }
-                                    "primary" => {
-                                        // Mark this subtype localisation as primary
-                                        // This could be stored in a more complex structure if needed
-                                    }
-                                    _ => {}
-                                }
+                        // Keep the subtype's own requirement, including keys that only exist
+                        // for the subtype
+                        let mut requirement =
+                            LocalisationRequirement::new(interner.get_or_intern(&pattern_str));
+                        for option in &loc_rule.options {
+                            match option.key {
+                                "required" => requirement.required = true,
+                                "primary" => requirement.primary = true,
+                                _ => {}
                             }
                         }
-                        // If no base requirement exists, this is a subtype-only localisation
-                        // We don't add it to the base localisation map
+
+                        type_def
+                            .subtype_localisation
+                            .entry(subtype_key)
+                            .or_insert_with(SpurMap::new)
+                            .insert(interner.get_or_intern(loc_key), requirement);
                     }
                 }
             }
@@ -1183,6 +1185,28 @@ types = {
                 .contains_key(&interner.get_or_intern("description"))
         );
 
+        // Subtype-only localisation is kept separately from the base requirements
+        let variant_a_localisation = complex_type
+            .subtype_localisation
+            .get(&interner.get_or_intern("variant_a"))
+            .unwrap();
+        assert_eq!(variant_a_localisation.len(), 2);
+        assert_eq!(
+            variant_a_localisation
+                .get(&interner.get_or_intern("variant_a_name"))
+                .unwrap()
+                .pattern,
+            interner.get_or_intern("$_variant_a_name")
+        );
+        assert_eq!(
+            complex_type
+                .subtype_localisation
+                .get(&interner.get_or_intern("variant_b"))
+                .unwrap()
+                .len(),
+            1
+        );
+
         // Check base modifiers
         assert_eq!(complex_type.modifiers.modifiers.len(), 2);
         assert_eq!(
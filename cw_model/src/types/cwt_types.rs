@@ -4,7 +4,7 @@
 //! closely aligned with the CWT specification rather than inferred types.
 
 use crate::{CaseInsensitiveInterner, SeverityLevel, SpurMap, TypeKeyFilter};
-use cw_parser::{AstCwtRule, CwtCommentRangeBound};
+use cw_parser::{AstCwtRule, CwtCommentRangeBound, CwtRange, CwtRangeBound};
 use lasso::Spur;
 use std::{collections::HashSet, sync::Arc};
 
@@ -23,6 +23,9 @@ pub enum CwtType {
     /// Simple primitive types (bool, int, float, scalar, etc.)
     Simple(SimpleType),
 
+    /// Numeric primitive types with an inline range, like `int[0..inf]`
+    Ranged(SimpleType, Range),
+
     /// Reference types (<type>, enum[key], scope[key], etc.)
     Reference(ReferenceType),
 
@@ -59,6 +62,7 @@ impl CwtType {
     pub fn get_type_name(&self) -> Option<Spur> {
         match self {
             CwtType::Simple(_) => None,
+            CwtType::Ranged(_, _) => None,
             CwtType::Reference(_) => None,
             CwtType::Block(block_type) => block_type.type_name.clone(),
             CwtType::Unknown => None,
@@ -73,7 +77,7 @@ impl CwtType {
 
     pub fn type_name_for_display(&self, interner: &CaseInsensitiveInterner) -> String {
         match self {
-            CwtType::Simple(_) => "(simple)".to_string(),
+            CwtType::Simple(_) | CwtType::Ranged(_, _) => "(simple)".to_string(),
             CwtType::Reference(_) => "(reference)".to_string(),
             CwtType::Block(block_type) => {
                 if let Some(type_name) = &block_type.type_name {
@@ -896,6 +900,9 @@ impl TypeFingerprint for CwtType {
         match self {
             CwtType::Unknown => "unknown".to_string(),
             CwtType::Simple(simple) => format!("simple:{}", simple.fingerprint()),
+            CwtType::Ranged(simple, range) => {
+                format!("ranged:{}:{}", simple.fingerprint(), range.fingerprint())
+            }
             CwtType::Reference(reference) => format!("reference:{}", reference.fingerprint()),
             CwtType::Block(block) => format!("block:{}", block.fingerprint()),
             CwtType::Array(array) => format!("array:{}", array.fingerprint()),
@@ -1596,9 +1603,68 @@ impl Range {
             max: RangeBound::PosInfinity,
         }
     }
+
+    /// Convert an inline range like `int[0..inf]` from the CWT AST. Bounds that can't be parsed
+    /// are treated as unbounded.
+    pub fn from_cwt(range: &CwtRange) -> Self {
+        Self {
+            min: RangeBound::from_cwt(&range.min, RangeBound::NegInfinity),
+            max: RangeBound::from_cwt(&range.max, RangeBound::PosInfinity),
+        }
+    }
+
+    /// Check if a value is within the range, bounds included
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.as_f64().is_none_or(|min| value >= min)
+            && self.max.as_f64().is_none_or(|max| value <= max)
+    }
+}
+
+impl RangeBound {
+    fn from_cwt(bound: &CwtRangeBound, unbounded: RangeBound) -> Self {
+        match bound {
+            CwtRangeBound::Int(n) => n.parse().map(RangeBound::Integer).unwrap_or(unbounded),
+            CwtRangeBound::Float(n) => n.parse().map(RangeBound::Float).unwrap_or(unbounded),
+            CwtRangeBound::Infinity(true) => RangeBound::PosInfinity,
+            CwtRangeBound::Infinity(false) => RangeBound::NegInfinity,
+        }
+    }
+
+    /// The bound as a number, or None for infinity
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            RangeBound::Integer(i) => Some(*i as f64),
+            RangeBound::Float(f) => Some(*f),
+            RangeBound::NegInfinity | RangeBound::PosInfinity => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.min, self.max)
+    }
+}
+
+impl std::fmt::Display for RangeBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeBound::Integer(i) => write!(f, "{}", i),
+            RangeBound::Float(value) => write!(f, "{:?}", value),
+            RangeBound::NegInfinity => write!(f, "-inf"),
+            RangeBound::PosInfinity => write!(f, "inf"),
+        }
+    }
 }
 
 impl CwtOptions {
+    /// The `## cardinality` of a rule, or the CWT default of exactly once when it has none
+    pub fn cardinality_or_default(&self) -> Cardinality {
+        self.cardinality
+            .clone()
+            .unwrap_or_else(Cardinality::required)
+    }
+
     /// Extract CWT options from a rule
     pub fn from_rule(rule: &AstCwtRule, interner: &CaseInsensitiveInterner) -> Self {
         let mut options = CwtOptions::default();
@@ -1642,6 +1708,21 @@ impl CwtOptions {
                         )]));
                     }
                 }
+                "scope" => {
+                    let scopes = match option.value.as_list() {
+                        Some(scopes) => scopes
+                            .iter()
+                            .filter_map(|scope| scope.as_string_or_identifier())
+                            .map(|scope| interner.get_or_intern(scope))
+                            .collect(),
+                        None => option
+                            .value
+                            .as_string_or_identifier()
+                            .map(|scope| vec![interner.get_or_intern(scope)])
+                            .unwrap_or_default(),
+                    };
+                    options.scope = Some(scopes);
+                }
                 "starts_with" => {
                     options.starts_with = Some(
                         interner.get_or_intern(option.value.as_string_or_identifier().unwrap()),
@@ -1709,7 +1790,11 @@ impl CwtOptions {
     /// For cardinality, takes the most permissive bounds
     pub fn merge(self, other: CwtOptions) -> CwtOptions {
         let merged_cardinality = match (&self.cardinality, &other.cardinality) {
-            (Some(existing_card), Some(new_card)) => {
+            (None, None) => None,
+            // A rule without a cardinality stands for the default of exactly once
+            _ => {
+                let existing_card = self.cardinality_or_default();
+                let new_card = other.cardinality_or_default();
                 // Merge cardinalities by taking the more permissive bounds
                 Some(Cardinality {
                     min: Some(std::cmp::min(
@@ -1725,8 +1810,6 @@ impl CwtOptions {
                     soft: existing_card.soft || new_card.soft, // Keep soft if either is soft
                 })
             }
-            (Some(card), None) | (None, Some(card)) => Some(card.clone()),
-            (None, None) => None,
         };
 
         CwtOptions {
@@ -1758,6 +1841,23 @@ impl CwtOptions {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_range_contains() {
+        let range = Range {
+            min: RangeBound::Integer(0),
+            max: RangeBound::PosInfinity,
+        };
+        assert!(range.contains(0.0));
+        assert!(range.contains(1e9));
+        assert!(!range.contains(-5.0));
+        assert_eq!(range.to_string(), "0..inf");
+
+        let range = Range::float_range(0.0, 1.0);
+        assert!(range.contains(0.5));
+        assert!(!range.contains(1.5));
+        assert_eq!(range.to_string(), "0.0..1.0");
+    }
+
     #[test]
     fn test_fingerprint_uniqueness() {
         // Test that different types have different fingerprints
@@ -1,6 +1,7 @@
 pub mod cw;
 pub mod cwt;
 mod errors;
+pub mod localisation;
 pub mod mod_definition;
 mod shared;
 
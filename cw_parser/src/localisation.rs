@@ -0,0 +1,362 @@
+//! Parser for Paradox localisation files (`*_l_english.yml` and friends)
+//!
+//! Despite the extension these aren't YAML, every line is one of:
+//!
+//! ```text
+//! l_english:
+//!  # a comment
+//!  KEY:0 "Some text with \"quotes\" and [Root.GetName]" # trailing comment
+//!  OTHER_KEY: "No version number"
+//! ```
+//!
+//! The game is lenient with unescaped quotes inside values, so a value ends at the last quote on
+//! its line that is only followed by whitespace or a comment. Parsing is line oriented and never
+//! fails as a whole: malformed lines are skipped and reported in
+//! [`AstLocalisationFile::errors`], so one typo doesn't hide every other key in the file.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::{AstComment, AstNode, ParseError};
+
+const BOM: char = '\u{feff}';
+
+/// A parsed localisation file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AstLocalisationFile<'a> {
+    /// The `l_<language>:` header, if the file has one
+    pub header: Option<AstLocalisationHeader<'a>>,
+    pub entries: Vec<AstLocalisationEntry<'a>>,
+    /// Comments on their own line
+    pub comments: Vec<AstComment<'a>>,
+    /// Whether the file starts with a UTF-8 byte order mark, which the game requires
+    pub has_bom: bool,
+    /// Lines that couldn't be parsed
+    pub errors: Vec<ParseError>,
+    pub span: Range<usize>,
+}
+
+/// The `l_<language>:` line at the top of a localisation file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AstLocalisationHeader<'a> {
+    /// The language without the `l_` prefix, e.g. `english`
+    pub language: &'a str,
+    pub span: Range<usize>,
+}
+
+/// A single `KEY:0 "text"` line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AstLocalisationEntry<'a> {
+    pub key: &'a str,
+    pub key_span: Range<usize>,
+    /// The number after the colon, which the game ignores
+    pub version: Option<u32>,
+    /// The raw text between the quotes, with escape sequences left as they are
+    pub value: &'a str,
+    /// The span of the value, excluding the quotes
+    pub value_span: Range<usize>,
+    pub trailing_comment: Option<AstComment<'a>>,
+    pub span: Range<usize>,
+}
+
+impl<'a> AstLocalisationEntry<'a> {
+    /// The text of the entry with `\"`, `\\`, `\n` and `\t` escapes resolved
+    pub fn text(&self) -> Cow<'a, str> {
+        if !self.value.contains('\\') {
+            return Cow::Borrowed(self.value);
+        }
+
+        let mut text = String::with_capacity(self.value.len());
+        let mut chars = self.value.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                text.push(ch);
+                continue;
+            }
+
+            match chars.next() {
+                Some('"') => text.push('"'),
+                Some('\\') => text.push('\\'),
+                Some('n') => text.push('\n'),
+                Some('t') => text.push('\t'),
+                Some(other) => {
+                    text.push('\\');
+                    text.push(other);
+                }
+                None => text.push('\\'),
+            }
+        }
+
+        Cow::Owned(text)
+    }
+}
+
+impl<'a> AstNode<'a> for AstLocalisationEntry<'a> {
+    fn span_range(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    fn leading_comments(&self) -> &[AstComment<'a>] {
+        &[]
+    }
+
+    fn trailing_comment(&self) -> Option<&AstComment<'a>> {
+        self.trailing_comment.as_ref()
+    }
+}
+
+impl<'a> AstLocalisationFile<'a> {
+    /// Parse the content of a localisation file
+    pub fn from_input(input: &'a str) -> Self {
+        let has_bom = input.starts_with(BOM);
+        let mut file = Self {
+            header: None,
+            entries: Vec::new(),
+            comments: Vec::new(),
+            has_bom,
+            errors: Vec::new(),
+            span: 0..input.len(),
+        };
+
+        let mut offset = 0;
+        for (line_number, line) in input.split_inclusive('\n').enumerate() {
+            let line_start = offset;
+            offset += line.len();
+
+            let mut line = line.trim_end_matches(['\r', '\n']);
+            let mut line_start = line_start;
+            if line_number == 0 && has_bom {
+                line = &line[BOM.len_utf8()..];
+                line_start += BOM.len_utf8();
+            }
+
+            file.parse_line(line, line_start, line_number);
+        }
+
+        file
+    }
+
+    /// The language of the file, e.g. `english`
+    pub fn language(&self) -> Option<&'a str> {
+        self.header.as_ref().map(|header| header.language)
+    }
+
+    fn parse_line(&mut self, line: &'a str, line_start: usize, line_number: usize) {
+        let indent = line.len() - line.trim_start().len();
+        let content = line.trim();
+        let start = line_start + indent;
+
+        if content.is_empty() {
+            return;
+        }
+
+        if content.starts_with('#') {
+            self.comments
+                .push(AstComment::new(content, start..start + content.len()));
+            return;
+        }
+
+        let Some(colon) = content.find(':') else {
+            self.error(
+                "Expected a localisation key followed by ':'",
+                start..start + content.len(),
+                line_number,
+                indent,
+            );
+            return;
+        };
+
+        let key = &content[..colon];
+        let key_span = start..start + colon;
+        if key.is_empty() || key.contains(|ch: char| ch.is_whitespace() || ch == '"') {
+            self.error(
+                format!("Invalid localisation key '{}'", key),
+                key_span,
+                line_number,
+                indent,
+            );
+            return;
+        }
+
+        let rest = &content[colon + 1..];
+        let digits = rest.len()
+            - rest
+                .trim_start_matches(|ch: char| ch.is_ascii_digit())
+                .len();
+        let version = rest[..digits].parse().ok();
+        let after_version = &rest[digits..];
+        let value_part = after_version.trim_start();
+        let value_start = start + colon + 1 + digits + (after_version.len() - value_part.len());
+
+        // `l_english:` on its own line
+        if self.header.is_none()
+            && self.entries.is_empty()
+            && key.starts_with("l_")
+            && version.is_none()
+            && (value_part.is_empty() || value_part.starts_with('#'))
+        {
+            self.header = Some(AstLocalisationHeader {
+                language: &key[2..],
+                span: start..start + colon + 1,
+            });
+            return;
+        }
+
+        let Some(value) = value_part.strip_prefix('"') else {
+            self.error(
+                format!("Expected a quoted value for '{}'", key),
+                key_span,
+                line_number,
+                indent,
+            );
+            return;
+        };
+
+        let Some((value_len, trailing_comment)) = find_value_end(value) else {
+            self.error(
+                format!("Missing closing quote in the value of '{}'", key),
+                value_start..start + content.len(),
+                line_number,
+                indent + (value_start - start),
+            );
+            return;
+        };
+
+        if self.header.is_none() && self.entries.is_empty() {
+            self.error(
+                "Missing 'l_<language>:' header before the first key",
+                key_span.clone(),
+                line_number,
+                indent,
+            );
+        }
+
+        let value_span = value_start + 1..value_start + 1 + value_len;
+        let trailing_comment = trailing_comment.map(|comment_offset| {
+            let comment = value[comment_offset..].trim_end();
+            let comment_start = value_start + 1 + comment_offset;
+            AstComment::new(comment, comment_start..comment_start + comment.len())
+        });
+
+        self.entries.push(AstLocalisationEntry {
+            key,
+            key_span,
+            version,
+            value: &value[..value_len],
+            value_span: value_span.clone(),
+            trailing_comment,
+            span: start..value_span.end + 1,
+        });
+    }
+
+    fn error(
+        &mut self,
+        message: impl Into<String>,
+        span: Range<usize>,
+        line: usize,
+        column: usize,
+    ) {
+        self.errors
+            .push(ParseError::new(message, span, line, column));
+    }
+}
+
+/// Find the closing quote of a value, given the text after its opening quote. The closing quote
+/// is the last unescaped quote followed only by whitespace or a comment. Returns the length of the
+/// value and the offset of the trailing comment, if any.
+fn find_value_end(value: &str) -> Option<(usize, Option<usize>)> {
+    let mut escaped = false;
+    let mut end = None;
+
+    for (index, ch) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
+        match ch {
+            '\\' => escaped = true,
+            '"' => {
+                let rest = value[index + 1..].trim_start();
+                if rest.is_empty() || rest.starts_with('#') {
+                    let comment = (!rest.is_empty()).then(|| value.len() - rest.len());
+                    end = Some((index, comment));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_localisation_file() {
+        let input = "\u{feff}l_english:\n # Technologies\n tech_lasers_1:0 \"Red Lasers\"\n tech_lasers_1_desc: \"Basic lasers.\" # TODO\n";
+        let file = AstLocalisationFile::from_input(input);
+
+        assert!(file.has_bom);
+        assert!(file.errors.is_empty());
+        assert_eq!(file.language(), Some("english"));
+        assert_eq!(file.comments.len(), 1);
+        assert_eq!(file.comments[0].text, "# Technologies");
+
+        assert_eq!(file.entries.len(), 2);
+        let entry = &file.entries[0];
+        assert_eq!(entry.key, "tech_lasers_1");
+        assert_eq!(&input[entry.key_span.clone()], "tech_lasers_1");
+        assert_eq!(entry.version, Some(0));
+        assert_eq!(entry.value, "Red Lasers");
+        assert_eq!(&input[entry.value_span.clone()], "Red Lasers");
+        assert_eq!(&input[entry.span.clone()], "tech_lasers_1:0 \"Red Lasers\"");
+
+        let entry = &file.entries[1];
+        assert_eq!(entry.version, None);
+        assert_eq!(entry.value, "Basic lasers.");
+        assert_eq!(entry.trailing_comment.as_ref().unwrap().text, "# TODO");
+    }
+
+    #[test]
+    fn test_parse_quotes_in_values() {
+        let input =
+            "l_english:\n KEY:0 \"Say \\\"hi\\\" to \"them\" # not \"part\" of it\n EMPTY:0 \"\"\n";
+        let file = AstLocalisationFile::from_input(input);
+
+        assert!(file.errors.is_empty());
+        assert_eq!(file.entries[0].value, "Say \\\"hi\\\" to \"them");
+        assert_eq!(file.entries[0].text(), "Say \"hi\" to \"them");
+        assert_eq!(
+            file.entries[0].trailing_comment.as_ref().unwrap().text,
+            "# not \"part\" of it"
+        );
+        assert_eq!(file.entries[1].value, "");
+    }
+
+    #[test]
+    fn test_parse_errors_are_recovered() {
+        let input = "l_german:\r\n BROKEN:0 \"no end\r\n NO_VALUE:0\r\n GOOD:0 \"fine\"\r\n";
+        let file = AstLocalisationFile::from_input(input);
+
+        assert!(!file.has_bom);
+        assert_eq!(file.language(), Some("german"));
+        assert_eq!(file.errors.len(), 2);
+        assert_eq!(file.errors[0].line, 1);
+        assert_eq!(file.errors[1].line, 2);
+        assert_eq!(file.entries.len(), 1);
+        assert_eq!(file.entries[0].key, "GOOD");
+    }
+
+    #[test]
+    fn test_missing_header() {
+        let file = AstLocalisationFile::from_input("KEY:0 \"text\"\n");
+
+        assert_eq!(file.language(), None);
+        assert_eq!(file.entries.len(), 1);
+        assert_eq!(file.errors.len(), 1);
+    }
+}
@@ -2,7 +2,7 @@
 //! based on runtime settings.
 
 use anyhow::Result;
-use cw_model::{GameMod, LoadMode, Modifier};
+use cw_model::{EntityMergeMode, GameMod, LoadMode, Modifier};
 use std::collections::HashSet;
 use std::path::PathBuf;
 
@@ -104,6 +104,33 @@ pub mod game {
         }
     }
 
+    /// Get the folder the current game keeps its localisation in, like `localisation`
+    pub fn get_localisation_folder() -> &'static str {
+        match get_current_game() {
+            "victoria3" => victoria_3::BaseGame::get_localisation_folder(),
+            _ => stellaris::BaseGame::get_localisation_folder(),
+        }
+    }
+
+    /// Get the characters that can follow `§` in localisation text, empty if the game doesn't use
+    /// `§` colour codes
+    pub fn get_text_colour_codes() -> &'static [char] {
+        match get_current_game() {
+            "victoria3" => victoria_3::BaseGame::get_text_colour_codes(),
+            _ => stellaris::BaseGame::get_text_colour_codes(),
+        }
+    }
+
+    /// Get how entities with the same name are resolved in a namespace like
+    /// `game/common/armies`
+    pub fn get_entity_merge_mode(namespace: &str) -> EntityMergeMode {
+        let folder = namespace.strip_prefix("game/").unwrap_or(namespace);
+        match get_current_game() {
+            "victoria3" => victoria_3::BaseGame::get_entity_merge_mode(folder),
+            _ => stellaris::BaseGame::get_entity_merge_mode(folder),
+        }
+    }
+
     /// Get the glob patterns for the current game
     pub fn get_glob_patterns() -> Vec<&'static str> {
         match get_current_game() {
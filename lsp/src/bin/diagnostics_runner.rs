@@ -11,7 +11,7 @@ use clap::Parser;
 use colored::Colorize;
 use cw_lsp::base_game::game;
 use cw_lsp::base_game::game::detect_base_directory;
-use cw_lsp::handlers::cache::FileIndex;
+use cw_lsp::handlers::cache::{FileIndex, LocalisationCache, SpriteCache};
 use cw_lsp::handlers::diagnostics::provider::DiagnosticsProvider;
 use cw_lsp::handlers::initialization::CacheInitializer;
 use cw_lsp::handlers::settings::Settings;
@@ -19,6 +19,7 @@ use cw_lsp::interner::get_interner;
 use cw_model::GameMod;
 use cw_model::LoadMode;
 use cw_model::ModDefinition;
+use cw_model::is_localisation_file;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 use tower_lsp::lsp_types::Diagnostic;
@@ -43,23 +44,26 @@ struct Args {
     settings: Settings,
 }
 
-/// Recursively find all .txt files in a directory
-fn find_txt_files(dir: &Path) -> io::Result<Vec<std::path::PathBuf>> {
-    let mut txt_files = Vec::new();
+/// Recursively find all script (.txt) and localisation (.yml) files in a directory
+fn find_files_to_check(dir: &Path) -> io::Result<Vec<std::path::PathBuf>> {
+    let mut files = Vec::new();
 
     if dir.is_dir() {
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
             if path.is_dir() {
-                txt_files.extend(find_txt_files(&path)?);
-            } else if path.is_file() && path.extension().map_or(false, |ext| ext == "txt") {
-                txt_files.push(path);
+                files.extend(find_files_to_check(&path)?);
+            } else if path.is_file()
+                && (path.extension().map_or(false, |ext| ext == "txt")
+                    || is_localisation_file(&path, game::get_localisation_folder()))
+            {
+                files.push(path);
             }
         }
     }
 
-    Ok(txt_files)
+    Ok(files)
 }
 
 /// Generate diagnostics for a single file using DiagnosticsProvider
@@ -187,14 +191,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     } else if input_path.is_dir() {
         println!(
             "{} {}",
-            "Finding .txt files in directory...".yellow().bold(),
+            "Finding .txt and .yml files in directory..."
+                .yellow()
+                .bold(),
             input_path.display().to_string().bright_white()
         );
-        let files = find_txt_files(input_path)?;
+        let files = find_files_to_check(input_path)?;
         println!(
             "{} {}",
             "Found".green().bold(),
-            format!("{} .txt files", files.len()).bright_white()
+            format!("{} files", files.len()).bright_white()
         );
         files
     } else {
@@ -276,6 +282,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         FileIndex::update_global_with_mod(&load_result.game_mod);
+        LocalisationCache::load_mod(root_dir.as_ref().unwrap());
+        SpriteCache::index_mod(&load_result.game_mod);
 
         println!(
             "{} {}",
@@ -284,6 +292,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         );
     }
 
+    // Localisation files are checked against every other localisation file
+    if txt_files
+        .iter()
+        .any(|path| is_localisation_file(path, game::get_localisation_folder()))
+    {
+        while !LocalisationCache::is_initialized() {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    // Sprite names are checked wherever an icon is expected
+    while !SpriteCache::is_initialized() {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
     // Process each file in parallel with progress bar
     txt_files.par_iter().for_each(|file_path| {
         match fs::read_to_string(&file_path) {
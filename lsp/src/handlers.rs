@@ -5,30 +5,45 @@ use tower_lsp::lsp_types::*;
 use crate::CwLspServer;
 
 pub mod cache;
+mod code_actions;
 pub mod common_validation;
+mod completion;
+mod cursor;
+mod definition;
 pub mod diagnostics;
 mod document;
 pub mod document_cache;
+mod document_symbols;
+mod file_changes;
 mod formatting;
 mod hover;
 pub mod initialization;
 pub mod mod_detection;
 mod modifiers;
+mod references;
+mod rename;
 mod scope;
 mod scoped_type;
 mod semantic_tokens;
 mod server_lifecycle;
 pub mod settings;
 pub mod utils;
+mod workspace_symbols;
 
 #[tower_lsp::async_trait]
 impl LanguageServer for CwLspServer {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
-        server_lifecycle::initialize(params).await
+        server_lifecycle::initialize(&self.watched_files_registration, params).await
     }
 
     async fn initialized(&self, params: InitializedParams) {
-        server_lifecycle::initialized(&self.client, self.documents.clone(), params).await;
+        server_lifecycle::initialized(
+            &self.client,
+            self.documents.clone(),
+            &self.watched_files_registration,
+            params,
+        )
+        .await;
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -43,6 +58,18 @@ impl LanguageServer for CwLspServer {
         document::did_change(self, params);
     }
 
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        document::did_close(self, params).await;
+    }
+
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        document::did_save(self, params);
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        file_changes::did_change_watched_files(self, params);
+    }
+
     async fn semantic_tokens_full(
         &self,
         params: SemanticTokensParams,
@@ -71,6 +98,55 @@ impl LanguageServer for CwLspServer {
         hover::hover(&self.client, &self.documents, &self.document_cache, params)
     }
 
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        completion::completion(&self.client, &self.documents, &self.document_cache, params)
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        definition::goto_definition(&self.client, &self.documents, &self.document_cache, params)
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        references::find_references(&self.client, &self.documents, &self.document_cache, params)
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        rename::prepare_rename(&self.client, &self.documents, &self.document_cache, params)
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        rename::rename(&self.client, &self.documents, &self.document_cache, params)
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        document_symbols::document_symbol(
+            &self.client,
+            &self.documents,
+            &self.document_cache,
+            params,
+        )
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        workspace_symbols::workspace_symbol(&self.client, params)
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        code_actions::code_action(&self.client, params)
+    }
+
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
         formatting::document_formatting(&self.client, &self.documents, &self.document_cache, params)
     }
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
 mod complex_enums;
+mod name_format_keys;
 mod scripted_effect_arguments;
 mod value_sets;
 
@@ -8,17 +9,25 @@ use cw_model::SpurMap;
 use lasso::Spur;
 
 use crate::handlers::cache::{
+    TypeCache,
     collector::{
         complex_enums::ComplexEnumCollector,
-        scripted_effect_arguments::ScriptedEffectArgumentCollector, value_sets::ValueSetCollector,
+        name_format_keys::{NAME_FORMAT_KEY_NAMESPACES, NameFormatKeyCollector},
+        scripted_effect_arguments::ScriptedEffectArgumentCollector,
+        value_sets::ValueSetCollector,
     },
     resolver::TypeResolver,
 };
+use crate::interner::get_interner;
 
 pub struct DataCollector<'resolver> {
     value_sets: SpurMap<HashSet<Spur>>,
+    /// The value sets found in each namespace, to recompute single namespaces later
+    namespace_value_sets: SpurMap<SpurMap<HashSet<Spur>>>,
     complex_enums: SpurMap<HashSet<Spur>>,
     scripted_effect_arguments: SpurMap<HashSet<Spur>>, // Also scripted triggers for convenience... might be wrong because clashes
+    /// Name format family -> the `<key>`s its formats can use
+    name_format_keys: SpurMap<HashSet<Spur>>,
     type_resolver: &'resolver TypeResolver,
 }
 
@@ -26,8 +35,10 @@ impl<'resolver> DataCollector<'resolver> {
     pub fn new(type_resolver: &'resolver TypeResolver) -> Self {
         Self {
             value_sets: SpurMap::new(),
+            namespace_value_sets: SpurMap::new(),
             complex_enums: SpurMap::new(),
             scripted_effect_arguments: SpurMap::new(),
+            name_format_keys: SpurMap::new(),
             type_resolver,
         }
     }
@@ -36,6 +47,10 @@ impl<'resolver> DataCollector<'resolver> {
         &self.value_sets
     }
 
+    pub fn namespace_value_sets(&self) -> &SpurMap<SpurMap<HashSet<Spur>>> {
+        &self.namespace_value_sets
+    }
+
     pub fn complex_enums(&self) -> &SpurMap<HashSet<Spur>> {
         &self.complex_enums
     }
@@ -44,14 +59,203 @@ impl<'resolver> DataCollector<'resolver> {
         &self.scripted_effect_arguments
     }
 
+    pub fn name_format_keys(&self) -> &SpurMap<HashSet<Spur>> {
+        &self.name_format_keys
+    }
+
     pub fn collect_all(&mut self) {
         let value_set_collector = ValueSetCollector::new(self.type_resolver);
-        self.value_sets = value_set_collector.collect();
+        self.namespace_value_sets = value_set_collector.collect();
+        self.value_sets = ValueSetCollector::merge(&self.namespace_value_sets);
 
         let complex_enum_collector = ComplexEnumCollector::new(self.type_resolver);
         self.complex_enums = complex_enum_collector.collect();
 
         let scripted_effect_argument_collector = ScriptedEffectArgumentCollector::new();
         self.scripted_effect_arguments = scripted_effect_argument_collector.collect();
+
+        self.name_format_keys = NameFormatKeyCollector::new().collect();
+    }
+
+    /// Start from a previous collection, so that only the data of changed namespaces has to be
+    /// collected again with `collect_namespaces`
+    pub fn with_previous(
+        mut self,
+        namespace_value_sets: SpurMap<SpurMap<HashSet<Spur>>>,
+        complex_enums: SpurMap<HashSet<Spur>>,
+        scripted_effect_arguments: SpurMap<HashSet<Spur>>,
+        name_format_keys: SpurMap<HashSet<Spur>>,
+    ) -> Self {
+        self.value_sets = ValueSetCollector::merge(&namespace_value_sets);
+        self.namespace_value_sets = namespace_value_sets;
+        self.complex_enums = complex_enums;
+        self.scripted_effect_arguments = scripted_effect_arguments;
+        self.name_format_keys = name_format_keys;
+        self
+    }
+
+    /// Collect the value sets, complex enums, scripted effect arguments and name format keys of the given
+    /// namespaces again, keeping everything collected from other namespaces
+    pub fn collect_namespaces(&mut self, namespaces: &HashSet<Spur>) {
+        let interner = get_interner();
+
+        let namespace_list: Vec<Spur> = namespaces.iter().copied().collect();
+        let value_set_collector = ValueSetCollector::new(self.type_resolver);
+        replace_namespace_value_sets(
+            &mut self.namespace_value_sets,
+            namespaces,
+            value_set_collector.collect_namespaces(&namespace_list),
+        );
+        self.value_sets = ValueSetCollector::merge(&self.namespace_value_sets);
+
+        let complex_enum_collector = ComplexEnumCollector::new(self.type_resolver);
+        replace_complex_enums(
+            &mut self.complex_enums,
+            complex_enum_collector.collect_namespaces(namespaces),
+        );
+
+        let has_scripted_arguments = [
+            "game/common/scripted_effects",
+            "game/common/scripted_triggers",
+        ]
+        .iter()
+        .any(|namespace| {
+            namespaces.contains(&TypeCache::get_actual_namespace(
+                interner.get_or_intern(namespace),
+            ))
+        });
+        if has_scripted_arguments {
+            let scripted_effect_argument_collector = ScriptedEffectArgumentCollector::new();
+            self.scripted_effect_arguments = scripted_effect_argument_collector.collect();
+        }
+
+        let has_name_format_keys = NAME_FORMAT_KEY_NAMESPACES.iter().any(|namespace| {
+            namespaces.contains(&TypeCache::get_actual_namespace(
+                interner.get_or_intern(namespace),
+            ))
+        });
+        if has_name_format_keys {
+            self.name_format_keys = NameFormatKeyCollector::new().collect();
+        }
+    }
+}
+
+/// Replace the value sets of the reloaded namespaces, dropping those of namespaces that no longer
+/// have any
+fn replace_namespace_value_sets(
+    namespace_value_sets: &mut SpurMap<SpurMap<HashSet<Spur>>>,
+    namespaces: &HashSet<Spur>,
+    collected: SpurMap<SpurMap<HashSet<Spur>>>,
+) {
+    for namespace in namespaces {
+        namespace_value_sets.remove(namespace);
+    }
+    for (namespace, value_sets) in collected {
+        namespace_value_sets.insert(namespace, value_sets);
+    }
+}
+
+/// Replace the values of the recollected complex enums, dropping enums that have none left
+fn replace_complex_enums(
+    complex_enums: &mut SpurMap<HashSet<Spur>>,
+    collected: SpurMap<HashSet<Spur>>,
+) {
+    for (enum_name, values) in collected {
+        if values.is_empty() {
+            complex_enums.remove(&enum_name);
+        } else {
+            complex_enums.insert(enum_name, values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spur(value: &str) -> Spur {
+        get_interner().get_or_intern(value)
+    }
+
+    fn value_sets(entries: &[(&str, &[&str])]) -> SpurMap<HashSet<Spur>> {
+        entries
+            .iter()
+            .map(|(key, values)| (spur(key), values.iter().map(|value| spur(value)).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_reload_replaces_only_changed_namespaces() {
+        let mut namespace_value_sets = SpurMap::new();
+        namespace_value_sets.insert(
+            spur("game/common/buildings"),
+            value_sets(&[("country_flag", &["building_flag_old"])]),
+        );
+        namespace_value_sets.insert(
+            spur("game/events"),
+            value_sets(&[("country_flag", &["event_flag"])]),
+        );
+
+        let mut collected = SpurMap::new();
+        collected.insert(
+            spur("game/common/buildings"),
+            value_sets(&[("country_flag", &["building_flag_new"])]),
+        );
+        replace_namespace_value_sets(
+            &mut namespace_value_sets,
+            &HashSet::from([spur("game/common/buildings")]),
+            collected,
+        );
+
+        let merged = ValueSetCollector::merge(&namespace_value_sets);
+        assert_eq!(
+            merged.get(&spur("country_flag")),
+            Some(&HashSet::from([
+                spur("building_flag_new"),
+                spur("event_flag")
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_reload_drops_namespaces_without_value_sets() {
+        let mut namespace_value_sets = SpurMap::new();
+        namespace_value_sets.insert(
+            spur("game/common/buildings"),
+            value_sets(&[("country_flag", &["building_flag"])]),
+        );
+
+        replace_namespace_value_sets(
+            &mut namespace_value_sets,
+            &HashSet::from([spur("game/common/buildings")]),
+            SpurMap::new(),
+        );
+
+        assert!(namespace_value_sets.is_empty());
+        assert!(ValueSetCollector::merge(&namespace_value_sets).is_empty());
+    }
+
+    #[test]
+    fn test_reload_replaces_complex_enums() {
+        let mut complex_enums = value_sets(&[
+            ("building_names", &["building_a"]),
+            ("event_chains", &["chain_a"]),
+            ("tech_names", &["tech_a"]),
+        ]);
+
+        replace_complex_enums(
+            &mut complex_enums,
+            value_sets(&[("building_names", &["building_b"]), ("event_chains", &[])]),
+        );
+
+        assert_eq!(
+            complex_enums.get(&spur("building_names")),
+            Some(&HashSet::from([spur("building_b")]))
+        );
+        assert!(!complex_enums.contains_key(&spur("event_chains")));
+        assert_eq!(
+            complex_enums.get(&spur("tech_names")),
+            Some(&HashSet::from([spur("tech_a")]))
+        );
     }
 }
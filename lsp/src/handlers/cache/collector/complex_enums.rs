@@ -4,7 +4,7 @@ use cw_model::{ComplexEnumDefinition, CwtType, Entity, SpurMap};
 use lasso::Spur;
 
 use crate::{
-    handlers::cache::{EntityRestructurer, resolver::TypeResolver},
+    handlers::cache::{EntityRestructurer, TypeCache, resolver::TypeResolver},
     interner::get_interner,
 };
 
@@ -21,6 +21,25 @@ impl<'resolver> ComplexEnumCollector<'resolver> {
         }
     }
 
+    /// Collect the values of the complex enums defined by the given namespaces. Every such enum
+    /// is included, even when it has no values anymore.
+    pub fn collect_namespaces(&self, namespaces: &HashSet<Spur>) -> SpurMap<HashSet<Spur>> {
+        let mut complex_enums = SpurMap::new();
+
+        for (enum_name, enum_def) in self.type_resolver.get_enums() {
+            if let Some(complex_def) = &enum_def.complex
+                && namespaces.contains(&TypeCache::get_actual_namespace(complex_def.path))
+            {
+                complex_enums.insert(
+                    enum_name,
+                    self.extract_complex_enum_values(complex_def, enum_name),
+                );
+            }
+        }
+
+        complex_enums
+    }
+
     pub fn collect(mut self) -> SpurMap<HashSet<Spur>> {
         // Get all enum definitions from the CwtAnalyzer
         let enum_definitions = self.type_resolver.get_enums();
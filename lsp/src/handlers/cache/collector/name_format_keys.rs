@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+
+use cw_model::{Entity, SpurMap};
+use lasso::Spur;
+
+use crate::{handlers::cache::EntityRestructurer, interner::get_interner};
+
+/// The namespaces that define the keys a name format can refer to with `<key>`
+pub const NAME_FORMAT_KEY_NAMESPACES: &[&str] =
+    &["game/common/name_lists", "game/common/species_classes"];
+
+/// Collects the `<key>`s each name format family can use. A key is declared by the `key` of a
+/// parts list named after its family, like `empire_name_parts_list = { key = "imperial_mil" }`
+/// for `stellaris_name_format[empire]`.
+pub struct NameFormatKeyCollector {
+    name_format_keys: SpurMap<HashSet<Spur>>,
+}
+
+impl NameFormatKeyCollector {
+    pub fn new() -> Self {
+        Self {
+            name_format_keys: SpurMap::new(),
+        }
+    }
+
+    pub fn collect(mut self) -> SpurMap<HashSet<Spur>> {
+        let interner = get_interner();
+
+        for namespace in NAME_FORMAT_KEY_NAMESPACES {
+            let Some(entities) =
+                EntityRestructurer::get_all_entities_map(interner.get_or_intern(namespace))
+            else {
+                continue;
+            };
+            for (name, entity) in entities.iter() {
+                self.collect_entity(interner.resolve(&name), entity);
+            }
+        }
+
+        self.name_format_keys
+    }
+
+    /// Collect the parts lists in an entity, which is a parts list itself when its name says so
+    fn collect_entity(&mut self, name: &str, entity: &Entity) {
+        let interner = get_interner();
+
+        if let Some(family) = parts_list_family(name) {
+            let family = interner.get_or_intern(family);
+            let keys = entity
+                .properties
+                .kv
+                .get(&interner.get_or_intern("key"))
+                .into_iter()
+                .flat_map(|values| values.0.iter())
+                .filter_map(|value| value.value.as_string());
+            for key in keys {
+                self.name_format_keys
+                    .entry(family)
+                    .or_default()
+                    .insert(*key);
+            }
+            return;
+        }
+
+        for (key, values) in entity.properties.kv.iter() {
+            for value in values.0.iter() {
+                if let Some(nested_entity) = value.value.as_entity() {
+                    self.collect_entity(interner.resolve(&key), nested_entity);
+                }
+            }
+        }
+    }
+}
+
+/// The family of a parts list name like `empire_name_parts_list`. Top level entities with the
+/// same key are numbered by the game data cache, so `empire_name_parts_list_2` is one as well.
+fn parts_list_family(name: &str) -> Option<&str> {
+    let unnumbered = match name.rsplit_once('_') {
+        Some((rest, number))
+            if !number.is_empty() && number.chars().all(|ch| ch.is_ascii_digit()) =>
+        {
+            rest
+        }
+        _ => name,
+    };
+    unnumbered
+        .strip_suffix("_name_parts_list")
+        .filter(|family| !family.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cw_model::Module;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn collect_from(input: &str) -> SpurMap<HashSet<Spur>> {
+        let interner = get_interner();
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("common/name_lists/test.txt");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, input).unwrap();
+        let module = Module::from_file(&path, temp_dir.path(), interner).unwrap();
+
+        let mut collector = NameFormatKeyCollector::new();
+        for (name, values) in module.properties.kv.iter() {
+            for value in values.0.iter() {
+                if let Some(entity) = value.value.as_entity() {
+                    collector.collect_entity(interner.resolve(&name), entity);
+                }
+            }
+        }
+        collector.name_format_keys
+    }
+
+    fn family_keys(keys: &SpurMap<HashSet<Spur>>, family: &str) -> Vec<String> {
+        let interner = get_interner();
+        let mut keys: Vec<String> = keys
+            .get(&interner.get_or_intern(family))
+            .into_iter()
+            .flatten()
+            .map(|key| interner.resolve(key).to_string())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_parts_list_family() {
+        assert_eq!(parts_list_family("empire_name_parts_list"), Some("empire"));
+        assert_eq!(
+            parts_list_family("empire_name_parts_list_2"),
+            Some("empire")
+        );
+        assert_eq!(parts_list_family("name_parts_list"), None);
+        assert_eq!(parts_list_family("weight"), None);
+    }
+
+    #[test]
+    fn test_collects_keys_by_family() {
+        let keys = collect_from(
+            r#"
+            empire_name_parts_list = {
+                key = "imperial_mil"
+                parts = { weight = 10 random_names = { "Empire" } }
+            }
+            federation_name_parts_list = { key = "fed_adj" }
+            HUM1 = {
+                randomized = no
+                empire_name_parts_list = { key = "human_states" }
+                ship_names = { generic = { "Enterprise" } }
+            }
+            "#,
+        );
+
+        assert_eq!(
+            family_keys(&keys, "empire"),
+            vec!["human_states", "imperial_mil"]
+        );
+        assert_eq!(family_keys(&keys, "federation"), vec!["fed_adj"]);
+        assert!(family_keys(&keys, "weight").is_empty());
+    }
+}
@@ -16,16 +16,12 @@ use crate::{
 };
 
 pub struct ValueSetCollector<'resolver> {
-    value_sets: SpurMap<HashSet<Spur>>,
     type_resolver: &'resolver TypeResolver,
 }
 
 impl<'resolver> ValueSetCollector<'resolver> {
     pub fn new(type_resolver: &'resolver TypeResolver) -> Self {
-        Self {
-            value_sets: SpurMap::new(),
-            type_resolver,
-        }
+        Self { type_resolver }
     }
 
     /// Extract the flag name from a country flag value, removing scope information after '@'
@@ -38,34 +34,49 @@ impl<'resolver> ValueSetCollector<'resolver> {
         }
     }
 
-    pub fn collect(mut self) -> SpurMap<HashSet<Spur>> {
+    /// Collect the value sets of every namespace, keyed by the namespace they were found in
+    pub fn collect(&self) -> SpurMap<SpurMap<HashSet<Spur>>> {
         // Get namespaces from GameDataCache, then use EntityRestructurer for entity access
-        let namespaces = match GameDataCache::get() {
-            Some(game_data) => game_data.get_namespaces(),
+        let namespaces: Vec<Spur> = match GameDataCache::get() {
+            Some(game_data) => game_data.get_namespaces().keys().collect(),
             None => return SpurMap::new(), // Early return if game data not available
         };
 
+        self.collect_namespaces(&namespaces)
+    }
+
+    /// Collect the value sets found in the given namespaces, keyed by namespace
+    pub fn collect_namespaces(&self, namespaces: &[Spur]) -> SpurMap<SpurMap<HashSet<Spur>>> {
         // Collect value_sets from parallel processing using EntityRestructurer
-        let results: Vec<SpurMap<HashSet<Spur>>> = namespaces
-            .as_inner()
+        let results: Vec<(Spur, SpurMap<HashSet<Spur>>)> = namespaces
             .par_iter()
-            .filter_map(|(namespace, _namespace_data)| {
-                get_namespace_entity_type(namespace.0, None) // TODO: Add file_path
+            .filter_map(|namespace| {
+                get_namespace_entity_type(*namespace, None) // TODO: Add file_path
                     .and_then(|namespace_type| namespace_type.scoped_type)
                     .map(|scoped_type| {
-                        self.collect_value_sets_from_namespace(namespace.0, scoped_type)
+                        (
+                            *namespace,
+                            self.collect_value_sets_from_namespace(*namespace, scoped_type),
+                        )
                     })
             })
             .collect();
 
-        // Merge all results into the main value_sets HashMap
-        for result in results {
-            for (key, values) in result {
-                self.value_sets.entry(key).or_default().extend(values);
+        results.into_iter().collect()
+    }
+
+    /// Merge the value sets of all namespaces into one map
+    pub fn merge(namespace_value_sets: &SpurMap<SpurMap<HashSet<Spur>>>) -> SpurMap<HashSet<Spur>> {
+        let mut value_sets: SpurMap<HashSet<Spur>> = SpurMap::new();
+        for namespace_sets in namespace_value_sets.values() {
+            for (key, values) in namespace_sets {
+                value_sets
+                    .entry(key)
+                    .or_default()
+                    .extend(values.iter().copied());
             }
         }
-
-        self.value_sets
+        value_sets
     }
 
     fn collect_value_sets_from_namespace(
@@ -29,6 +29,12 @@ pub struct TypeCache {
 
 static TYPE_CACHE: OnceLock<Arc<TypeCache>> = OnceLock::new();
 
+#[cfg(test)]
+thread_local! {
+    static TEST_TYPE_CACHE: std::cell::Cell<Option<&'static Arc<TypeCache>>> =
+        const { std::cell::Cell::new(None) };
+}
+
 impl TypeCache {
     /// Initialize the type cache by loading Stellaris data
     pub fn initialize_in_background() {
@@ -39,6 +45,11 @@ impl TypeCache {
     }
 
     pub fn get() -> Option<&'static Arc<TypeCache>> {
+        #[cfg(test)]
+        if let Some(cache) = TEST_TYPE_CACHE.get() {
+            return Some(cache);
+        }
+
         TYPE_CACHE.get()
     }
 
@@ -48,197 +59,215 @@ impl TypeCache {
             eprintln!("Initializing type cache");
 
             // Load CWT files - these contain all the type definitions we need
-            let mut cwt_analyzer = Self::load_cwt_files();
-            let interner = get_interner();
-
-            eprintln!("Building cache from CWT types");
-
-            // Pre-compute entity types for quick lookups
-            let mut namespace_types: SpurMap<Vec<Arc<ScopedType>>> = SpurMap::new();
-            for (type_name, type_def) in cwt_analyzer.get_types() {
-                let type_name = get_interner().resolve(&type_name);
-                // Extract namespace from the path
-                let namespace = if let Some(path) = &type_def.path {
-                    get_interner().resolve(path)
-                } else {
-                    eprintln!("Type has no path: {}", type_name);
-                    continue;
-                };
+            Arc::new(Self::from_cwt_analyzer(Self::load_cwt_files()))
+        })
+    }
 
-                let mut scoped_type =
-                    ScopedType::new_cwt(type_def.rules.clone(), Default::default(), None);
+    /// Use a type cache built from the given CWT rules in place of the global one, for the
+    /// current test thread only
+    #[cfg(test)]
+    pub fn set_for_test(cwt: &str) {
+        let module = CwtModuleCell::from_input(cwt.to_string());
+        let mut cwt_analyzer = CwtAnalyzer::new();
+        cwt_analyzer
+            .convert_module(module.borrow_dependent().as_ref().unwrap(), get_interner())
+            .unwrap();
 
-                if let Some(push_scope) = type_def.rule_options.push_scope.as_ref() {
-                    if let Some(scope_name) = cwt_analyzer.resolve_scope_name(*push_scope) {
-                        scoped_type
-                            .scope_stack_mut()
-                            .push_scope_type(scope_name)
-                            .unwrap();
-                    }
-                }
+        let cache = Box::leak(Box::new(Arc::new(Self::from_cwt_analyzer(cwt_analyzer))));
+        TEST_TYPE_CACHE.set(Some(cache));
+    }
 
-                if let Some(replace_scope) = type_def.rule_options.replace_scope.as_ref() {
-                    let mut new_scopes: SpurMap<Spur> = SpurMap::new();
-                    for (key, value) in replace_scope {
-                        if let Some(scope_name) = cwt_analyzer.resolve_scope_name(*value) {
-                            new_scopes.insert(key, scope_name);
-                        }
-                    }
+    /// Build the cache from parsed CWT rules
+    fn from_cwt_analyzer(mut cwt_analyzer: CwtAnalyzer) -> TypeCache {
+        let interner = get_interner();
 
-                    scoped_type
-                        .scope_stack_mut()
-                        .replace_scope_from_strings(new_scopes)
-                        .unwrap();
-                }
+        eprintln!("Building cache from CWT types");
 
-                // Special case for scripted_effects: since they can be used from any scope,
-                // we need to set up a scope stack where this=any, prev=any, prevprev=any, etc.
-                // This allows validation of prev/prevprev scope references without requiring
-                // specific scope types that can't be determined statically.
-                if namespace == "game/common/scripted_effects"
-                    || namespace == "game/common/script_values"
-                {
-                    let mut scripted_effect_scopes: SpurMap<Spur> = SpurMap::new();
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("this"),
-                        interner.get_or_intern("any"),
-                    );
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("prev"),
-                        interner.get_or_intern("any"),
-                    );
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("prevprev"),
-                        interner.get_or_intern("any"),
-                    );
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("prevprevprev"),
-                        interner.get_or_intern("any"),
-                    );
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("prevprevprevprev"),
-                        interner.get_or_intern("any"),
-                    );
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("root"),
-                        interner.get_or_intern("any"),
-                    );
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("from"),
-                        interner.get_or_intern("any"),
-                    );
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("fromfrom"),
-                        interner.get_or_intern("any"),
-                    );
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("fromfromfrom"),
-                        interner.get_or_intern("any"),
-                    );
-                    scripted_effect_scopes.insert(
-                        interner.get_or_intern("fromfromfromfrom"),
-                        interner.get_or_intern("any"),
-                    );
+        // Pre-compute entity types for quick lookups
+        let mut namespace_types: SpurMap<Vec<Arc<ScopedType>>> = SpurMap::new();
+        for (type_name, type_def) in cwt_analyzer.get_types() {
+            let type_name = get_interner().resolve(&type_name);
+            // Extract namespace from the path
+            let namespace = if let Some(path) = &type_def.path {
+                get_interner().resolve(path)
+            } else {
+                eprintln!("Type has no path: {}", type_name);
+                continue;
+            };
 
+            let mut scoped_type =
+                ScopedType::new_cwt(type_def.rules.clone(), Default::default(), None);
+
+            if let Some(push_scope) = type_def.rule_options.push_scope.as_ref() {
+                if let Some(scope_name) = cwt_analyzer.resolve_scope_name(*push_scope) {
                     scoped_type
                         .scope_stack_mut()
-                        .replace_scope_from_strings(scripted_effect_scopes)
+                        .push_scope_type(scope_name)
                         .unwrap();
                 }
+            }
 
-                // Store the type rules for this namespace
-                namespace_types
-                    .entry(interner.get_or_intern(namespace))
-                    .or_default()
-                    .push(Arc::new(scoped_type));
+            if let Some(replace_scope) = type_def.rule_options.replace_scope.as_ref() {
+                let mut new_scopes: SpurMap<Spur> = SpurMap::new();
+                for (key, value) in replace_scope {
+                    if let Some(scope_name) = cwt_analyzer.resolve_scope_name(*value) {
+                        new_scopes.insert(key, scope_name);
+                    }
+                }
+
+                scoped_type
+                    .scope_stack_mut()
+                    .replace_scope_from_strings(new_scopes)
+                    .unwrap();
             }
 
-            // Modifiers are loaded separately so artificially add a modifier type
-            cwt_analyzer.add_type(
-                interner.get_or_intern("modifier"),
-                Arc::new(TypeDefinition {
-                    path: Some(interner.get_or_intern("game/modifiers")),
-                    name_field: None,
-                    skip_root_key: None,
-                    localisation: SpurMap::new(),
-                    rules: Arc::new(CwtType::Unknown),
-                    subtypes: SpurMap::new(),
-                    options: Default::default(),
-                    rule_options: Default::default(),
-                    modifiers: Default::default(),
-                }),
-            );
+            // Special case for scripted_effects: since they can be used from any scope,
+            // we need to set up a scope stack where this=any, prev=any, prevprev=any, etc.
+            // This allows validation of prev/prevprev scope references without requiring
+            // specific scope types that can't be determined statically.
+            if namespace == "game/common/scripted_effects"
+                || namespace == "game/common/script_values"
+            {
+                let mut scripted_effect_scopes: SpurMap<Spur> = SpurMap::new();
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("this"),
+                    interner.get_or_intern("any"),
+                );
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("prev"),
+                    interner.get_or_intern("any"),
+                );
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("prevprev"),
+                    interner.get_or_intern("any"),
+                );
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("prevprevprev"),
+                    interner.get_or_intern("any"),
+                );
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("prevprevprevprev"),
+                    interner.get_or_intern("any"),
+                );
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("root"),
+                    interner.get_or_intern("any"),
+                );
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("from"),
+                    interner.get_or_intern("any"),
+                );
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("fromfrom"),
+                    interner.get_or_intern("any"),
+                );
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("fromfromfrom"),
+                    interner.get_or_intern("any"),
+                );
+                scripted_effect_scopes.insert(
+                    interner.get_or_intern("fromfromfromfrom"),
+                    interner.get_or_intern("any"),
+                );
+
+                scoped_type
+                    .scope_stack_mut()
+                    .replace_scope_from_strings(scripted_effect_scopes)
+                    .unwrap();
+            }
+
+            // Store the type rules for this namespace
+            namespace_types
+                .entry(interner.get_or_intern(namespace))
+                .or_default()
+                .push(Arc::new(scoped_type));
+        }
 
-            let mut inline_script_block = BlockType {
-                type_name: Some(interner.get_or_intern("$inline_script")),
-                properties: SpurMap::new(),
+        // Modifiers are loaded separately so artificially add a modifier type
+        cwt_analyzer.add_type(
+            interner.get_or_intern("modifier"),
+            Arc::new(TypeDefinition {
+                path: Some(interner.get_or_intern("game/modifiers")),
+                name_field: None,
+                skip_root_key: None,
+                localisation: SpurMap::new(),
+                subtype_localisation: SpurMap::new(),
+                rules: Arc::new(CwtType::Unknown),
                 subtypes: SpurMap::new(),
-                subtype_properties: SpurMap::new(),
-                subtype_pattern_properties: SpurMap::new(),
-                pattern_properties: vec![],
-                localisation: None,
+                options: Default::default(),
+                rule_options: Default::default(),
                 modifiers: Default::default(),
-                additional_flags: Default::default(),
-            };
+            }),
+        );
 
-            inline_script_block.properties.insert(
-                interner.get_or_intern("script"),
-                Property {
-                    property_type: Arc::new(CwtType::Reference(ReferenceType::InlineScript)),
-                    documentation: None,
-                    options: Default::default(),
-                },
-            );
+        let mut inline_script_block = BlockType {
+            type_name: Some(interner.get_or_intern("$inline_script")),
+            properties: SpurMap::new(),
+            subtypes: SpurMap::new(),
+            subtype_properties: SpurMap::new(),
+            subtype_pattern_properties: SpurMap::new(),
+            pattern_properties: vec![],
+            localisation: None,
+            modifiers: Default::default(),
+            additional_flags: Default::default(),
+        };
 
-            inline_script_block.properties.insert(
-                interner.get_or_intern("scalar"),
-                Property {
-                    property_type: Arc::new(CwtType::Any),
-                    documentation: None,
-                    options: Default::default(),
-                },
-            );
+        inline_script_block.properties.insert(
+            interner.get_or_intern("script"),
+            Property {
+                property_type: Arc::new(CwtType::Reference(ReferenceType::InlineScript)),
+                documentation: None,
+                options: Default::default(),
+            },
+        );
 
-            // inline_script is special, it can appear anywhere and is not defined in the cwt files
-            cwt_analyzer.add_type(
-                interner.get_or_intern("$inline_script"),
-                Arc::new(TypeDefinition {
-                    path: Some(interner.get_or_intern("game/$inline_scripts")),
-                    name_field: None,
-                    skip_root_key: None,
-                    subtypes: SpurMap::new(),
-                    localisation: SpurMap::new(),
-                    rules: Arc::new(CwtType::Union(vec![
-                        // inline_script = {}
-                        Arc::new(CwtType::Block(inline_script_block)),
-                        // inline_script = "path/to/script"
-                        Arc::new(CwtType::Simple(SimpleType::Scalar)),
-                    ])),
-                    options: Default::default(),
-                    rule_options: Default::default(),
-                    modifiers: Default::default(),
-                }),
-            );
+        inline_script_block.properties.insert(
+            interner.get_or_intern("scalar"),
+            Property {
+                property_type: Arc::new(CwtType::Any),
+                documentation: None,
+                options: Default::default(),
+            },
+        );
 
-            eprintln!(
-                "Built type cache with {} CWT types",
-                cwt_analyzer.get_types().len()
-            );
+        // inline_script is special, it can appear anywhere and is not defined in the cwt files
+        cwt_analyzer.add_type(
+            interner.get_or_intern("$inline_script"),
+            Arc::new(TypeDefinition {
+                path: Some(interner.get_or_intern("game/$inline_scripts")),
+                name_field: None,
+                skip_root_key: None,
+                subtypes: SpurMap::new(),
+                localisation: SpurMap::new(),
+                subtype_localisation: SpurMap::new(),
+                rules: Arc::new(CwtType::Union(vec![
+                    // inline_script = {}
+                    Arc::new(CwtType::Block(inline_script_block)),
+                    // inline_script = "path/to/script"
+                    Arc::new(CwtType::Simple(SimpleType::Scalar)),
+                ])),
+                options: Default::default(),
+                rule_options: Default::default(),
+                modifiers: Default::default(),
+            }),
+        );
 
-            let cwt_analyzer = Arc::new(cwt_analyzer);
+        eprintln!(
+            "Built type cache with {} CWT types",
+            cwt_analyzer.get_types().len()
+        );
 
-            Arc::new(TypeCache {
-                namespace_types,
-                cwt_analyzer: cwt_analyzer.clone(),
-                resolver: TypeResolver::new(cwt_analyzer.clone()),
-            })
-        })
-    }
+        let cwt_analyzer = Arc::new(cwt_analyzer);
 
-    /// Load CWT files from a path relative to the executable
-    fn load_cwt_files() -> CwtAnalyzer {
-        eprintln!("Loading CWT files from relative path");
+        TypeCache {
+            namespace_types,
+            cwt_analyzer: cwt_analyzer.clone(),
+            resolver: TypeResolver::new(cwt_analyzer.clone()),
+        }
+    }
 
+    /// Directory containing the CWT config files, relative to the executable when bundled
+    pub fn get_cwt_directory() -> std::path::PathBuf {
         // First try to load from relative path (for bundled extension)
         let cwt_path = if let Ok(exe_path) = env::current_exe() {
             // Get the directory containing the executable (server/)
@@ -267,10 +296,17 @@ impl TypeCache {
         };
 
         // Fall back to hardcoded path if relative path doesn't work
-        let dir_path = cwt_path.unwrap_or_else(|| {
+        cwt_path.unwrap_or_else(|| {
             eprintln!("Using default config path for current game");
             base_game::game::get_default_config_path()
-        });
+        })
+    }
+
+    /// Load CWT files from a path relative to the executable
+    fn load_cwt_files() -> CwtAnalyzer {
+        eprintln!("Loading CWT files from relative path");
+
+        let dir_path = Self::get_cwt_directory();
 
         let mut cwt_analyzer = CwtAnalyzer::new();
 
@@ -1057,7 +1093,7 @@ impl TypeCache {
 
     /// Check if the cache is ready
     pub fn is_initialized() -> bool {
-        TYPE_CACHE.get().is_some()
+        Self::get().is_some()
     }
 
     /// Get the CWT analyzer
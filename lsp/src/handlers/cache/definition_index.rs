@@ -0,0 +1,902 @@
+//! Location index for definitions
+//!
+//! The game data caches only keep parsed entities, not where they came from: both the base game
+//! and mods are loaded without preserving their ASTs. This index re-reads the files backing each
+//! namespace and records the span of every definition key and scripted variable, so that
+//! features like go-to-definition can jump to the file that defines a key.
+//!
+//! Definition keys follow the same rules as `EntityRestructurer`: `skip_root_key` containers are
+//! indexed by their nested keys, and `name_field` types by the value of their name field.
+
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Instant;
+
+use cw_model::{GameMod, Module, SpurMap, TypeDefinition};
+use cw_parser::{
+    AstEntity, AstEntityItem, AstExpression, AstModule, AstModuleCell, AstNode, AstValue, Position,
+    Span,
+};
+use lasso::Spur;
+use rayon::prelude::*;
+
+use crate::handlers::cache::{
+    FileIndex, GameDataCache, TypeCache, matches_type_key_filter, should_skip_root_key,
+};
+use crate::interner::get_interner;
+
+/// Where an indexed definition was loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DefinitionSource {
+    BaseGame,
+    /// A mod, identified by its root directory
+    Mod(PathBuf),
+    /// The CWT config files (e.g. simple enum values)
+    Config,
+}
+
+/// The location of a single definition
+#[derive(Debug, Clone)]
+pub struct DefinitionLocation {
+    pub path: PathBuf,
+    pub span: Span,
+    pub source: DefinitionSource,
+    /// The CWT type of the definition, when it is a typed entity
+    pub type_name: Option<Spur>,
+}
+
+/// Definitions found in a single file
+#[derive(Default)]
+struct FileDefinitions {
+    definitions: Vec<(Spur, DefinitionLocation)>,
+    scripted_variables: Vec<(Spur, DefinitionLocation)>,
+}
+
+/// Maps definition keys to the files and spans that define them
+#[derive(Default)]
+pub struct DefinitionIndex {
+    /// namespace -> definition key -> locations
+    definitions: SpurMap<SpurMap<Vec<DefinitionLocation>>>,
+
+    /// Scripted variable name (including the leading `@`) -> locations
+    scripted_variables: SpurMap<Vec<DefinitionLocation>>,
+
+    /// namespace -> files that were indexed for it
+    namespace_files: SpurMap<Vec<(PathBuf, DefinitionSource)>>,
+}
+
+static DEFINITION_INDEX: OnceLock<RwLock<DefinitionIndex>> = OnceLock::new();
+static BASE_GAME_INDEXED: AtomicBool = AtomicBool::new(false);
+static CWT_FILES: OnceLock<Vec<(PathBuf, String)>> = OnceLock::new();
+
+impl DefinitionIndex {
+    fn global() -> &'static RwLock<DefinitionIndex> {
+        DEFINITION_INDEX.get_or_init(|| RwLock::new(DefinitionIndex::default()))
+    }
+
+    /// Index the base game in a background thread once the game data is available
+    pub fn initialize_in_background() {
+        std::thread::spawn(|| {
+            Self::index_base_game_blocking();
+        });
+    }
+
+    /// Check if the base game has been indexed
+    pub fn is_initialized() -> bool {
+        BASE_GAME_INDEXED.load(Ordering::Acquire)
+    }
+
+    fn index_base_game_blocking() {
+        if Self::is_initialized() {
+            return;
+        }
+
+        while !TypeCache::is_initialized()
+            || !GameDataCache::is_initialized()
+            || !FileIndex::is_initialized()
+        {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+
+        let start = Instant::now();
+
+        let game_root = FileIndex::get()
+            .and_then(|index| {
+                index
+                    .read()
+                    .ok()
+                    .map(|index| index.game_root().to_path_buf())
+            })
+            .unwrap_or_default();
+
+        if game_root.as_os_str().is_empty() {
+            eprintln!("Warning: No game root path found, definition index will be empty");
+            BASE_GAME_INDEXED.store(true, Ordering::Release);
+            return;
+        }
+
+        let modules: Vec<Arc<Module>> = GameDataCache::get()
+            .unwrap()
+            .get_namespaces()
+            .values()
+            .flat_map(|namespace| namespace.modules.values().cloned())
+            .collect();
+
+        let count = Self::index_modules(&game_root, &modules, DefinitionSource::BaseGame);
+
+        BASE_GAME_INDEXED.store(true, Ordering::Release);
+
+        eprintln!(
+            "Indexed {} base game definitions in {:?}",
+            count,
+            start.elapsed()
+        );
+    }
+
+    /// Index the definitions of a mod, replacing anything previously indexed for it.
+    /// Indexing happens in a background thread since it needs the type cache.
+    pub fn index_mod(game_mod: &GameMod) {
+        let Some(mod_root) = game_mod.definition.path.clone() else {
+            return;
+        };
+
+        let modules: Vec<Arc<Module>> = game_mod
+            .namespaces
+            .values()
+            .flat_map(|namespace| namespace.modules.values().cloned())
+            .collect();
+        let mod_name = game_mod.definition.name.clone();
+
+        std::thread::spawn(move || {
+            while !TypeCache::is_initialized() {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+
+            let start = Instant::now();
+            let source = DefinitionSource::Mod(mod_root.clone());
+
+            Self::global().write().unwrap().remove_source(&source);
+            let count = Self::index_modules(&mod_root, &modules, source);
+
+            eprintln!(
+                "Indexed {} definitions for mod '{}' in {:?}",
+                count,
+                mod_name,
+                start.elapsed()
+            );
+        });
+    }
+
+    /// Parse the files backing the given modules and add their definitions to the global index
+    fn index_modules(root: &Path, modules: &[Arc<Module>], source: DefinitionSource) -> usize {
+        let type_defs_by_namespace = get_type_defs_by_namespace();
+        let interner = get_interner();
+
+        let files: Vec<(Spur, PathBuf)> = modules
+            .iter()
+            .map(|module| {
+                let namespace =
+                    TypeCache::get_actual_namespace(interner.get_or_intern(&module.namespace));
+                (namespace, module_path(root, module))
+            })
+            .collect();
+
+        let results: Vec<(Spur, PathBuf, FileDefinitions)> = files
+            .into_par_iter()
+            .filter_map(|(namespace, path)| {
+                let type_defs = type_defs_by_namespace
+                    .get(&namespace)
+                    .map(|defs| defs.as_slice())
+                    .unwrap_or(&[]);
+                let definitions = index_file(&path, type_defs, &source)?;
+                Some((namespace, path, definitions))
+            })
+            .collect();
+
+        let mut index = Self::global().write().unwrap();
+        let mut count = 0;
+
+        for (namespace, path, file_definitions) in results {
+            count += index.insert_file(namespace, path, source.clone(), file_definitions);
+        }
+
+        count
+    }
+
+    /// Re-index a single mod file after it changed on disk, or drop its definitions if it was
+    /// deleted
+    pub fn reindex_file(mod_root: &Path, namespace: &str, path: &Path) {
+        if !TypeCache::is_initialized() {
+            return;
+        }
+
+        let namespace = TypeCache::get_actual_namespace(get_interner().get_or_intern(namespace));
+        let source = DefinitionSource::Mod(mod_root.to_path_buf());
+
+        let file_definitions = if path.is_file() {
+            let type_defs_by_namespace = get_type_defs_by_namespace();
+            let type_defs = type_defs_by_namespace
+                .get(&namespace)
+                .map(|defs| defs.as_slice())
+                .unwrap_or(&[]);
+            index_file(path, type_defs, &source)
+        } else {
+            None
+        };
+
+        let mut index = Self::global().write().unwrap();
+        index.remove_file(path);
+        if let Some(file_definitions) = file_definitions {
+            index.insert_file(namespace, path.to_path_buf(), source, file_definitions);
+        }
+    }
+
+    /// Add the definitions found in a file, returning how many were added
+    fn insert_file(
+        &mut self,
+        namespace: Spur,
+        path: PathBuf,
+        source: DefinitionSource,
+        file_definitions: FileDefinitions,
+    ) -> usize {
+        let count = file_definitions.definitions.len();
+
+        let namespace_definitions = self.definitions.entry(namespace).or_default();
+        for (key, location) in file_definitions.definitions {
+            namespace_definitions.entry(key).or_default().push(location);
+        }
+
+        for (name, location) in file_definitions.scripted_variables {
+            self.scripted_variables
+                .entry(name)
+                .or_default()
+                .push(location);
+        }
+
+        self.namespace_files
+            .entry(namespace)
+            .or_default()
+            .push((path, source));
+
+        count
+    }
+
+    /// Remove everything that was indexed from a single file
+    fn remove_file(&mut self, path: &Path) {
+        for (_, namespace_definitions) in self.definitions.iter_mut() {
+            for (_, locations) in namespace_definitions.iter_mut() {
+                locations.retain(|location| location.path != path);
+            }
+        }
+
+        for (_, locations) in self.scripted_variables.iter_mut() {
+            locations.retain(|location| location.path != path);
+        }
+
+        for (_, files) in self.namespace_files.iter_mut() {
+            files.retain(|(file_path, _)| file_path != path);
+        }
+    }
+
+    /// Remove everything that was indexed from the given source
+    fn remove_source(&mut self, source: &DefinitionSource) {
+        for (_, namespace_definitions) in self.definitions.iter_mut() {
+            for (_, locations) in namespace_definitions.iter_mut() {
+                locations.retain(|location| &location.source != source);
+            }
+        }
+
+        for (_, locations) in self.scripted_variables.iter_mut() {
+            locations.retain(|location| &location.source != source);
+        }
+
+        for (_, files) in self.namespace_files.iter_mut() {
+            files.retain(|(_, file_source)| file_source != source);
+        }
+    }
+
+    /// Get the locations where a key is defined in a namespace, base game first
+    pub fn get_definitions(namespace: Spur, key: Spur) -> Vec<DefinitionLocation> {
+        let namespace = TypeCache::get_actual_namespace(namespace);
+        let index = Self::global().read().unwrap();
+
+        let mut locations = index
+            .definitions
+            .get(&namespace)
+            .and_then(|definitions| definitions.get(&key))
+            .cloned()
+            .unwrap_or_default();
+
+        sort_by_load_order(&mut locations);
+        locations
+    }
+
+    /// Get the locations where a scripted variable (including the leading `@`) is defined
+    pub fn get_scripted_variable(name: Spur) -> Vec<DefinitionLocation> {
+        let index = Self::global().read().unwrap();
+
+        let mut locations = index
+            .scripted_variables
+            .get(&name)
+            .cloned()
+            .unwrap_or_default();
+
+        sort_by_load_order(&mut locations);
+        locations
+    }
+
+    /// Get all files that were indexed for a namespace
+    pub fn get_namespace_files(namespace: Spur) -> Vec<PathBuf> {
+        let namespace = TypeCache::get_actual_namespace(namespace);
+        let index = Self::global().read().unwrap();
+
+        index
+            .namespace_files
+            .get(&namespace)
+            .map(|files| files.iter().map(|(path, _)| path.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get every file that was indexed for a namespace along with where it was loaded from, in
+    /// load order
+    pub fn get_namespace_file_sources(namespace: Spur) -> Vec<(PathBuf, DefinitionSource)> {
+        let namespace = TypeCache::get_actual_namespace(namespace);
+        let index = Self::global().read().unwrap();
+
+        let mut files = index
+            .namespace_files
+            .get(&namespace)
+            .cloned()
+            .unwrap_or_default();
+        files.sort_by_key(|(_, source)| source_rank(source));
+        files
+    }
+
+    /// Find definitions whose key is accepted by `score`, returning at most `limit` of them with
+    /// the highest scores first. Results are `(namespace, key, location)`.
+    pub fn search_definitions(
+        score: impl Fn(&str) -> Option<i64> + Sync,
+        limit: usize,
+    ) -> Vec<(Spur, Spur, DefinitionLocation)> {
+        let index = Self::global().read().unwrap();
+        let interner = get_interner();
+
+        let mut matches: Vec<(i64, Spur, Spur, &DefinitionLocation)> = index
+            .definitions
+            .as_inner()
+            .par_iter()
+            .flat_map_iter(|(namespace, definitions)| {
+                let namespace = namespace.0;
+                definitions
+                    .iter()
+                    .filter_map(|(key, locations)| {
+                        score(interner.resolve(&key)).map(|score| (score, key, locations))
+                    })
+                    .flat_map(move |(score, key, locations)| {
+                        locations
+                            .iter()
+                            .map(move |location| (score, namespace, key, location))
+                    })
+            })
+            .collect();
+
+        matches.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then_with(|| interner.resolve(&a.2).cmp(interner.resolve(&b.2)))
+        });
+        matches.truncate(limit);
+
+        matches
+            .into_iter()
+            .map(|(_, namespace, key, location)| (namespace, key, location.clone()))
+            .collect()
+    }
+
+    /// Get every indexed file along with the namespace it belongs to
+    pub fn get_all_files() -> Vec<(Spur, PathBuf)> {
+        let index = Self::global().read().unwrap();
+
+        index
+            .namespace_files
+            .iter()
+            .flat_map(|(namespace, files)| {
+                files.iter().map(move |(path, _)| (namespace, path.clone()))
+            })
+            .collect()
+    }
+
+    /// Find where a value appears in the files of a namespace. Used for values that are not
+    /// keyed definitions, like complex enum values extracted from nested fields.
+    pub fn find_value_in_namespace(namespace: Spur, value: &str) -> Option<DefinitionLocation> {
+        let namespace = TypeCache::get_actual_namespace(namespace);
+
+        let files = {
+            let index = Self::global().read().unwrap();
+            index.namespace_files.get(&namespace).cloned()?
+        };
+
+        files.into_iter().find_map(|(path, source)| {
+            let content = fs::read_to_string(&path).ok()?;
+            let offset = find_token(&content, value, 0..content.len())?;
+            Some(DefinitionLocation {
+                span: LineIndex::new(&content).span(&content, offset..offset + value.len()),
+                path,
+                source,
+                type_name: None,
+            })
+        })
+    }
+
+    /// Find a simple enum value in the CWT config files, e.g. `enums = { enum[key] = { value } }`
+    pub fn find_enum_value(enum_key: &str, value: &str) -> Option<DefinitionLocation> {
+        let enum_marker = format!("enum[{}]", enum_key);
+
+        get_cwt_files().iter().find_map(|(path, content)| {
+            let marker_offset = content.find(&enum_marker)?;
+            let block = find_block_after(content, marker_offset + enum_marker.len())?;
+            let offset = find_token(content, value, block)?;
+
+            Some(DefinitionLocation {
+                path: path.clone(),
+                span: LineIndex::new(content).span(content, offset..offset + value.len()),
+                source: DefinitionSource::Config,
+                type_name: None,
+            })
+        })
+    }
+}
+
+/// Base game definitions come first, then mods and config in the order they were indexed
+fn sort_by_load_order(locations: &mut [DefinitionLocation]) {
+    locations.sort_by_key(|location| source_rank(&location.source));
+}
+
+fn source_rank(source: &DefinitionSource) -> u8 {
+    match source {
+        DefinitionSource::BaseGame => 0,
+        DefinitionSource::Mod(_) => 1,
+        DefinitionSource::Config => 2,
+    }
+}
+
+/// Get the absolute path of the file backing a module
+pub(super) fn module_path(root: &Path, module: &Module) -> PathBuf {
+    match module.namespace.strip_prefix("game/") {
+        Some(relative_dir) => root.join(relative_dir).join(&module.filename),
+        None => root.join(&module.filename),
+    }
+}
+
+/// A CWT type definition along with its name
+type NamedTypeDefinition = (Spur, Arc<TypeDefinition>);
+
+/// Group the CWT type definitions by the namespace they apply to
+fn get_type_defs_by_namespace() -> SpurMap<Vec<NamedTypeDefinition>> {
+    let mut result: SpurMap<Vec<NamedTypeDefinition>> = SpurMap::new();
+
+    if let Some(type_cache) = TypeCache::get() {
+        for (type_name, type_def) in type_cache.get_cwt_analyzer().get_types() {
+            if let Some(path) = &type_def.path {
+                result
+                    .entry(TypeCache::get_actual_namespace(*path))
+                    .or_default()
+                    .push((type_name, type_def.clone()));
+            }
+        }
+    }
+
+    result
+}
+
+/// A definition found in a parsed file
+pub struct ModuleDefinition<'a> {
+    /// The definition key, after applying `skip_root_key` and `name_field`
+    pub name: Spur,
+    /// Range of the name, which is either the entity key or the value of its name field
+    pub name_range: Range<usize>,
+    /// The CWT type of the definition, if one of the namespace's types matched it
+    pub type_name: Option<Spur>,
+    pub expression: &'a AstExpression<'a>,
+    pub entity: &'a AstEntity<'a>,
+}
+
+/// Find the definitions in a parsed file of a namespace, restructured the same way
+/// `EntityRestructurer` does it
+pub fn find_module_definitions<'a>(
+    module: &'a AstModule<'a>,
+    namespace: Spur,
+) -> Vec<ModuleDefinition<'a>> {
+    let type_defs = get_type_defs_by_namespace()
+        .get(&TypeCache::get_actual_namespace(namespace))
+        .cloned()
+        .unwrap_or_default();
+
+    collect_module_definitions(module, &type_defs)
+}
+
+fn collect_module_definitions<'a>(
+    module: &'a AstModule<'a>,
+    type_defs: &[NamedTypeDefinition],
+) -> Vec<ModuleDefinition<'a>> {
+    let interner = get_interner();
+    let mut definitions = Vec::new();
+
+    let matching_type = |key: Spur, type_defs: &[&NamedTypeDefinition]| {
+        type_defs
+            .iter()
+            .find(|(_, type_def)| {
+                type_def
+                    .rule_options
+                    .type_key_filter
+                    .as_ref()
+                    .is_none_or(|filter| matches_type_key_filter(key, filter))
+            })
+            .map(|named| (*named).clone())
+    };
+
+    for item in &module.items {
+        let AstEntityItem::Expression(expression) = item else {
+            continue;
+        };
+
+        let key_str = expression.key.raw_value();
+        if key_str.starts_with('@') {
+            continue;
+        }
+        let key = interner.get_or_intern(key_str);
+
+        let AstValue::Entity(entity) = &expression.value else {
+            continue;
+        };
+
+        let skip_root_type_defs: Vec<&NamedTypeDefinition> = type_defs
+            .iter()
+            .filter(|(_, type_def)| should_skip_root_key(key, &type_def.skip_root_key))
+            .collect();
+
+        if !skip_root_type_defs.is_empty() {
+            // Container key, the definitions are the nested entities
+            for child in &entity.items {
+                let AstEntityItem::Expression(child) = child else {
+                    continue;
+                };
+                let AstValue::Entity(child_entity) = &child.value else {
+                    continue;
+                };
+
+                let child_key = interner.get_or_intern(child.key.raw_value());
+                let Some((type_name, type_def)) = matching_type(child_key, &skip_root_type_defs)
+                else {
+                    continue;
+                };
+
+                let (name, name_range) = find_name_field(child_entity, type_def.name_field)
+                    .unwrap_or((child_key, child.key.span_range()));
+                definitions.push(ModuleDefinition {
+                    name,
+                    name_range,
+                    type_name: Some(type_name),
+                    expression: child,
+                    entity: child_entity,
+                });
+            }
+            continue;
+        }
+
+        // Types that skip a root key don't apply to entities outside their container
+        let root_type_defs: Vec<&NamedTypeDefinition> = type_defs
+            .iter()
+            .filter(|(_, type_def)| type_def.skip_root_key.is_none())
+            .collect();
+        let matched = matching_type(key, &root_type_defs);
+
+        let name_field = matched
+            .as_ref()
+            .and_then(|(_, type_def)| type_def.name_field);
+        let (name, name_range) =
+            find_name_field(entity, name_field).unwrap_or((key, expression.key.span_range()));
+        definitions.push(ModuleDefinition {
+            name,
+            name_range,
+            type_name: matched.map(|(type_name, _)| type_name),
+            expression,
+            entity,
+        });
+    }
+
+    definitions
+}
+
+/// Parse a file and collect its definition keys and scripted variables
+fn index_file(
+    path: &Path,
+    type_defs: &[NamedTypeDefinition],
+    source: &DefinitionSource,
+) -> Option<FileDefinitions> {
+    let content = fs::read_to_string(path).ok()?;
+    let ast = AstModuleCell::from_input(content);
+    let module = ast.borrow_dependent().as_ref().ok()?;
+    let input = ast.borrow_owner();
+
+    let interner = get_interner();
+    let line_index = LineIndex::new(input);
+    let mut result = FileDefinitions::default();
+
+    let location = |range: Range<usize>, type_name: Option<Spur>| DefinitionLocation {
+        path: path.to_path_buf(),
+        span: line_index.span(input, range),
+        source: source.clone(),
+        type_name,
+    };
+
+    for item in &module.items {
+        if let AstEntityItem::Expression(expression) = item
+            && expression.key.raw_value().starts_with('@')
+        {
+            result.scripted_variables.push((
+                interner.get_or_intern(expression.key.raw_value()),
+                location(expression.key.span_range(), None),
+            ));
+        }
+    }
+
+    for definition in collect_module_definitions(module, type_defs) {
+        result.definitions.push((
+            definition.name,
+            location(definition.name_range, definition.type_name),
+        ));
+    }
+
+    Some(result)
+}
+
+/// Find the value of a `name_field` in an entity, along with its span
+fn find_name_field(entity: &AstEntity, name_field: Option<Spur>) -> Option<(Spur, Range<usize>)> {
+    let name_field = name_field?;
+    let interner = get_interner();
+
+    entity.items.iter().find_map(|item| match item {
+        AstEntityItem::Expression(expression)
+            if interner.get_or_intern(expression.key.raw_value()) == name_field =>
+        {
+            match &expression.value {
+                AstValue::String(name) => {
+                    Some((interner.get_or_intern(name.raw_value()), name.span_range()))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Load the contents of all CWT config files, once
+fn get_cwt_files() -> &'static Vec<(PathBuf, String)> {
+    CWT_FILES.get_or_init(|| {
+        fn visit_dir(dir: &Path, files: &mut Vec<(PathBuf, String)>) {
+            let Ok(entries) = fs::read_dir(dir) else {
+                return;
+            };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if path.is_dir() {
+                    visit_dir(&path, files);
+                } else if path.extension().is_some_and(|ext| ext == "cwt")
+                    && let Ok(content) = fs::read_to_string(&path)
+                {
+                    files.push((path, content));
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        visit_dir(&TypeCache::get_cwt_directory(), &mut files);
+        files
+    })
+}
+
+/// Find the `{ ... }` block that starts after `offset`, returning the range inside the braces
+fn find_block_after(content: &str, offset: usize) -> Option<Range<usize>> {
+    let open = offset + content[offset..].find('{')?;
+    let mut depth = 0;
+
+    for (i, ch) in content[open..].char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + 1..open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Find `token` as a whole word (optionally quoted) within `range` of `content`
+fn find_token(content: &str, token: &str, range: Range<usize>) -> Option<usize> {
+    if token.is_empty() {
+        return None;
+    }
+
+    let haystack = &content[range.clone()];
+    let mut search_from = 0;
+
+    while let Some(found) = haystack[search_from..].find(token) {
+        let start = search_from + found;
+        let end = start + token.len();
+
+        let before_ok = haystack[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|ch| !is_word_char(ch));
+        let after_ok = haystack[end..]
+            .chars()
+            .next()
+            .is_none_or(|ch| !is_word_char(ch));
+
+        // Skip matches inside comments
+        let line_start = haystack[..start].rfind('\n').map_or(0, |i| i + 1);
+        let in_comment = starts_comment(&haystack[line_start..start]);
+
+        if before_ok && after_ok && !in_comment {
+            return Some(range.start + start);
+        }
+
+        search_from = end;
+    }
+
+    None
+}
+
+/// Find the offset of every occurrence of `needle` in `content` outside of comments, ignoring
+/// ASCII case like the interner does. This includes occurrences inside longer words like
+/// `building_<needle>_desc`, and possibly several within one word.
+pub fn find_word_occurrences(content: &str, needle: &str) -> Vec<usize> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    // ASCII lowercasing keeps byte offsets intact
+    let lowercase_content = content.to_ascii_lowercase();
+    let lowercase_needle = needle.to_ascii_lowercase();
+
+    lowercase_content
+        .match_indices(&lowercase_needle)
+        .map(|(start, _)| start)
+        .filter(|start| {
+            let line_start = content[..*start].rfind('\n').map_or(0, |i| i + 1);
+            !starts_comment(&content[line_start..*start])
+        })
+        .collect()
+}
+
+/// Whether the text before a match on its line opens a comment, i.e. has a `#` outside of a
+/// quoted string
+fn starts_comment(line_before: &str) -> bool {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for ch in line_before.chars() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '.' | ':' | '@' | '$')
+}
+
+/// Line start offsets of a file, for converting many byte offsets to positions cheaply
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(input: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(input.match_indices('\n').map(|(i, _)| i + 1));
+        Self { line_starts }
+    }
+
+    /// The 0-based line of a byte offset
+    pub fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// Convert a byte offset to a 1-based line/column position. Columns count UTF-16 code units,
+    /// like LSP positions do, so that spans can be handed to the client as they are.
+    pub fn position(&self, input: &str, offset: usize) -> Position {
+        let line = self.line(offset);
+        let line_start = self.line_starts[line];
+        let column = input[line_start..offset].encode_utf16().count() + 1;
+
+        Position::new(line + 1, column, offset)
+    }
+
+    pub fn span(&self, input: &str, range: Range<usize>) -> Span {
+        Span::new(
+            self.position(input, range.start),
+            self.position(input, range.end),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_word_occurrences() {
+        let content = "a = tech_lasers\nb = tech_lasers_2\nc = \"Tech_Lasers\"\n";
+        let occurrences = find_word_occurrences(content, "tech_lasers");
+
+        assert_eq!(occurrences, vec![4, 20, 39]);
+    }
+
+    #[test]
+    fn test_find_word_occurrences_skips_comments() {
+        let content = "# uses tech_lasers\na = tech_lasers # tech_lasers\n";
+        let occurrences = find_word_occurrences(content, "tech_lasers");
+
+        assert_eq!(occurrences, vec![23]);
+    }
+
+    #[test]
+    fn test_find_word_occurrences_in_strings_with_hashes() {
+        let content = "a = \"#1 tech_lasers\" b = tech_lasers\nc = \"\\\"#\" tech_lasers\n";
+        let occurrences = find_word_occurrences(content, "tech_lasers");
+
+        assert_eq!(occurrences, vec![8, 25, 47]);
+    }
+
+    #[test]
+    fn test_find_token_skips_comments_outside_strings() {
+        let content = "name = \"#\" tech # tech\n";
+
+        assert_eq!(find_token(content, "tech", 0..content.len()), Some(11));
+        let content = "# tech\ntech = {}\n";
+        assert_eq!(find_token(content, "tech", 0..content.len()), Some(7));
+    }
+
+    #[test]
+    fn test_line_index_positions() {
+        let content = "a = b\nfoo = bar\n";
+        let span = LineIndex::new(content).span(content, 12..15);
+
+        assert_eq!((span.start.line, span.start.column), (2, 7));
+        assert_eq!((span.end.line, span.end.column), (2, 10));
+    }
+
+    #[test]
+    fn test_line_index_counts_utf16_columns() {
+        let content = "name = \"🚀\" foo = bar\n";
+        let start = content.find("foo").unwrap();
+        let span = LineIndex::new(content).span(content, start..start + 3);
+
+        assert_eq!((span.start.line, span.start.column), (1, 13));
+        assert_eq!((span.end.line, span.end.column), (1, 16));
+    }
+
+    #[test]
+    fn test_line_index_lines() {
+        let line_index = LineIndex::new("a = b\nfoo = bar\n");
+
+        assert_eq!(line_index.line(0), 0);
+        assert_eq!(line_index.line(5), 0);
+        assert_eq!(line_index.line(6), 1);
+        assert_eq!(line_index.line(16), 2);
+    }
+}
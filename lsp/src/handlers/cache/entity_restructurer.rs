@@ -20,6 +20,26 @@ use crate::{
 /// This is needed for subtype determination when entities are restructured
 pub const ORIGINAL_KEY_PROPERTY: &str = "_original_key";
 
+/// Check whether a root key is a container that `skip_root_key` skips over
+pub fn should_skip_root_key(key: Spur, skip_config: &Option<SkipRootKey>) -> bool {
+    match skip_config {
+        Some(SkipRootKey::Specific(skip_key)) => key == *skip_key,
+        Some(SkipRootKey::Any) => true,
+        Some(SkipRootKey::Except(exceptions)) => !exceptions.contains(&key),
+        Some(SkipRootKey::Multiple(keys)) => keys.contains(&key),
+        None => false,
+    }
+}
+
+/// Check whether a definition key passes a type's `type_key_filter`
+pub fn matches_type_key_filter(key: Spur, filter: &TypeKeyFilter) -> bool {
+    match filter {
+        TypeKeyFilter::Specific(required_key) => key == *required_key,
+        TypeKeyFilter::OneOf(required_keys) => required_keys.contains(&key),
+        TypeKeyFilter::Not(excluded_key) => key != *excluded_key,
+    }
+}
+
 /// Post-processor that restructures entities according to type definitions
 ///
 /// Handles two main restructuring scenarios:
@@ -176,114 +196,124 @@ impl EntityRestructurer {
     fn process_all_namespaces(&self, restructured: &mut RestructuredEntities) {
         // Get type definitions that need special handling
         let types_with_special_loading = self.get_types_needing_restructure();
-        let interner = get_interner();
 
         eprintln!(
             "Found {} types needing restructure",
             types_with_special_loading.len(),
         );
 
-        // Process base game namespaces in parallel and collect results
-        let base_game_results: Vec<_> = types_with_special_loading
-            .as_inner()
-            .par_iter()
-            .filter_map(|(namespace, type_defs)| {
-                let actual_namespace = TypeCache::get_actual_namespace(namespace.0);
-                if let Some(namespace_data) = self.game_data.get_namespaces().get(&actual_namespace)
-                {
-                    let (entities, info) =
-                        self.process_namespace(actual_namespace, type_defs, namespace_data);
-                    Some((*namespace, entities, info))
-                } else {
-                    eprintln!(
-                        "WARN: Namespace {} not found in base game data, skipping",
-                        interner.resolve(&namespace.0)
-                    );
-                    None
-                }
-            })
-            .collect();
-
-        // Process mod data namespaces in parallel and collect results
         let mod_namespaces = ModDataCache::get_namespaces();
-        let mod_results: Vec<_> = types_with_special_loading
+        let results: Vec<_> = types_with_special_loading
             .as_inner()
             .par_iter()
             .filter_map(|(namespace, type_defs)| {
-                let actual_namespace = TypeCache::get_actual_namespace(namespace.0);
-                if let Some(mod_namespace_data) = mod_namespaces.get(&actual_namespace) {
-                    let (entities, info) =
-                        self.process_namespace(actual_namespace, type_defs, mod_namespace_data);
-                    Some((*namespace, entities, info, true)) // true indicates this is mod data
-                } else {
-                    None
-                }
+                self.restructure_namespace(namespace.0, type_defs, &mod_namespaces)
+                    .map(|result| (namespace.0, result))
             })
             .collect();
 
-        // Store lengths before moving the data
-        let base_game_count = base_game_results.len();
-        let mod_count = mod_results.len();
+        eprintln!("Processed {} namespaces for restructuring", results.len());
 
-        // Merge base game results back into the restructured entities
-        for (namespace, entities, info) in base_game_results {
-            restructured.entities.insert(
-                namespace.0,
-                entities
-                    .into_iter()
-                    .map(|(k, v)| (k, Arc::new(v)))
-                    .collect(),
-            );
-            restructured
-                .restructured_namespaces
-                .insert(namespace.0, info);
+        for (namespace, (entities, info)) in results {
+            restructured.entities.insert(namespace, entities);
+            restructured.restructured_namespaces.insert(namespace, info);
         }
+    }
 
-        // Merge mod results with base game results (mod data can override/extend base game data)
-        for (namespace, mod_entities, mod_info, _is_mod) in mod_results {
-            let actual_namespace = namespace.0;
-
-            // Get existing base game entities for this namespace or create new map
-            let mut combined_entities = restructured
-                .entities
-                .get(&actual_namespace)
-                .cloned()
-                .unwrap_or_else(|| SpurMap::new());
+    /// Restructure the entities of a single namespace from both the base game and mod data. Mod
+    /// entities override base game entities with the same key.
+    fn restructure_namespace(
+        &self,
+        namespace: Spur,
+        type_defs: &Vec<Arc<TypeDefinition>>,
+        mod_namespaces: &SpurMap<Namespace>,
+    ) -> Option<(SpurMap<Arc<Entity>>, RestructureInfo)> {
+        let actual_namespace = TypeCache::get_actual_namespace(namespace);
 
-            // Add mod entities (may override base game entities with same keys)
-            for (key, entity) in mod_entities {
-                combined_entities.insert(key, Arc::new(entity));
+        let base_game_result = match self.game_data.get_namespaces().get(&actual_namespace) {
+            Some(namespace_data) => {
+                Some(self.process_namespace(actual_namespace, type_defs, namespace_data))
             }
+            None => {
+                eprintln!(
+                    "WARN: Namespace {} not found in base game data, skipping",
+                    get_interner().resolve(&namespace)
+                );
+                None
+            }
+        };
+        let mod_result = mod_namespaces.get(&actual_namespace).map(|namespace_data| {
+            self.process_namespace(actual_namespace, type_defs, namespace_data)
+        });
 
-            // Update the combined entities
-            restructured
-                .entities
-                .insert(actual_namespace, combined_entities);
+        match (base_game_result, mod_result) {
+            (None, None) => None,
+            (Some((entities, info)), None) | (None, Some((entities, info))) => Some((
+                entities
+                    .into_iter()
+                    .map(|(key, entity)| (key, Arc::new(entity)))
+                    .collect(),
+                info,
+            )),
+            (Some((base_entities, base_info)), Some((mod_entities, mod_info))) => {
+                let mut combined_entities: SpurMap<Arc<Entity>> = base_entities
+                    .into_iter()
+                    .map(|(key, entity)| (key, Arc::new(entity)))
+                    .collect();
+                for (key, entity) in mod_entities {
+                    combined_entities.insert(key, Arc::new(entity));
+                }
 
-            // Update restructure info (mod info takes precedence, but preserve counts if base game info exists)
-            if let Some(existing_info) = restructured.restructured_namespaces.get(&actual_namespace)
-            {
+                // Mod info takes precedence, but the counts cover both
                 let combined_info = RestructureInfo {
-                    skip_root_key: mod_info.skip_root_key.or(existing_info.skip_root_key),
-                    name_field: mod_info.name_field.or(existing_info.name_field),
-                    original_entity_count: existing_info.original_entity_count
+                    skip_root_key: mod_info.skip_root_key.or(base_info.skip_root_key),
+                    name_field: mod_info.name_field.or(base_info.name_field),
+                    original_entity_count: base_info.original_entity_count
                         + mod_info.original_entity_count,
-                    restructured_entity_count: existing_info.restructured_entity_count
+                    restructured_entity_count: base_info.restructured_entity_count
                         + mod_info.restructured_entity_count,
                 };
-                restructured
-                    .restructured_namespaces
-                    .insert(actual_namespace, combined_info);
-            } else {
-                restructured
-                    .restructured_namespaces
-                    .insert(actual_namespace, mod_info);
+
+                Some((combined_entities, combined_info))
+            }
+        }
+    }
+
+    /// Restructure only the given namespaces again, e.g. after some of their files changed, and
+    /// swap the result in at once so readers never see a partially rebuilt cache
+    pub fn reload_namespaces(&self, namespaces: &HashSet<Spur>) {
+        let Some(current) = Self::get() else {
+            self.load();
+            return;
+        };
+
+        let start = std::time::Instant::now();
+        let mod_namespaces = ModDataCache::get_namespaces();
+        let mut restructured = (*current).clone();
+
+        for (namespace, type_defs) in self.get_types_needing_restructure() {
+            if !namespaces.contains(&TypeCache::get_actual_namespace(namespace)) {
+                continue;
+            }
+
+            match self.restructure_namespace(namespace, &type_defs, &mod_namespaces) {
+                Some((entities, info)) => {
+                    restructured.entities.insert(namespace, entities);
+                    restructured.restructured_namespaces.insert(namespace, info);
+                }
+                None => {
+                    restructured.entities.remove(&namespace);
+                    restructured.restructured_namespaces.remove(&namespace);
+                }
             }
         }
 
+        *RESTRUCTURED_ENTITIES.write().unwrap() = Some(Arc::new(restructured));
+
         eprintln!(
-            "Processed {} base game namespaces and {} mod namespaces for restructuring",
-            base_game_count, mod_count
+            "Entity restructuring of {} namespaces completed in {:?}",
+            namespaces.len(),
+            start.elapsed()
         );
     }
 
@@ -375,9 +405,7 @@ impl EntityRestructurer {
                         // Check if any type definition wants to skip this root key
                         let skip_root_type_defs: Vec<Arc<TypeDefinition>> = type_defs
                             .iter()
-                            .filter(|type_def| {
-                                self.should_skip_root_key(key, &type_def.skip_root_key)
-                            })
+                            .filter(|type_def| should_skip_root_key(key, &type_def.skip_root_key))
                             .cloned()
                             .collect();
 
@@ -477,19 +505,6 @@ impl EntityRestructurer {
         (restructured_entities, info)
     }
 
-    /// Check if a root key should be skipped
-    fn should_skip_root_key(&self, key: Spur, skip_config: &Option<SkipRootKey>) -> bool {
-        match skip_config {
-            Some(SkipRootKey::Specific(skip_key)) => key == *skip_key,
-            Some(SkipRootKey::Any) => true,
-            Some(SkipRootKey::Except(exceptions)) => {
-                !exceptions.iter().any(|exception| *exception == key)
-            }
-            Some(SkipRootKey::Multiple(keys)) => keys.iter().any(|k| *k == key),
-            None => false,
-        }
-    }
-
     /// Check if a key passes the type_key_filter
     fn passes_type_key_filter(&self, key: Spur, type_defs: &Vec<Arc<TypeDefinition>>) -> bool {
         // If no type_key_filter is defined, allow all keys
@@ -504,7 +519,7 @@ impl EntityRestructurer {
 
         // Check if the key matches any of the type_key_filters
         for filter in &type_key_filters {
-            if self.matches_type_key_filter(key, filter) {
+            if matches_type_key_filter(key, filter) {
                 return true;
             }
         }
@@ -512,15 +527,6 @@ impl EntityRestructurer {
         false
     }
 
-    /// Check if a key matches a specific type_key_filter
-    fn matches_type_key_filter(&self, key: Spur, filter: &TypeKeyFilter) -> bool {
-        match filter {
-            TypeKeyFilter::Specific(required_key) => key == *required_key,
-            TypeKeyFilter::OneOf(required_keys) => required_keys.iter().any(|k| *k == key),
-            TypeKeyFilter::Not(excluded_key) => key != *excluded_key,
-        }
-    }
-
     /// Get the type definitions that are applicable for a given entity based on type_key_filter
     fn get_applicable_type_defs<'a>(
         &self,
@@ -533,7 +539,7 @@ impl EntityRestructurer {
                 // If no type_key_filter, this type definition applies to all entities
                 if let Some(filter) = &type_def.rule_options.type_key_filter {
                     // Check if ANY key within the entity matches the type_key_filter
-                    self.matches_type_key_filter(key, filter)
+                    matches_type_key_filter(key, filter)
                 } else {
                     true
                 }
@@ -461,6 +461,27 @@ impl FileIndex {
         }
     }
 
+    /// Add or remove a single file after it was created or deleted on disk. Files are indexed
+    /// relative to the root they belong to, so a deleted file is only dropped from the index
+    /// if no other root still has it.
+    pub fn update_file(&mut self, path: &Path) {
+        let roots: Vec<&PathBuf> = std::iter::once(&self.game_root)
+            .chain(self.mod_paths.iter())
+            .filter(|root| !root.as_os_str().is_empty())
+            .collect();
+
+        let Some(relative_path) = roots.iter().find_map(|root| path.strip_prefix(root).ok()) else {
+            return;
+        };
+        let normalized_path = relative_path.to_string_lossy().replace('\\', "/");
+
+        if path.is_file() {
+            self.files.insert(normalized_path);
+        } else if !roots.iter().any(|root| root.join(relative_path).is_file()) {
+            self.files.remove(&normalized_path);
+        }
+    }
+
     /// Add files from multiple mods to the index
     pub fn integrate_mods(&mut self, mods: &[&GameMod]) {
         for game_mod in mods {
@@ -500,6 +521,15 @@ pub fn file_exists(file_path: &str) -> bool {
     }
 }
 
+/// Convenience function to update a single file in the global file index
+pub fn update_file_in_index(path: &Path) {
+    if let Some(cache) = FileIndex::get()
+        && let Ok(mut index) = cache.write()
+    {
+        index.update_file(path);
+    }
+}
+
 /// Convenience function to find files containing a pattern
 pub fn find_files_containing(pattern: &str) -> Vec<String> {
     if let Some(cache) = FileIndex::get() {
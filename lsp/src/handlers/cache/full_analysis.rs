@@ -12,8 +12,12 @@ pub struct FullAnalysis {
 #[derive(Clone)]
 pub struct FullAnalysisResult {
     pub dynamic_value_sets: SpurMap<HashSet<Spur>>,
+    /// The dynamic value sets found in each namespace, merged into `dynamic_value_sets`
+    pub namespace_value_sets: SpurMap<SpurMap<HashSet<Spur>>>,
     pub complex_enums: SpurMap<HashSet<Spur>>,
     pub scripted_effect_arguments: SpurMap<HashSet<Spur>>,
+    /// Name format family -> the `<key>`s its formats can use
+    pub name_format_keys: SpurMap<HashSet<Spur>>,
 }
 
 static FULL_ANALYSIS: RwLock<Option<FullAnalysisResult>> = RwLock::new(None);
@@ -39,6 +43,17 @@ impl FullAnalysis {
         *cache = None;
     }
 
+    /// Whether `key` is one of the `<key>`s formats of a name format family can use, or `None`
+    /// when the analysis hasn't been loaded yet
+    pub fn has_name_format_key(family: Spur, key: Spur) -> Option<bool> {
+        FULL_ANALYSIS.read().unwrap().as_ref().map(|result| {
+            result
+                .name_format_keys
+                .get(&family)
+                .is_some_and(|keys| keys.contains(&key))
+        })
+    }
+
     pub fn load_global_blocking() {
         let full_analysis = FullAnalysis::new(TypeCache::get().unwrap());
         full_analysis.load();
@@ -59,11 +74,7 @@ impl FullAnalysis {
         let duration = start.elapsed();
         eprintln!("Full analysis loaded in {:?}", duration);
 
-        let result = FullAnalysisResult {
-            dynamic_value_sets: collector.value_sets().clone(),
-            complex_enums: collector.complex_enums().clone(),
-            scripted_effect_arguments: collector.scripted_effect_arguments().clone(),
-        };
+        let result = Self::result_from(collector);
 
         // Now acquire the lock only to store the result
         let mut cache = FULL_ANALYSIS.write().unwrap();
@@ -73,6 +84,43 @@ impl FullAnalysis {
             *cache = Some(result);
         }
     }
+
+    /// Collect the data of the given namespaces again, keeping what was collected from other
+    /// namespaces. The previous result stays visible until the new one is swapped in.
+    pub fn reload_namespaces(&self, namespaces: &HashSet<Spur>) {
+        let Some(previous) = Self::get() else {
+            self.load();
+            return;
+        };
+
+        let start = std::time::Instant::now();
+
+        let mut collector = DataCollector::new(self.type_cache.get_resolver()).with_previous(
+            previous.namespace_value_sets,
+            previous.complex_enums,
+            previous.scripted_effect_arguments,
+            previous.name_format_keys,
+        );
+        collector.collect_namespaces(namespaces);
+
+        *FULL_ANALYSIS.write().unwrap() = Some(Self::result_from(collector));
+
+        eprintln!(
+            "Full analysis of {} namespaces reloaded in {:?}",
+            namespaces.len(),
+            start.elapsed()
+        );
+    }
+
+    fn result_from(collector: DataCollector) -> FullAnalysisResult {
+        FullAnalysisResult {
+            dynamic_value_sets: collector.value_sets().clone(),
+            namespace_value_sets: collector.namespace_value_sets().clone(),
+            complex_enums: collector.complex_enums().clone(),
+            scripted_effect_arguments: collector.scripted_effect_arguments().clone(),
+            name_format_keys: collector.name_format_keys().clone(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -88,8 +136,10 @@ mod tests {
             let mut cache = FULL_ANALYSIS.write().unwrap();
             *cache = Some(FullAnalysisResult {
                 dynamic_value_sets: SpurMap::new(),
+                namespace_value_sets: SpurMap::new(),
                 complex_enums: SpurMap::new(),
                 scripted_effect_arguments: SpurMap::new(),
+                name_format_keys: SpurMap::new(),
             });
         }
 
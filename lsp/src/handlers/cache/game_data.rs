@@ -8,7 +8,7 @@ use crate::handlers::cache::FileIndex;
 use crate::interner::get_interner;
 use cw_model::Module;
 use cw_model::SpurMap;
-use cw_model::{Entity, GameMod, LoadMode, Value};
+use cw_model::{Entity, GameMod, LoadMode, Modifier, Value};
 use lasso::Spur;
 
 use crate::handlers::cache::EntityRestructurer;
@@ -20,6 +20,8 @@ pub struct GameDataCache {
     /// Maps namespace -> set of keys defined in that namespace
     pub namespaces: SpurMap<Namespace>,
     pub scripted_variables: SpurMap<Value>,
+    /// Maps modifier -> the categories it belongs to
+    pub modifiers: SpurMap<Modifier>,
 }
 
 #[derive(Clone)]
@@ -170,16 +172,19 @@ impl GameDataCache {
             let mut cache = GameDataCache {
                 namespaces,
                 scripted_variables: global_scripted_variables,
+                modifiers: SpurMap::new(),
             };
 
             // Load modifiers and integrate them into the cache
             let start = Instant::now();
 
-            if let Err(e) = crate::handlers::modifiers::integrate_modifiers_into_cache(&mut cache) {
-                eprintln!("Warning: Failed to load modifiers: {}", e);
-            } else {
-                eprintln!("Loaded modifiers into cache in {:?}", start.elapsed());
-            }
+            let modifier_count =
+                crate::handlers::modifiers::integrate_modifiers_into_cache(&mut cache);
+            eprintln!(
+                "Loaded {} modifiers into cache in {:?}",
+                modifier_count,
+                start.elapsed()
+            );
 
             cache
         })
@@ -219,6 +224,8 @@ pub struct ModDataCache {
     /// Maps namespace -> set of keys defined in that namespace
     pub namespaces: SpurMap<Namespace>,
     pub scripted_variables: SpurMap<Value>,
+    /// Maps modifier -> the categories it belongs to, for modifiers generated by mod definitions
+    pub modifiers: SpurMap<Modifier>,
 }
 
 static MOD_DATA_CACHE: OnceLock<RwLock<ModDataCache>> = OnceLock::new();
@@ -230,6 +237,7 @@ impl ModDataCache {
             RwLock::new(ModDataCache {
                 namespaces: SpurMap::new(),
                 scripted_variables: SpurMap::new(),
+                modifiers: SpurMap::new(),
             })
         })
     }
@@ -240,57 +248,20 @@ impl ModDataCache {
         let mut cache = cache_lock.write().unwrap();
 
         eprintln!("Merging mod data: {}", game_mod.definition.name);
-        let interner = get_interner();
 
         let mut added_entities = 0;
         let mut added_variables = 0;
 
         // Process each namespace in the mod
         for (namespace_name, namespace) in &game_mod.namespaces {
-            let properties = &namespace.properties.kv;
-
-            for (key, value) in properties {
-                let key_str = get_interner().resolve(&key);
-
-                if namespace_name == "game/common/scripted_variables" {
-                    cache
-                        .scripted_variables
-                        .insert(key, value.0.first().unwrap().value.clone());
-                    added_variables += 1;
-                } else if key_str.starts_with("@") {
-                    let namespace_data = cache
-                        .namespaces
-                        .entry(interner.get_or_intern(namespace_name))
-                        .or_insert_with(Namespace::new);
-                    namespace_data
-                        .scripted_variables
-                        .insert(key, value.0.first().unwrap().value.clone());
-                    added_variables += 1;
-                } else {
-                    // Handle multiple entities with the same key (like multiple random_list entries)
-                    for (index, property_info) in value.0.iter().enumerate() {
-                        if let Some(entity) = property_info.value.as_entity() {
-                            let entity_key = if index == 0 {
-                                key_str.to_string()
-                            } else {
-                                format!("{}_{}", key_str, index + 1)
-                            };
-
-                            let namespace_data = cache
-                                .namespaces
-                                .entry(interner.get_or_intern(namespace_name))
-                                .or_insert_with(Namespace::new);
-                            namespace_data.entities.insert(
-                                interner.get_or_intern(entity_key),
-                                Arc::new(entity.clone()),
-                            );
-                            added_entities += 1;
-                        }
-                    }
-                }
-            }
+            let (entities, variables) = cache.merge_namespace(namespace_name, namespace);
+            added_entities += entities;
+            added_variables += variables;
         }
 
+        // The mod's definitions can generate modifiers, e.g. a new job
+        crate::handlers::modifiers::integrate_mod_modifiers_into_cache(&mut cache);
+
         // Update keys for all modified namespaces
         for namespace_data in cache.namespaces.values_mut() {
             namespace_data.update_keys();
@@ -308,34 +279,134 @@ impl ModDataCache {
         drop(cache);
 
         // Trigger entity restructuring to include the new mod data
-        Self::trigger_restructuring();
+        Self::trigger_restructuring(game_mod.namespaces.keys().map(|name| name.as_str()));
     }
 
-    /// Trigger entity restructuring and full analysis to include mod data
-    fn trigger_restructuring() {
+    /// Rebuild the given namespaces from the loaded mods, e.g. after some of their files changed
+    /// on disk, and trigger restructuring
+    pub fn reload_namespaces<'a>(
+        namespace_names: impl IntoIterator<Item = &'a str>,
+        mods: &[&GameMod],
+    ) {
+        let namespace_names: Vec<&str> = namespace_names.into_iter().collect();
+        let cache_lock = Self::get();
+        let mut cache = cache_lock.write().unwrap();
+
+        for &namespace_name in &namespace_names {
+            let namespace_key = get_interner().get_or_intern(namespace_name);
+            cache.namespaces.remove(&namespace_key);
+            if namespace_name == "game/common/scripted_variables" {
+                cache.scripted_variables.clear();
+            }
+
+            for game_mod in mods {
+                if let Some(namespace) = game_mod.namespaces.get(namespace_name) {
+                    cache.merge_namespace(namespace_name, namespace);
+                }
+            }
+
+            if let Some(namespace_data) = cache.namespaces.get_mut(&namespace_key) {
+                namespace_data.update_keys();
+            }
+
+            eprintln!("Reloaded mod namespace '{}'", namespace_name);
+        }
+
+        crate::handlers::modifiers::integrate_mod_modifiers_into_cache(&mut cache);
+
+        // Drop the lock before triggering restructuring
+        drop(cache);
+
+        Self::trigger_restructuring(namespace_names);
+    }
+
+    /// Merge the entities and scripted variables of a mod namespace, returning how many of each
+    /// were added
+    fn merge_namespace(
+        &mut self,
+        namespace_name: &str,
+        namespace: &cw_model::Namespace,
+    ) -> (usize, usize) {
+        let interner = get_interner();
+
+        let mut added_entities = 0;
+        let mut added_variables = 0;
+
+        for (key, value) in &namespace.properties.kv {
+            let key_str = interner.resolve(&key);
+
+            if namespace_name == "game/common/scripted_variables" {
+                self.scripted_variables
+                    .insert(key, value.0.first().unwrap().value.clone());
+                added_variables += 1;
+            } else if key_str.starts_with("@") {
+                let namespace_data = self
+                    .namespaces
+                    .entry(interner.get_or_intern(namespace_name))
+                    .or_insert_with(Namespace::new);
+                namespace_data
+                    .scripted_variables
+                    .insert(key, value.0.first().unwrap().value.clone());
+                added_variables += 1;
+            } else {
+                // Handle multiple entities with the same key (like multiple random_list entries)
+                for (index, property_info) in value.0.iter().enumerate() {
+                    if let Some(entity) = property_info.value.as_entity() {
+                        let entity_key = if index == 0 {
+                            key_str.to_string()
+                        } else {
+                            format!("{}_{}", key_str, index + 1)
+                        };
+
+                        let namespace_data = self
+                            .namespaces
+                            .entry(interner.get_or_intern(namespace_name))
+                            .or_insert_with(Namespace::new);
+                        namespace_data
+                            .entities
+                            .insert(interner.get_or_intern(entity_key), Arc::new(entity.clone()));
+                        added_entities += 1;
+                    }
+                }
+            }
+        }
+
+        (added_entities, added_variables)
+    }
+
+    /// Restructure entities and recompute the full analysis for the namespaces that changed,
+    /// leaving everything else as it is
+    fn trigger_restructuring<'a>(namespace_names: impl IntoIterator<Item = &'a str>) {
         if !EntityRestructurer::is_initialized() {
             eprintln!("EntityRestructurer is not initialized, skipping entity restructuring");
             return;
         }
 
-        if !GameDataCache::is_initialized() {
+        let (Some(game_data), Some(type_cache)) = (GameDataCache::get(), TypeCache::get()) else {
             eprintln!("GameDataCache is not initialized, skipping entity restructuring");
             return;
-        }
+        };
 
-        eprintln!("Triggering entity restructuring and full analysis to include mod data");
+        let interner = get_interner();
+        let namespaces: HashSet<Spur> = namespace_names
+            .into_iter()
+            .map(|name| TypeCache::get_actual_namespace(interner.get_or_intern(name)))
+            .collect();
 
-        EntityRestructurer::reset();
-        FullAnalysis::reset();
+        eprintln!(
+            "Restructuring entities and reloading full analysis for {} namespaces",
+            namespaces.len()
+        );
 
         let start = Instant::now();
-        eprintln!("Loading entity restructuring and full analysis");
 
-        EntityRestructurer::load_global_blocking();
-        FullAnalysis::load_global_blocking();
+        // Value sets and complex enums are collected from the restructured entities, so those
+        // have to be up to date first
+        EntityRestructurer::new(game_data, type_cache).reload_namespaces(&namespaces);
+        FullAnalysis::new(type_cache).reload_namespaces(&namespaces);
 
         eprintln!(
-            "Loaded entity restructuring and full analysis in {:?}",
+            "Reloaded entity restructuring and full analysis in {:?}",
             start.elapsed()
         );
     }
@@ -0,0 +1,304 @@
+//! Localisation keys of the base game and loaded mods
+//!
+//! Only the configured languages are loaded for the base game, which has tens of thousands of
+//! keys per language. Mods are loaded with every language they ship, since they are small and
+//! their translations are what mod authors need checked.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock, RwLock, RwLockReadGuard};
+use std::time::Instant;
+
+use cw_model::{
+    LocalisationIndex, LocalisationRequirement, TypeDefinition, entity_from_ast,
+    is_localisation_file,
+};
+use cw_parser::AstEntity;
+use lasso::Spur;
+
+use crate::base_game::game;
+use crate::handlers::cache::{FileIndex, TypeCache};
+use crate::handlers::scope::ScopeStack;
+use crate::handlers::scoped_type::ScopedType;
+use crate::handlers::settings::Settings;
+use crate::interner::get_interner;
+
+static LOCALISATION_INDEX: OnceLock<RwLock<LocalisationIndex>> = OnceLock::new();
+static BASE_GAME_LOADED: AtomicBool = AtomicBool::new(false);
+
+/// A localisation key that a definition's type asks for, e.g. `tech_lasers_1_desc`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionLocalisation {
+    /// The name of the requirement in the type's `localisation` block, e.g. `desc`
+    pub name: Spur,
+    pub key: String,
+    pub required: bool,
+    pub primary: bool,
+}
+
+/// Global localisation index, filled in load order: base game first, then mods as they are opened
+pub struct LocalisationCache;
+
+impl LocalisationCache {
+    fn global() -> &'static RwLock<LocalisationIndex> {
+        LOCALISATION_INDEX
+            .get_or_init(|| RwLock::new(LocalisationIndex::new(game::get_localisation_folder())))
+    }
+
+    /// Load the base game localisation in a background thread
+    pub fn initialize_in_background() {
+        std::thread::spawn(|| {
+            Self::load_base_game_blocking();
+        });
+    }
+
+    /// Check if the base game localisation has been loaded
+    pub fn is_initialized() -> bool {
+        BASE_GAME_LOADED.load(Ordering::Acquire)
+    }
+
+    /// Read access to the localisation of the base game and every loaded mod
+    pub fn get() -> RwLockReadGuard<'static, LocalisationIndex> {
+        Self::global().read().unwrap()
+    }
+
+    /// The languages keys are expected to be defined for
+    pub fn languages() -> &'static [String] {
+        &Settings::global().localisation_languages
+    }
+
+    /// Get the configured languages a key is not defined for
+    pub fn missing_languages(key: Spur) -> Vec<&'static str> {
+        let index = Self::get();
+        Self::languages()
+            .iter()
+            .filter(|language| index.get(language, key).is_none())
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn load_base_game_blocking() {
+        if Self::is_initialized() {
+            return;
+        }
+
+        while !FileIndex::is_initialized() {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+
+        let game_root = FileIndex::get()
+            .and_then(|index| {
+                index
+                    .read()
+                    .ok()
+                    .map(|index| index.game_root().to_path_buf())
+            })
+            .unwrap_or_default();
+
+        if game_root.as_os_str().is_empty() {
+            eprintln!("Warning: No game root path found, localisation will be empty");
+            BASE_GAME_LOADED.store(true, Ordering::Release);
+            return;
+        }
+
+        let start = Instant::now();
+        let mut index = LocalisationIndex::new(game::get_localisation_folder());
+        let errors = index.load_directory(&game_root, Some(Self::languages()), get_interner());
+
+        let key_count: usize = Self::languages()
+            .iter()
+            .map(|language| index.keys(language).count())
+            .sum();
+
+        // Mods opened while the base game was loading are already in the global index, and have
+        // to come after the base game
+        let mut global = Self::global().write().unwrap();
+        let mods = std::mem::replace(&mut *global, index);
+        global.extend(mods);
+        drop(global);
+
+        BASE_GAME_LOADED.store(true, Ordering::Release);
+
+        eprintln!(
+            "Loaded {} base game localisation keys in {:?} ({} files could not be parsed cleanly)",
+            key_count,
+            start.elapsed(),
+            errors.len()
+        );
+    }
+
+    /// Load the localisation of a mod, replacing anything previously loaded from it
+    pub fn load_mod(mod_root: &Path) {
+        let start = Instant::now();
+
+        let mut index = LocalisationIndex::new(game::get_localisation_folder());
+        index.load_directory(mod_root, None, get_interner());
+
+        let mut global = Self::global().write().unwrap();
+        global.remove_root(mod_root);
+        global.extend(index);
+
+        eprintln!(
+            "Loaded localisation for mod {} in {:?}",
+            mod_root.display(),
+            start.elapsed()
+        );
+    }
+
+    /// Re-read a localisation file that changed on disk, or drop its keys if it was deleted.
+    /// Returns whether the path is a localisation file.
+    pub fn reload_file(path: &Path) -> bool {
+        if !is_localisation_file(path, game::get_localisation_folder()) {
+            return false;
+        }
+
+        let content = std::fs::read_to_string(path).ok();
+
+        let mut index = Self::global().write().unwrap();
+        index.remove_file(path);
+        if let Some(content) = content {
+            index.add_file(path, &content, get_interner());
+        }
+
+        true
+    }
+}
+
+/// The localisation keys a definition is expected to have, based on the localisation patterns of
+/// its type and the subtypes the definition matches
+pub fn definition_localisation(
+    type_def: &TypeDefinition,
+    name: &str,
+    entity: &AstEntity<'_>,
+) -> Vec<DefinitionLocalisation> {
+    if type_def.localisation.is_empty() && type_def.subtype_localisation.is_empty() {
+        return Vec::new();
+    }
+
+    let interner = get_interner();
+    let subtypes = match TypeCache::get() {
+        Some(type_cache) if !type_def.subtypes.is_empty() => {
+            type_cache.get_resolver().determine_matching_subtypes(
+                Arc::new(ScopedType::new_cwt(
+                    type_def.rules.clone(),
+                    ScopeStack::default(),
+                    None,
+                )),
+                &entity_from_ast(entity, interner),
+            )
+        }
+        _ => HashSet::new(),
+    };
+
+    localisation_requirements(type_def, &subtypes)
+        .into_iter()
+        .filter_map(|(requirement_name, requirement)| {
+            let pattern = interner.resolve(&requirement.pattern);
+            // Patterns without a `$` don't depend on the definition
+            if !pattern.contains('$') {
+                return None;
+            }
+
+            Some(DefinitionLocalisation {
+                name: requirement_name,
+                key: pattern.replace('$', name),
+                required: requirement.required,
+                primary: requirement.primary,
+            })
+        })
+        .collect()
+}
+
+/// The localisation requirements that apply to a definition with the given subtypes. A subtype's
+/// requirement replaces the base requirement with the same name, including whether it's required.
+fn localisation_requirements<'t>(
+    type_def: &'t TypeDefinition,
+    subtypes: &HashSet<Spur>,
+) -> Vec<(Spur, &'t LocalisationRequirement)> {
+    let mut requirements: Vec<(Spur, &LocalisationRequirement)> =
+        type_def.localisation.iter().collect();
+
+    let mut subtypes: Vec<Spur> = subtypes.iter().copied().collect();
+    subtypes.sort_by_key(|subtype| get_interner().resolve(subtype));
+
+    for subtype in subtypes {
+        let Some(subtype_requirements) = type_def.subtype_localisation.get(&subtype) else {
+            continue;
+        };
+        for (name, requirement) in subtype_requirements.iter() {
+            requirements.retain(|(existing, _)| *existing != name);
+            requirements.push((name, requirement));
+        }
+    }
+
+    requirements.sort_by_key(|(name, _)| get_interner().resolve(name));
+    requirements
+}
+
+#[cfg(test)]
+mod tests {
+    use cw_model::{CwtType, SpurMap};
+
+    use super::*;
+
+    #[test]
+    fn test_subtype_localisation_replaces_base_requirement() {
+        let interner = get_interner();
+        let mut type_def = TypeDefinition::new(Arc::new(CwtType::Unknown));
+        type_def.localisation.insert(
+            interner.get_or_intern("name"),
+            LocalisationRequirement::required(interner.get_or_intern("$")),
+        );
+        type_def.localisation.insert(
+            interner.get_or_intern("desc"),
+            LocalisationRequirement::required(interner.get_or_intern("$_desc")),
+        );
+
+        let mut repeatable = SpurMap::new();
+        repeatable.insert(
+            interner.get_or_intern("desc"),
+            LocalisationRequirement::new(interner.get_or_intern("$_repeatable_desc")),
+        );
+        repeatable.insert(
+            interner.get_or_intern("tooltip"),
+            LocalisationRequirement::required(interner.get_or_intern("$_tooltip")),
+        );
+        type_def
+            .subtype_localisation
+            .insert(interner.get_or_intern("repeatable"), repeatable);
+
+        let patterns = |subtypes: &[&str]| -> Vec<(String, String, bool)> {
+            let subtypes = subtypes
+                .iter()
+                .map(|subtype| interner.get_or_intern(subtype))
+                .collect();
+            localisation_requirements(&type_def, &subtypes)
+                .into_iter()
+                .map(|(name, requirement)| {
+                    (
+                        interner.resolve(&name).to_string(),
+                        interner.resolve(&requirement.pattern).to_string(),
+                        requirement.required,
+                    )
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            patterns(&[]),
+            vec![
+                ("desc".to_string(), "$_desc".to_string(), true),
+                ("name".to_string(), "$".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            patterns(&["repeatable"]),
+            vec![
+                ("desc".to_string(), "$_repeatable_desc".to_string(), false),
+                ("name".to_string(), "$".to_string(), true),
+                ("tooltip".to_string(), "$_tooltip".to_string(), true),
+            ]
+        );
+    }
+}
@@ -2,25 +2,37 @@
 mod api;
 mod collector;
 mod core;
+mod definition_index;
 mod entity_restructurer;
 mod file_index;
 mod formatter;
 mod full_analysis;
 pub mod game_data;
+mod localisation;
 mod resolver;
 mod resolver_modules;
+mod sprites;
 pub mod types;
 
 // Re-exports for public API
 pub use api::{get_entity_property_type_from_ast, get_namespace_entity_type};
 pub use collector::*;
 pub use core::*;
+pub use definition_index::{
+    DefinitionIndex, DefinitionLocation, DefinitionSource, LineIndex, ModuleDefinition,
+    find_module_definitions, find_word_occurrences,
+};
 pub use entity_restructurer::*;
 pub use file_index::{
     FileIndex, add_mod_to_index, add_mods_to_index, file_exists, find_files_containing,
-    find_files_with_extension, initialize_file_index,
+    find_files_with_extension, initialize_file_index, update_file_in_index,
 };
 pub use formatter::TypeFormatter;
 pub use full_analysis::*;
 pub use game_data::*;
+pub use localisation::{DefinitionLocalisation, LocalisationCache, definition_localisation};
 pub use resolver_modules::*;
+pub use sprites::{
+    ModuleSprite, SpriteCache, SpriteDefinition, find_module_sprites, is_sprite_file,
+    is_sprite_name,
+};
@@ -2,7 +2,7 @@ use crate::handlers::scope::ScopeStack;
 use crate::handlers::scoped_type::{CwtTypeOrSpecialRef, PropertyNavigationResult, ScopedType};
 use crate::interner::get_interner;
 use cw_model::types::{CwtAnalyzer, LinkDefinition, PatternProperty, PatternType};
-use cw_model::{CwtType, Entity, EnumDefinition, ReferenceType, SpurMap};
+use cw_model::{AliasDefinition, CwtType, Entity, EnumDefinition, ReferenceType, SpurMap};
 use lasso::Spur;
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -130,6 +130,19 @@ impl TypeResolver {
             .key_matches_pattern_type(key, pattern_type)
     }
 
+    /// Get the alias definitions of a category whose name matches a key
+    pub fn get_matching_alias_definitions(
+        &self,
+        key: Spur,
+        category: Spur,
+    ) -> Vec<&AliasDefinition> {
+        self.pattern_matcher
+            .get_matching_aliases(key, category)
+            .into_iter()
+            .filter_map(|alias_pattern| self.cwt_analyzer.get_alias(alias_pattern))
+            .collect()
+    }
+
     /// Get the documentation for a property if it exists
     pub fn get_property_documentation(
         &self,
@@ -2,7 +2,7 @@ use crate::interner::get_interner;
 
 use super::{ResolverUtils, SubtypeHandler};
 use cw_model::types::{CwtAnalyzer, PatternProperty, PatternType};
-use cw_model::{AliasName, BlockType};
+use cw_model::{AliasName, AliasPattern, BlockType};
 use lasso::Spur;
 use std::sync::Arc;
 
@@ -60,76 +60,7 @@ impl PatternMatcher {
         match pattern_type {
             PatternType::AliasName { category } => {
                 // Check if the key matches any alias name from this category
-                if let Some(aliases_in_category) =
-                    self.cwt_analyzer.get_aliases_for_category(*category)
-                {
-                    for alias_pattern in aliases_in_category {
-                        match &alias_pattern.name {
-                            AliasName::Static(name) => {
-                                if *name == key {
-                                    return true;
-                                }
-                            }
-                            AliasName::TypeRef(type_name) => {
-                                // Check if key matches any type from this namespace
-                                if let Some(namespace_keys) =
-                                    self.utils.get_namespace_keys_for_type_ref(*type_name)
-                                {
-                                    if namespace_keys.contains(&key) {
-                                        return true;
-                                    }
-                                }
-                            }
-                            AliasName::Enum(enum_name) => {
-                                // Check if key matches any enum value
-                                if let Some(enum_def) = self.cwt_analyzer.get_enum(*enum_name) {
-                                    if enum_def.values.contains(&key) {
-                                        return true;
-                                    }
-                                }
-                            }
-                            AliasName::TypeRefWithPrefixSuffix(name, prefix, suffix) => {
-                                // Check if key matches pattern with prefix/suffix
-                                let key = interner.resolve(&key);
-                                let mut stripped_key = key;
-
-                                // Remove prefix if present
-                                if let Some(prefix_str) = prefix {
-                                    if let Some(without_prefix) =
-                                        stripped_key.strip_prefix(interner.resolve(&prefix_str))
-                                    {
-                                        stripped_key = without_prefix;
-                                    } else {
-                                        return false; // Key doesn't start with required prefix
-                                    }
-                                }
-
-                                // Remove suffix if present
-                                if let Some(suffix_str) = suffix {
-                                    if let Some(without_suffix) =
-                                        stripped_key.strip_suffix(interner.resolve(&suffix_str))
-                                    {
-                                        stripped_key = without_suffix;
-                                    } else {
-                                        return false; // Key doesn't end with required suffix
-                                    }
-                                }
-
-                                // Check if the remaining key matches any type from this namespace
-                                if let Some(namespace_keys) =
-                                    self.utils.get_namespace_keys_for_type_ref(*name)
-                                {
-                                    if namespace_keys
-                                        .contains(&interner.get_or_intern(stripped_key))
-                                    {
-                                        return true;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                false
+                !self.get_matching_aliases(key, *category).is_empty()
             }
             PatternType::Enum { key: enum_key } => {
                 // Check if the key matches any enum value
@@ -184,6 +115,63 @@ impl PatternMatcher {
         }
     }
 
+    /// Get the aliases of a category whose name matches a key
+    pub fn get_matching_aliases(&self, key: Spur, category: Spur) -> Vec<&AliasPattern> {
+        self.cwt_analyzer
+            .get_aliases_for_category(category)
+            .unwrap_or(&[])
+            .iter()
+            .filter(|alias_pattern| self.alias_name_matches_key(&alias_pattern.name, key))
+            .collect()
+    }
+
+    /// Check if a key matches a single alias name
+    fn alias_name_matches_key(&self, alias_name: &AliasName, key: Spur) -> bool {
+        let interner = get_interner();
+        match alias_name {
+            AliasName::Static(name) => *name == key,
+            AliasName::TypeRef(type_name) => {
+                // Check if key matches any type from this namespace
+                self.utils
+                    .get_namespace_keys_for_type_ref(*type_name)
+                    .is_some_and(|namespace_keys| namespace_keys.contains(&key))
+            }
+            AliasName::Enum(enum_name) => {
+                // Check if key matches any enum value
+                self.cwt_analyzer
+                    .get_enum(*enum_name)
+                    .is_some_and(|enum_def| enum_def.values.contains(&key))
+            }
+            AliasName::TypeRefWithPrefixSuffix(name, prefix, suffix) => {
+                // Check if key matches pattern with prefix/suffix
+                let mut stripped_key = interner.resolve(&key);
+
+                // Remove prefix if present
+                if let Some(prefix_str) = prefix {
+                    match stripped_key.strip_prefix(interner.resolve(prefix_str)) {
+                        Some(without_prefix) => stripped_key = without_prefix,
+                        None => return false, // Key doesn't start with required prefix
+                    }
+                }
+
+                // Remove suffix if present
+                if let Some(suffix_str) = suffix {
+                    match stripped_key.strip_suffix(interner.resolve(suffix_str)) {
+                        Some(without_suffix) => stripped_key = without_suffix,
+                        None => return false, // Key doesn't end with required suffix
+                    }
+                }
+
+                // Check if the remaining key matches any type from this namespace
+                self.utils
+                    .get_namespace_keys_for_type_ref(*name)
+                    .is_some_and(|namespace_keys| {
+                        namespace_keys.contains(&interner.get_or_intern(stripped_key))
+                    })
+            }
+        }
+    }
+
     /// Get all possible completions for a pattern type
     pub fn get_pattern_completions(&self, pattern_type: &PatternType) -> Vec<String> {
         let interner = get_interner();
@@ -6,7 +6,7 @@ use lasso::Spur;
 use crate::{
     handlers::{
         cache::{
-            FullAnalysis, PatternMatcher, ReferenceResolver,
+            FullAnalysis, LocalisationCache, PatternMatcher, ReferenceResolver,
             resolver_modules::properties::{
                 handlers::{
                     handle_pattern_property, handle_pattern_property_all_matches,
@@ -150,7 +150,11 @@ pub fn navigate_to_block_property(
         .properties
         .get(&interner.get_or_intern("localisation"))
     {
-        if !Settings::global().validate_localisation {
+        // With localisation validation, only keys that are actually localisation keys match
+        if !Settings::global().validate_localisation
+            || !LocalisationCache::is_initialized()
+            || LocalisationCache::missing_languages(property_name).is_empty()
+        {
             let result = handle_regular_property(
                 cwt_analyzer.clone(),
                 scoped_type.clone(),
@@ -153,7 +153,7 @@ impl SubtypeHandler {
                     }
                 }
             }
-            CwtType::Simple(_) => {
+            CwtType::Simple(_) | CwtType::Ranged(_, _) => {
                 // For simple types, just check existence based on cardinality
                 match &condition_property.options.cardinality {
                     Some(cardinality) => {
@@ -0,0 +1,429 @@
+//! Sprites defined in `interface/*.gfx` files, e.g. `GFX_evt_alien_city`
+//!
+//! Like the definition index, the files backing the `interface` namespaces are re-read to find
+//! where each sprite is defined, along with the texture it uses so that hovers can show it and
+//! diagnostics can check that it exists.
+
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Instant;
+
+use cw_model::{GameMod, Module, SpurMap};
+use cw_parser::{AstEntity, AstEntityItem, AstModule, AstModuleCell, AstNode, AstValue};
+use lasso::Spur;
+use rayon::prelude::*;
+
+use crate::handlers::cache::definition_index::module_path;
+use crate::handlers::cache::{
+    DefinitionLocation, DefinitionSource, FileIndex, GameDataCache, LineIndex,
+};
+use crate::interner::get_interner;
+
+/// The blocks in a `spriteTypes` entity that define a sprite
+const SPRITE_KINDS: &[&str] = &["spriteType", "frameAnimatedSpriteType"];
+
+/// A sprite defined in a `.gfx` file
+#[derive(Debug, Clone)]
+pub struct SpriteDefinition {
+    /// The kind of block the sprite is defined by, e.g. `spriteType`
+    pub kind: Spur,
+    pub texture_file: Option<String>,
+    pub frames: Option<u32>,
+    /// Where the name of the sprite is defined
+    pub location: DefinitionLocation,
+}
+
+/// A sprite found in a parsed `.gfx` file
+#[derive(Debug, Clone)]
+pub struct ModuleSprite<'a> {
+    pub kind: &'a str,
+    pub name: &'a str,
+    pub name_range: Range<usize>,
+    /// The value of `texturefile` and its range
+    pub texture_file: Option<(&'a str, Range<usize>)>,
+    pub frames: Option<u32>,
+}
+
+/// Maps sprite names to the places they are defined, in load order
+#[derive(Default)]
+struct SpriteIndex {
+    sprites: SpurMap<Vec<SpriteDefinition>>,
+}
+
+static SPRITE_INDEX: OnceLock<RwLock<SpriteIndex>> = OnceLock::new();
+static BASE_GAME_INDEXED: AtomicBool = AtomicBool::new(false);
+
+/// Sprites of the base game and loaded mods
+pub struct SpriteCache;
+
+impl SpriteCache {
+    fn global() -> &'static RwLock<SpriteIndex> {
+        SPRITE_INDEX.get_or_init(|| RwLock::new(SpriteIndex::default()))
+    }
+
+    /// Index the base game sprites in a background thread once the game data is available
+    pub fn initialize_in_background() {
+        std::thread::spawn(|| {
+            Self::index_base_game_blocking();
+        });
+    }
+
+    /// Check if the base game sprites have been indexed
+    pub fn is_initialized() -> bool {
+        BASE_GAME_INDEXED.load(Ordering::Acquire)
+    }
+
+    fn index_base_game_blocking() {
+        if Self::is_initialized() {
+            return;
+        }
+
+        while !GameDataCache::is_initialized() || !FileIndex::is_initialized() {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+
+        let start = Instant::now();
+
+        let game_root = FileIndex::get()
+            .and_then(|index| {
+                index
+                    .read()
+                    .ok()
+                    .map(|index| index.game_root().to_path_buf())
+            })
+            .unwrap_or_default();
+
+        if game_root.as_os_str().is_empty() {
+            eprintln!("Warning: No game root path found, sprite index will be empty");
+            BASE_GAME_INDEXED.store(true, Ordering::Release);
+            return;
+        }
+
+        let modules: Vec<Arc<Module>> = GameDataCache::get()
+            .unwrap()
+            .get_namespaces()
+            .values()
+            .flat_map(|namespace| namespace.modules.values().cloned())
+            .collect();
+
+        let mut base_game = SpriteIndex::default();
+        let count = base_game.index_modules(&game_root, &modules, DefinitionSource::BaseGame);
+
+        // Mods indexed while the base game was loading have to come after it
+        let mut global = Self::global().write().unwrap();
+        let mods = std::mem::replace(&mut *global, base_game);
+        global.extend(mods);
+        drop(global);
+
+        BASE_GAME_INDEXED.store(true, Ordering::Release);
+
+        eprintln!(
+            "Indexed {} base game sprites in {:?}",
+            count,
+            start.elapsed()
+        );
+    }
+
+    /// Index the sprites of a mod, replacing anything previously indexed for it
+    pub fn index_mod(game_mod: &GameMod) {
+        let Some(mod_root) = game_mod.definition.path.as_ref() else {
+            return;
+        };
+
+        let modules: Vec<Arc<Module>> = game_mod
+            .namespaces
+            .values()
+            .flat_map(|namespace| namespace.modules.values().cloned())
+            .collect();
+        let source = DefinitionSource::Mod(mod_root.clone());
+
+        let mut index = SpriteIndex::default();
+        let count = index.index_modules(mod_root, &modules, source.clone());
+
+        let mut global = Self::global().write().unwrap();
+        global.remove_source(&source);
+        global.extend(index);
+
+        eprintln!(
+            "Indexed {} sprites for mod '{}'",
+            count, game_mod.definition.name
+        );
+    }
+
+    /// Re-index a single mod file after it changed on disk, or drop its sprites if it was
+    /// deleted
+    pub fn reindex_file(mod_root: &Path, namespace: &str, path: &Path) {
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        if !is_sprite_file(namespace, &filename) {
+            return;
+        }
+
+        let source = DefinitionSource::Mod(mod_root.to_path_buf());
+        let sprites = if path.is_file() {
+            index_file(path, &source)
+        } else {
+            None
+        };
+
+        let mut index = Self::global().write().unwrap();
+        index.remove_file(path);
+        for (name, sprite) in sprites.into_iter().flatten() {
+            index.sprites.entry(name).or_default().push(sprite);
+        }
+    }
+
+    /// Get every definition of a sprite, base game first
+    pub fn get_definitions(name: &str) -> Vec<SpriteDefinition> {
+        let name = get_interner().get_or_intern(name);
+        Self::global()
+            .read()
+            .unwrap()
+            .sprites
+            .get(&name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Check if a sprite is defined by the base game or a loaded mod
+    pub fn contains(name: &str) -> bool {
+        let name = get_interner().get_or_intern(name);
+        Self::global().read().unwrap().sprites.contains_key(&name)
+    }
+
+    /// Check if any sprites were found at all. Games that don't define their icons through
+    /// `.gfx` files have none.
+    pub fn has_sprites() -> bool {
+        !Self::global().read().unwrap().sprites.is_empty()
+    }
+}
+
+impl SpriteIndex {
+    /// Parse the `.gfx` files backing the given modules and add their sprites, returning how
+    /// many were added
+    fn index_modules(
+        &mut self,
+        root: &Path,
+        modules: &[Arc<Module>],
+        source: DefinitionSource,
+    ) -> usize {
+        let files: Vec<PathBuf> = modules
+            .iter()
+            .filter(|module| is_sprite_file(&module.namespace, &module.filename))
+            .map(|module| module_path(root, module))
+            .collect();
+
+        let results: Vec<Vec<(Spur, SpriteDefinition)>> = files
+            .par_iter()
+            .filter_map(|path| index_file(path, &source))
+            .collect();
+
+        let mut count = 0;
+        for (name, sprite) in results.into_iter().flatten() {
+            self.sprites.entry(name).or_default().push(sprite);
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Add the sprites of another index after the sprites of this one
+    fn extend(&mut self, other: SpriteIndex) {
+        for (name, sprites) in other.sprites.iter() {
+            self.sprites
+                .entry(name)
+                .or_default()
+                .extend(sprites.iter().cloned());
+        }
+    }
+
+    fn remove_file(&mut self, path: &Path) {
+        self.sprites.retain(|_, sprites| {
+            sprites.retain(|sprite| sprite.location.path != path);
+            !sprites.is_empty()
+        });
+    }
+
+    fn remove_source(&mut self, source: &DefinitionSource) {
+        self.sprites.retain(|_, sprites| {
+            sprites.retain(|sprite| &sprite.location.source != source);
+            !sprites.is_empty()
+        });
+    }
+}
+
+/// Check if a value refers to a sprite rather than a file, e.g. `GFX_evt_alien_city`
+pub fn is_sprite_name(value: &str) -> bool {
+    value
+        .get(..4)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("GFX_"))
+}
+
+/// Check if sprites are defined in a namespace. Only `interface` is read by the game.
+pub fn is_sprite_namespace(namespace: &str) -> bool {
+    namespace == "game/interface" || namespace.starts_with("game/interface/")
+}
+
+/// Check if a file defines sprites: a `.gfx` file in a sprite namespace
+pub fn is_sprite_file(namespace: &str, filename: &str) -> bool {
+    is_sprite_namespace(namespace) && filename.ends_with(".gfx")
+}
+
+/// Find the sprites defined in a parsed `.gfx` file
+pub fn find_module_sprites<'a>(module: &'a AstModule<'a>) -> Vec<ModuleSprite<'a>> {
+    let mut sprites = Vec::new();
+
+    for item in &module.items {
+        let AstEntityItem::Expression(expression) = item else {
+            continue;
+        };
+        if !expression
+            .key
+            .raw_value()
+            .eq_ignore_ascii_case("spriteTypes")
+        {
+            continue;
+        }
+        let AstValue::Entity(sprite_types) = &expression.value else {
+            continue;
+        };
+
+        for item in &sprite_types.items {
+            let AstEntityItem::Expression(expression) = item else {
+                continue;
+            };
+            let kind = expression.key.raw_value();
+            if !SPRITE_KINDS
+                .iter()
+                .any(|sprite_kind| sprite_kind.eq_ignore_ascii_case(kind))
+            {
+                continue;
+            }
+            let AstValue::Entity(sprite) = &expression.value else {
+                continue;
+            };
+
+            if let Some(sprite) = module_sprite(kind, sprite) {
+                sprites.push(sprite);
+            }
+        }
+    }
+
+    sprites
+}
+
+fn module_sprite<'a>(kind: &'a str, sprite: &'a AstEntity<'a>) -> Option<ModuleSprite<'a>> {
+    // Keys are case insensitive, vanilla uses both `texturefile` and `textureFile`
+    let property = |key: &str| {
+        sprite
+            .properties()
+            .find(|expression| expression.key.raw_value().eq_ignore_ascii_case(key))
+            .map(|expression| &expression.value)
+    };
+    let string_property = |key: &str| match property(key)? {
+        value @ AstValue::String(string) => Some((string.raw_value(), value.span_range())),
+        _ => None,
+    };
+
+    let (name, name_range) = string_property("name")?;
+    let frames = match property("noOfFrames") {
+        Some(AstValue::Number(number)) => number.value.value.parse().ok(),
+        _ => None,
+    };
+
+    Some(ModuleSprite {
+        kind,
+        name,
+        name_range,
+        texture_file: string_property("texturefile"),
+        frames,
+    })
+}
+
+/// Read a `.gfx` file and collect its sprites, or None if it couldn't be read or parsed
+fn index_file(path: &Path, source: &DefinitionSource) -> Option<Vec<(Spur, SpriteDefinition)>> {
+    let content = fs::read_to_string(path).ok()?;
+    let cell = AstModuleCell::from_input(content);
+    let module = cell.borrow_dependent().as_ref().ok()?;
+    let input = cell.borrow_owner();
+    let line_index = LineIndex::new(input);
+    let interner = get_interner();
+
+    let sprites = find_module_sprites(module)
+        .into_iter()
+        .map(|sprite| {
+            let definition = SpriteDefinition {
+                kind: interner.get_or_intern(sprite.kind),
+                texture_file: sprite
+                    .texture_file
+                    .map(|(texture_file, _)| texture_file.to_string()),
+                frames: sprite.frames,
+                location: DefinitionLocation {
+                    path: path.to_path_buf(),
+                    span: line_index.span(input, sprite.name_range),
+                    source: source.clone(),
+                    type_name: Some(interner.get_or_intern(sprite.kind)),
+                },
+            };
+            (interner.get_or_intern(sprite.name), definition)
+        })
+        .collect();
+
+    Some(sprites)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_sprite_file() {
+        assert!(is_sprite_file("game/interface", "eventpictures.gfx"));
+        assert!(is_sprite_file("game/interface/icons", "buildings.gfx"));
+        assert!(!is_sprite_file("game/interface", "main.gui"));
+        assert!(!is_sprite_file("game/gfx/interface", "unused.gfx"));
+        assert!(!is_sprite_file("game/common/interface", "unused.gfx"));
+    }
+
+    #[test]
+    fn test_find_module_sprites() {
+        let input = r#"spriteTypes = {
+    spriteType = {
+        name = "GFX_evt_alien_city"
+        texturefile = "gfx/event_pictures/alien_city.dds"
+    }
+    frameAnimatedSpriteType = {
+        name = "GFX_loading"
+        texturefile = "gfx/interface/loading.dds"
+        noOfFrames = 8
+    }
+    spriteType = { texturefile = "gfx/interface/unnamed.dds" }
+}
+objectTypes = {
+    spriteType = { name = "GFX_not_a_sprite" }
+}
+"#;
+        let mut module = AstModule::new();
+        module.parse_input(input).unwrap();
+
+        let sprites = find_module_sprites(&module);
+        assert_eq!(sprites.len(), 2);
+
+        assert_eq!(sprites[0].kind, "spriteType");
+        assert_eq!(sprites[0].name, "GFX_evt_alien_city");
+        assert_eq!(
+            &input[sprites[0].name_range.clone()],
+            "\"GFX_evt_alien_city\""
+        );
+        assert_eq!(
+            sprites[0].texture_file.as_ref().map(|(path, _)| *path),
+            Some("gfx/event_pictures/alien_city.dds")
+        );
+        assert_eq!(sprites[0].frames, None);
+
+        assert_eq!(sprites[1].kind, "frameAnimatedSpriteType");
+        assert_eq!(sprites[1].frames, Some(8));
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, jsonrpc::Result};
+
+use crate::handlers::diagnostics::diagnostic::DiagnosticFix;
+
+pub fn code_action(
+    _client: &Client,
+    params: CodeActionParams,
+) -> Result<Option<CodeActionResponse>> {
+    if let Some(only) = &params.context.only
+        && !only.contains(&CodeActionKind::QUICKFIX)
+    {
+        return Ok(None);
+    }
+
+    let actions: Vec<CodeActionOrCommand> = params
+        .context
+        .diagnostics
+        .iter()
+        .flat_map(|diagnostic| quick_fixes(&params.text_document.uri, diagnostic))
+        .map(CodeActionOrCommand::CodeAction)
+        .collect();
+
+    if actions.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(actions))
+    }
+}
+
+/// Build the quick fixes for a diagnostic from the fix stored in its `data`
+fn quick_fixes(uri: &Url, diagnostic: &Diagnostic) -> Vec<CodeAction> {
+    let Some(fix) = diagnostic
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value::<DiagnosticFix>(data).ok())
+    else {
+        return Vec::new();
+    };
+
+    match fix {
+        DiagnosticFix::Replace { suggestions } => suggestions
+            .into_iter()
+            .enumerate()
+            .map(|(index, suggestion)| {
+                new_quick_fix(
+                    format!("Replace with '{}'", suggestion),
+                    uri,
+                    diagnostic,
+                    TextEdit {
+                        range: diagnostic.range,
+                        new_text: suggestion,
+                    },
+                    index == 0,
+                )
+            })
+            .collect(),
+        DiagnosticFix::InsertProperties {
+            keys,
+            position,
+            text,
+        } => {
+            let title = if keys.len() == 1 {
+                format!("Add missing property '{}'", keys[0])
+            } else {
+                format!("Add missing properties {}", keys.join(", "))
+            };
+
+            vec![new_quick_fix(
+                title,
+                uri,
+                diagnostic,
+                TextEdit {
+                    range: Range {
+                        start: position,
+                        end: position,
+                    },
+                    new_text: text,
+                },
+                true,
+            )]
+        }
+    }
+}
+
+fn new_quick_fix(
+    title: String,
+    uri: &Url,
+    diagnostic: &Diagnostic,
+    edit: TextEdit,
+    is_preferred: bool,
+) -> CodeAction {
+    CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+            ..Default::default()
+        }),
+        is_preferred: Some(is_preferred),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic_with_fix(fix: DiagnosticFix) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(2, 4), Position::new(2, 13)),
+            data: serde_json::to_value(fix).ok(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_replace_fix_prefers_best_suggestion() {
+        let uri = Url::parse("file:///mod/common/technology/test.txt").unwrap();
+        let diagnostic = diagnostic_with_fix(DiagnosticFix::Replace {
+            suggestions: vec!["potential".to_string(), "potentials".to_string()],
+        });
+
+        let actions = quick_fixes(&uri, &diagnostic);
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].title, "Replace with 'potential'");
+        assert_eq!(actions[0].is_preferred, Some(true));
+        assert_eq!(actions[1].is_preferred, Some(false));
+
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].range, diagnostic.range);
+        assert_eq!(edits[0].new_text, "potential");
+    }
+
+    #[test]
+    fn test_insert_properties_fix() {
+        let uri = Url::parse("file:///mod/common/technology/test.txt").unwrap();
+        let diagnostic = diagnostic_with_fix(DiagnosticFix::InsertProperties {
+            keys: vec!["area".to_string(), "tier".to_string()],
+            position: Position::new(5, 0),
+            text: "\tarea = physics\n\ttier = 0\n".to_string(),
+        });
+
+        let actions = quick_fixes(&uri, &diagnostic);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].title, "Add missing properties area, tier");
+
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].range.start, Position::new(5, 0));
+        assert_eq!(edits[0].range.end, Position::new(5, 0));
+    }
+
+    #[test]
+    fn test_diagnostic_without_fix() {
+        let uri = Url::parse("file:///mod/common/technology/test.txt").unwrap();
+        assert!(quick_fixes(&uri, &Diagnostic::default()).is_empty());
+    }
+}
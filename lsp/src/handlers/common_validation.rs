@@ -1,4 +1,4 @@
-use cw_model::{CwtType, Entity, SimpleType};
+use cw_model::{CwtType, Entity, SimpleType, entity_from_module_ast};
 use cw_parser::{AstEntity, AstEntityItem, AstModule, AstValue};
 use lasso::Spur;
 use std::sync::Arc;
@@ -7,7 +7,7 @@ use super::document_cache::DocumentCache;
 use crate::handlers::cache::TypeFormatter;
 use crate::handlers::cache::types::TypeInfo;
 use crate::handlers::cache::{EntityRestructurer, GameDataCache, TypeCache};
-use crate::handlers::scoped_type::{CwtTypeOrSpecialRef, ScopedType};
+use crate::handlers::scoped_type::{CwtTypeOrSpecialRef, PropertyNavigationResult, ScopedType};
 use crate::handlers::utils::extract_namespace_from_uri;
 use crate::interner::get_interner;
 use std::path::Path;
@@ -282,6 +282,86 @@ pub fn create_variable_assignment_type(namespace_type: &Arc<ScopedType>) -> Arc<
     ))
 }
 
+/// Resolves the type of the property at `property_path`, given as keys from the top of the file.
+/// For a single key (or a container key plus a nested key for skip_root_key types) this is the
+/// type of the entity itself.
+pub fn resolve_property_path_type(
+    module: &AstModule,
+    namespace: Spur,
+    namespace_type: &Arc<ScopedType>,
+    property_path: &[&str],
+) -> Option<Arc<ScopedType>> {
+    let type_cache = TypeCache::get()?;
+    let interner = get_interner();
+
+    let (entity_type, remaining_path) = if is_type_per_file_namespace(namespace_type) {
+        let entity = entity_from_module_ast(module, interner);
+        let validation_type = apply_file_level_subtype_narrowing(namespace_type.clone(), &entity);
+        let filtered_type = type_cache.filter_union_types_by_properties(validation_type, &entity);
+
+        (filtered_type, property_path)
+    } else {
+        let (first, rest) = property_path.split_first()?;
+        let container_key = interner.get_or_intern(first);
+        let container_entity = find_entity_in_module(module, container_key).ast_entity?;
+
+        if detect_skip_root_key_container(namespace_type, container_key).is_skip_root_key_container
+        {
+            // The container itself has no type, only the entities nested in it
+            let (nested, rest) = rest.split_first()?;
+            let nested_key = interner.get_or_intern(nested);
+            let nested_entity =
+                find_nested_entity_in_container(container_entity, nested_key).ast_entity?;
+
+            let entity_type = filter_and_narrow_entity_type(
+                namespace_type.clone(),
+                namespace,
+                container_key,
+                nested_key,
+                nested_entity,
+            );
+            (entity_type, rest)
+        } else {
+            let entity_type = filter_and_narrow_entity_type(
+                namespace_type.clone(),
+                namespace,
+                container_key,
+                container_key,
+                container_entity,
+            );
+            (entity_type, rest)
+        }
+    };
+
+    navigate_property_path(entity_type, remaining_path)
+}
+
+/// Navigates through a chain of property keys, resolving references along the way
+pub fn navigate_property_path(
+    scoped_type: Arc<ScopedType>,
+    property_path: &[&str],
+) -> Option<Arc<ScopedType>> {
+    let type_cache = TypeCache::get()?;
+    let interner = get_interner();
+
+    let mut current_type = scoped_type;
+    for part in property_path {
+        current_type = type_cache.get_resolver().resolve_type(current_type);
+
+        match type_cache
+            .get_resolver()
+            .navigate_to_property(current_type, interner.get_or_intern(part))
+        {
+            PropertyNavigationResult::Success(property_type) => current_type = property_type,
+            PropertyNavigationResult::NotFound | PropertyNavigationResult::ScopeError(_) => {
+                return None;
+            }
+        }
+    }
+
+    Some(current_type)
+}
+
 /// Result of entity lookup in AST
 pub struct EntityLookupResult<'a> {
     pub found: bool,
@@ -0,0 +1,614 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use cw_model::{BlockType, CwtType, PatternType, ReferenceType, SimpleType};
+use cw_parser::{AstEntity, AstEntityItem, AstModule, AstModuleCell, AstValue};
+use lasso::Spur;
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, jsonrpc::Result};
+
+use crate::handlers::cache::{EntityRestructurer, FullAnalysis, TypeCache};
+use crate::handlers::common_validation::{
+    NamespaceValidationResult, resolve_property_path_type, validate_namespace_and_caches,
+};
+use crate::handlers::cursor::{CursorTargetKind, find_cursor_target};
+use crate::handlers::scoped_type::{CwtTypeOrSpecial, CwtTypeOrSpecialRef, ScopedType};
+use crate::interner::get_interner;
+
+use super::document_cache::DocumentCache;
+use super::utils::position_to_offset;
+
+/// Inserted at the cursor before parsing, so that incomplete input like `key = ` still parses
+/// and the cursor always lands on a token
+const COMPLETION_MARKER: &str = "__cw_completion__";
+
+/// Completion lists for large namespaces are cut off, the client re-requests as the user types
+const MAX_COMPLETION_ITEMS: usize = 500;
+
+pub fn completion(
+    _client: &Client,
+    documents: &Arc<RwLock<HashMap<String, String>>>,
+    document_cache: &DocumentCache,
+    params: CompletionParams,
+) -> Result<Option<CompletionResponse>> {
+    let uri = params.text_document_position.text_document.uri.to_string();
+    let position = params.text_document_position.position;
+
+    let documents = documents.read().expect("Failed to read documents");
+    let content = match documents.get(&uri) {
+        Some(content) => content,
+        None => return Ok(None),
+    };
+
+    let root_dir = match document_cache.get(&uri) {
+        Some(cached_document) => cached_document.root_dir.clone(),
+        None => return Ok(None),
+    };
+
+    let context = match validate_namespace_and_caches(&uri, &root_dir) {
+        NamespaceValidationResult::Valid(context) => context,
+        _ => return Ok(None),
+    };
+
+    let offset = position_to_offset(content, position);
+    let module = AstModuleCell::from_input(insert_completion_marker(content, offset));
+    let ast = match module.borrow_dependent().as_ref() {
+        Ok(ast) => ast,
+        Err(_) => return Ok(None),
+    };
+
+    let target = match find_cursor_target(ast, offset) {
+        Some(target) => target,
+        None => return Ok(None),
+    };
+
+    let prefix = match completion_prefix(&target.text) {
+        Some(prefix) => prefix.to_lowercase(),
+        None => return Ok(None),
+    };
+
+    let path: Vec<&str> = target.path.iter().map(|s| s.as_str()).collect();
+    let resolve = |path: &[&str]| {
+        resolve_property_path_type(ast, context.namespace, &context.namespace_type, path)
+    };
+
+    let mut items = Vec::new();
+    match target.kind {
+        CursorTargetKind::Key => {
+            let block_path = &path[..path.len() - 1];
+            if let Some(block_type) = resolve(block_path) {
+                let existing = count_existing_keys(ast, block_path);
+                collect_key_completions(&block_type, &existing, &mut items);
+            }
+        }
+        CursorTargetKind::Value => {
+            if let Some(value_type) = resolve(&path) {
+                collect_value_completions(&value_type, &mut items);
+            }
+        }
+        CursorTargetKind::ArrayItem => {
+            // A bare word in a block is either the start of a new property or an array item
+            if let Some(block_type) = resolve(&path) {
+                let existing = count_existing_keys(ast, &path);
+                collect_key_completions(&block_type, &existing, &mut items);
+                collect_array_item_completions(&block_type, &mut items);
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut items: Vec<CompletionItem> = items
+        .into_iter()
+        .filter(|item| item.label.to_lowercase().starts_with(&prefix))
+        .filter(|item| seen.insert(item.label.clone()))
+        .collect();
+
+    let is_incomplete = items.len() > MAX_COMPLETION_ITEMS;
+    items.sort_by(|a, b| a.label.cmp(&b.label));
+    items.truncate(MAX_COMPLETION_ITEMS);
+
+    if items.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(CompletionResponse::List(CompletionList {
+        is_incomplete,
+        items,
+    })))
+}
+
+fn insert_completion_marker(content: &str, offset: usize) -> String {
+    let mut patched = String::with_capacity(content.len() + COMPLETION_MARKER.len());
+    patched.push_str(&content[..offset]);
+    patched.push_str(COMPLETION_MARKER);
+    patched.push_str(&content[offset..]);
+    patched
+}
+
+/// The part of the token under the cursor that has already been typed
+fn completion_prefix(text: &str) -> Option<&str> {
+    text.find(COMPLETION_MARKER)
+        .map(|marker_pos| &text[..marker_pos])
+}
+
+/// Count how often each key already appears in the block at `block_path`
+fn count_existing_keys(module: &AstModule, block_path: &[&str]) -> HashMap<Spur, u32> {
+    let interner = get_interner();
+    let mut counts = HashMap::new();
+
+    let mut items = &module.items;
+    for part in block_path {
+        let entity = items.iter().find_map(|item| match item {
+            AstEntityItem::Expression(expression) if expression.key.raw_value() == *part => {
+                match &expression.value {
+                    AstValue::Entity(entity) => Some(entity),
+                    _ => None,
+                }
+            }
+            _ => None,
+        });
+
+        match entity {
+            Some(AstEntity {
+                items: entity_items,
+                ..
+            }) => items = entity_items,
+            None => return counts,
+        }
+    }
+
+    for item in items {
+        if let AstEntityItem::Expression(expression) = item {
+            *counts
+                .entry(interner.get_or_intern(expression.key.raw_value()))
+                .or_insert(0) += 1;
+        }
+    }
+
+    counts
+}
+
+fn collect_key_completions(
+    block_type: &Arc<ScopedType>,
+    existing: &HashMap<Spur, u32>,
+    items: &mut Vec<CompletionItem>,
+) {
+    let Some(type_cache) = TypeCache::get() else {
+        return;
+    };
+    let resolver = type_cache.get_resolver();
+    let interner = get_interner();
+    let block_type = resolver.resolve_type(block_type.clone());
+
+    if let CwtTypeOrSpecial::ScopedUnion(scoped_types) = block_type.cwt_type() {
+        for scoped_type in scoped_types {
+            collect_key_completions(scoped_type, existing, items);
+        }
+        return;
+    }
+
+    let CwtTypeOrSpecialRef::Block(block) = block_type.cwt_type_for_matching() else {
+        return;
+    };
+
+    collect_property_completions(block, block_type.subtypes(), existing, items);
+
+    let mut has_alias_patterns = false;
+    let mut pattern_types: Vec<&PatternType> = block
+        .pattern_properties
+        .iter()
+        .map(|pattern| &pattern.pattern_type)
+        .collect();
+    for subtype in block_type.subtypes() {
+        if let Some(subtype_patterns) = block.subtype_pattern_properties.get(subtype) {
+            pattern_types.extend(subtype_patterns.iter().map(|pattern| &pattern.pattern_type));
+        }
+    }
+
+    for pattern_type in pattern_types {
+        if matches!(pattern_type, PatternType::AliasName { .. }) {
+            has_alias_patterns = true;
+        }
+
+        let kind = match pattern_type {
+            PatternType::AliasName { category } => alias_completion_kind(*category),
+            PatternType::Enum { .. } => CompletionItemKind::ENUM_MEMBER,
+            PatternType::Type { .. } => CompletionItemKind::REFERENCE,
+        };
+
+        items.extend(
+            resolver
+                .get_pattern_completions(pattern_type)
+                .into_iter()
+                .map(|label| CompletionItem {
+                    label,
+                    kind: Some(kind),
+                    ..Default::default()
+                }),
+        );
+    }
+
+    // Trigger and effect blocks can switch scope with scope and link properties
+    if has_alias_patterns {
+        items.extend(
+            resolver
+                .get_available_scope_and_link_properties(block_type.scope_stack())
+                .into_iter()
+                .map(|property| CompletionItem {
+                    label: interner.resolve(&property).to_string(),
+                    kind: Some(CompletionItemKind::MODULE),
+                    ..Default::default()
+                }),
+        );
+    }
+}
+
+/// Complete the statically named properties of a block and of its active subtypes, leaving out
+/// properties that already appear as often as their cardinality allows
+fn collect_property_completions(
+    block: &BlockType,
+    subtypes: &HashSet<Spur>,
+    existing: &HashMap<Spur, u32>,
+    items: &mut Vec<CompletionItem>,
+) {
+    let interner = get_interner();
+
+    let mut properties: Vec<(Spur, &cw_model::Property)> = block.properties.iter().collect();
+    for subtype in subtypes {
+        if let Some(subtype_properties) = block.subtype_properties.get(subtype) {
+            properties.extend(subtype_properties.iter());
+        }
+    }
+
+    for (key, property) in properties {
+        let max = property.options.cardinality_or_default().max;
+        let count = existing.get(&key).copied().unwrap_or(0);
+        if max.is_some_and(|max| count >= max) {
+            continue;
+        }
+
+        items.push(CompletionItem {
+            label: interner.resolve(&key).to_string(),
+            kind: Some(CompletionItemKind::PROPERTY),
+            documentation: property.documentation.map(|documentation| {
+                Documentation::String(interner.resolve(&documentation).to_string())
+            }),
+            ..Default::default()
+        });
+    }
+}
+
+/// Effects and triggers show up as functions, other aliases as plain properties
+fn alias_completion_kind(category: Spur) -> CompletionItemKind {
+    match get_interner().resolve(&category) {
+        "effect" | "trigger" => CompletionItemKind::FUNCTION,
+        _ => CompletionItemKind::PROPERTY,
+    }
+}
+
+fn collect_value_completions(value_type: &Arc<ScopedType>, items: &mut Vec<CompletionItem>) {
+    let Some(type_cache) = TypeCache::get() else {
+        return;
+    };
+    let value_type = type_cache.get_resolver().resolve_type(value_type.clone());
+
+    match value_type.cwt_type() {
+        CwtTypeOrSpecial::ScopedUnion(scoped_types) => {
+            for scoped_type in scoped_types {
+                collect_value_completions(scoped_type, items);
+            }
+        }
+        CwtTypeOrSpecial::CwtType(cwt_type) => {
+            collect_cwt_value_completions(cwt_type, &value_type, items)
+        }
+    }
+}
+
+fn collect_cwt_value_completions(
+    cwt_type: &Arc<CwtType>,
+    value_type: &Arc<ScopedType>,
+    items: &mut Vec<CompletionItem>,
+) {
+    let Some(type_cache) = TypeCache::get() else {
+        return;
+    };
+    let resolver = type_cache.get_resolver();
+    let interner = get_interner();
+
+    let value_item = |label: String, kind: CompletionItemKind| CompletionItem {
+        label,
+        kind: Some(kind),
+        ..Default::default()
+    };
+
+    match &**cwt_type {
+        CwtType::Literal(_) | CwtType::LiteralSet(_) | CwtType::Simple(SimpleType::Bool) => {
+            collect_literal_completions(cwt_type, items);
+        }
+        CwtType::Reference(ReferenceType::ValueSet { key }) => {
+            if let Some(values) = FullAnalysis::get().and_then(|analysis| {
+                analysis
+                    .dynamic_value_sets
+                    .get(&interner.get_or_intern(key))
+                    .cloned()
+            }) {
+                items.extend(values.iter().map(|value| {
+                    value_item(
+                        interner.resolve(value).to_string(),
+                        CompletionItemKind::VALUE,
+                    )
+                }));
+            }
+        }
+        CwtType::Reference(ReferenceType::TypeWithAffix {
+            key,
+            prefix,
+            suffix,
+        }) => {
+            let base_type = key.split('.').next().unwrap_or(key);
+            let Some(namespace) = type_cache
+                .get_cwt_analyzer()
+                .get_type(interner.get_or_intern(base_type))
+                .and_then(|type_def| type_def.path)
+            else {
+                return;
+            };
+
+            items.extend(
+                EntityRestructurer::get_namespace_entity_keys(namespace)
+                    .into_iter()
+                    .map(|entity_key| {
+                        value_item(
+                            format!(
+                                "{}{}{}",
+                                prefix.as_deref().unwrap_or(""),
+                                interner.resolve(&entity_key),
+                                suffix.as_deref().unwrap_or("")
+                            ),
+                            CompletionItemKind::REFERENCE,
+                        )
+                    }),
+            );
+        }
+        CwtType::Reference(ReferenceType::Scope { .. })
+        | CwtType::Reference(ReferenceType::ScopeGroup { .. }) => {
+            items.extend(
+                resolver
+                    .get_available_scope_and_link_properties(value_type.scope_stack())
+                    .into_iter()
+                    .map(|property| {
+                        value_item(
+                            interner.resolve(&property).to_string(),
+                            CompletionItemKind::MODULE,
+                        )
+                    }),
+            );
+        }
+        CwtType::Union(types) => {
+            for member in types {
+                let member_type = Arc::new(value_type.child(member.clone()));
+                collect_value_completions(&member_type, items);
+            }
+        }
+        CwtType::Comparable(base_type) => {
+            let base_type = Arc::new(value_type.child(base_type.as_ref().clone()));
+            collect_value_completions(&base_type, items);
+        }
+        _ => {}
+    }
+}
+
+/// Complete the values that are spelled out by the type itself
+fn collect_literal_completions(cwt_type: &CwtType, items: &mut Vec<CompletionItem>) {
+    let interner = get_interner();
+    let value_item = |label: String, kind: CompletionItemKind| CompletionItem {
+        label,
+        kind: Some(kind),
+        ..Default::default()
+    };
+
+    match cwt_type {
+        CwtType::Literal(literal) => {
+            items.push(value_item(
+                interner.resolve(literal).to_string(),
+                CompletionItemKind::VALUE,
+            ));
+        }
+        CwtType::LiteralSet(literals) => {
+            items.extend(literals.iter().map(|literal| {
+                value_item(
+                    interner.resolve(literal).to_string(),
+                    CompletionItemKind::ENUM_MEMBER,
+                )
+            }));
+        }
+        CwtType::Simple(SimpleType::Bool) => {
+            items.push(value_item("yes".to_string(), CompletionItemKind::KEYWORD));
+            items.push(value_item("no".to_string(), CompletionItemKind::KEYWORD));
+        }
+        _ => {}
+    }
+}
+
+fn collect_array_item_completions(block_type: &Arc<ScopedType>, items: &mut Vec<CompletionItem>) {
+    let Some(type_cache) = TypeCache::get() else {
+        return;
+    };
+    let block_type = type_cache.get_resolver().resolve_type(block_type.clone());
+
+    match block_type.cwt_type_for_matching() {
+        CwtTypeOrSpecialRef::Block(block) => {
+            for flag in &block.additional_flags {
+                let flag_type = Arc::new(block_type.child(flag.clone()));
+                collect_value_completions(&flag_type, items);
+            }
+        }
+        CwtTypeOrSpecialRef::Array(array) => {
+            let element_type = Arc::new(block_type.child((*array.element_type).clone()));
+            collect_value_completions(&element_type, items);
+        }
+        CwtTypeOrSpecialRef::ScopedUnion(scoped_types) => {
+            for scoped_type in scoped_types {
+                collect_array_item_completions(scoped_type, items);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cw_model::{Cardinality, CwtOptions, Property, SpurMap};
+
+    use super::*;
+
+    fn target_at_cursor(input: &str) -> Option<(CursorTargetKind, Vec<String>, String)> {
+        let offset = input.find('|').unwrap();
+        let content = input.replace('|', "");
+        let module = AstModuleCell::from_input(insert_completion_marker(&content, offset));
+        let ast = module.borrow_dependent().as_ref().ok()?;
+        let target = find_cursor_target(ast, offset)?;
+        let prefix = completion_prefix(&target.text)?.to_string();
+
+        Some((target.kind, target.path, prefix))
+    }
+
+    #[test]
+    fn test_completes_missing_value() {
+        let (kind, path, prefix) =
+            target_at_cursor("building_foo = {\n    category = |\n}\n").unwrap();
+
+        assert_eq!(kind, CursorTargetKind::Value);
+        assert_eq!(path, vec!["building_foo", "category"]);
+        assert_eq!(prefix, "");
+    }
+
+    #[test]
+    fn test_completes_partial_key() {
+        let (kind, path, prefix) =
+            target_at_cursor("building_foo = {\n    potential = { has_te| }\n}\n").unwrap();
+
+        assert_eq!(kind, CursorTargetKind::ArrayItem);
+        assert_eq!(path, vec!["building_foo", "potential"]);
+        assert_eq!(prefix, "has_te");
+    }
+
+    #[test]
+    fn test_completes_partial_value() {
+        let (kind, path, prefix) =
+            target_at_cursor("building_foo = {\n    category = reso|\n}\n").unwrap();
+
+        assert_eq!(kind, CursorTargetKind::Value);
+        assert_eq!(path, vec!["building_foo", "category"]);
+        assert_eq!(prefix, "reso");
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        let mut labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        labels.sort();
+        labels
+    }
+
+    /// A building-like block with a single `cost`, repeatable `upkeep`, and a `ship_size` that
+    /// only the `ship` subtype has
+    fn test_block_type() -> BlockType {
+        let interner = get_interner();
+        let simple = || Arc::new(CwtType::Simple(SimpleType::Int));
+
+        let mut properties = SpurMap::new();
+        properties.insert(interner.get_or_intern("cost"), Property::required(simple()));
+        properties.insert(
+            interner.get_or_intern("upkeep"),
+            Property::optional(simple()).with_options(CwtOptions {
+                cardinality: Some(Cardinality::required_repeating()),
+                ..Default::default()
+            }),
+        );
+
+        let mut ship_properties = SpurMap::new();
+        ship_properties.insert(
+            interner.get_or_intern("ship_size"),
+            Property::required(simple()),
+        );
+        let mut subtype_properties = SpurMap::new();
+        subtype_properties.insert(interner.get_or_intern("ship"), ship_properties);
+
+        BlockType {
+            type_name: None,
+            properties,
+            subtypes: SpurMap::new(),
+            subtype_properties,
+            subtype_pattern_properties: SpurMap::new(),
+            pattern_properties: Vec::new(),
+            localisation: None,
+            modifiers: None,
+            additional_flags: Vec::new(),
+        }
+    }
+
+    fn block_completions(input: &str, subtypes: &[&str]) -> Vec<CompletionItem> {
+        let module = AstModuleCell::from_input(input.to_string());
+        let ast = module.borrow_dependent().as_ref().unwrap();
+        let existing = count_existing_keys(ast, &["building_foo"]);
+        let subtypes = subtypes
+            .iter()
+            .map(|subtype| get_interner().get_or_intern(subtype))
+            .collect();
+
+        let mut items = Vec::new();
+        collect_property_completions(&test_block_type(), &subtypes, &existing, &mut items);
+        items
+    }
+
+    #[test]
+    fn test_block_completions_skip_exhausted_keys() {
+        let items = block_completions("building_foo = {\n    cost = 10\n    upkeep = 1\n}\n", &[]);
+
+        // `cost` may only appear once, `upkeep` can be repeated
+        assert_eq!(labels(&items), vec!["upkeep"]);
+        assert!(
+            items
+                .iter()
+                .all(|item| item.kind == Some(CompletionItemKind::PROPERTY))
+        );
+    }
+
+    #[test]
+    fn test_block_completions_include_subtype_properties() {
+        let input = "building_foo = {\n}\n";
+
+        assert_eq!(
+            labels(&block_completions(input, &[])),
+            vec!["cost", "upkeep"]
+        );
+        assert_eq!(
+            labels(&block_completions(input, &["ship"])),
+            vec!["cost", "ship_size", "upkeep"]
+        );
+    }
+
+    #[test]
+    fn test_literal_value_completions() {
+        let interner = get_interner();
+        let mut items = Vec::new();
+        collect_literal_completions(
+            &CwtType::LiteralSet(
+                ["resource", "research"]
+                    .iter()
+                    .map(|literal| interner.get_or_intern(literal))
+                    .collect(),
+            ),
+            &mut items,
+        );
+        collect_literal_completions(&CwtType::Simple(SimpleType::Bool), &mut items);
+
+        assert_eq!(labels(&items), vec!["no", "research", "resource", "yes"]);
+        assert_eq!(
+            items
+                .iter()
+                .find(|item| item.label == "research")
+                .and_then(|item| item.kind),
+            Some(CompletionItemKind::ENUM_MEMBER)
+        );
+    }
+}
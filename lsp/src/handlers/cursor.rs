@@ -0,0 +1,197 @@
+use std::ops::Range;
+
+use cw_parser::{
+    AstEntity, AstEntityItem, AstExpression, AstModule, AstNode, AstValue, AstVisitor,
+};
+
+/// What kind of script element the cursor is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorTargetKind {
+    /// The key of a `key = value` expression
+    Key,
+    /// The string value of a `key = value` expression
+    Value,
+    /// A bare string inside a block, like `{ a b c }`
+    ArrayItem,
+}
+
+/// The script element at a position in a document
+#[derive(Debug, Clone)]
+pub struct CursorTarget {
+    /// Keys from the top of the file down to the property the target belongs to. For keys and
+    /// values this ends with the expression's own key, for array items with the enclosing key.
+    pub path: Vec<String>,
+    pub kind: CursorTargetKind,
+    pub text: String,
+    /// Byte range of the token in the document
+    pub span: Range<usize>,
+}
+
+impl CursorTarget {
+    /// Byte offset of the text in the document, which is after the opening quote for quoted
+    /// strings
+    pub fn text_start(&self) -> usize {
+        self.span.start + (self.span.len() - self.text.len()) / 2
+    }
+}
+
+/// Find the key, value or array item at a byte offset in a module
+pub fn find_cursor_target(module: &AstModule, offset: usize) -> Option<CursorTarget> {
+    let mut finder = CursorTargetFinder {
+        offset,
+        current_path: Vec::new(),
+        found: None,
+    };
+    finder.visit_module(module);
+    finder.found
+}
+
+struct CursorTargetFinder {
+    offset: usize,
+    current_path: Vec<String>,
+    found: Option<CursorTarget>,
+}
+
+impl CursorTargetFinder {
+    fn contains(&self, span: &Range<usize>) -> bool {
+        self.offset >= span.start && self.offset <= span.end
+    }
+
+    fn found(&mut self, kind: CursorTargetKind, text: &str, span: Range<usize>) {
+        self.found = Some(CursorTarget {
+            path: self.current_path.clone(),
+            kind,
+            text: text.to_string(),
+            span,
+        });
+    }
+}
+
+impl<'a, 'ast> AstVisitor<'a, 'ast> for CursorTargetFinder
+where
+    'a: 'ast,
+{
+    fn visit_expression(&mut self, node: &'ast AstExpression<'a>) {
+        if self.found.is_some() || !self.contains(&node.span_range()) {
+            return;
+        }
+
+        self.current_path.push(node.key.raw_value().to_string());
+
+        if self.contains(&node.key.span_range()) {
+            self.found(
+                CursorTargetKind::Key,
+                node.key.raw_value(),
+                node.key.span_range(),
+            );
+        } else {
+            match &node.value {
+                AstValue::String(value) if self.contains(&value.span_range()) => {
+                    self.found(
+                        CursorTargetKind::Value,
+                        value.raw_value(),
+                        value.span_range(),
+                    );
+                }
+                AstValue::Entity(entity) => self.visit_entity(entity),
+                _ => {}
+            }
+        }
+
+        self.current_path.pop();
+    }
+
+    fn visit_entity(&mut self, node: &'ast AstEntity<'a>) {
+        if self.contains(&node.span_range()) {
+            self.walk_entity(node);
+        }
+    }
+
+    fn visit_entity_item(&mut self, node: &'ast AstEntityItem<'a>) {
+        if self.found.is_some() {
+            return;
+        }
+
+        match node {
+            AstEntityItem::Item(value) => {
+                if let AstValue::String(value) = &**value
+                    && self.contains(&value.span_range())
+                {
+                    self.found(
+                        CursorTargetKind::ArrayItem,
+                        value.raw_value(),
+                        value.span_range(),
+                    );
+                }
+            }
+            _ => self.walk_entity_item(node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cw_parser::AstModuleCell;
+
+    fn target_at(input: &str, needle: &str) -> Option<CursorTarget> {
+        let module = AstModuleCell::from_input(input.to_string());
+        let offset = input.find(needle).unwrap() + 1;
+        find_cursor_target(module.borrow_dependent().as_ref().unwrap(), offset)
+    }
+
+    #[test]
+    fn test_finds_nested_key() {
+        let input = "building_foo = {\n    potential = {\n        has_technology = tech_lasers_1\n    }\n}\n";
+        let target = target_at(input, "has_technology").unwrap();
+
+        assert_eq!(target.kind, CursorTargetKind::Key);
+        assert_eq!(target.text, "has_technology");
+        assert_eq!(
+            target.path,
+            vec!["building_foo", "potential", "has_technology"]
+        );
+    }
+
+    #[test]
+    fn test_finds_value() {
+        let input = "building_foo = {\n    potential = {\n        has_technology = tech_lasers_1\n    }\n}\n";
+        let target = target_at(input, "tech_lasers_1").unwrap();
+
+        assert_eq!(target.kind, CursorTargetKind::Value);
+        assert_eq!(target.text, "tech_lasers_1");
+        assert_eq!(
+            target.path,
+            vec!["building_foo", "potential", "has_technology"]
+        );
+    }
+
+    #[test]
+    fn test_finds_array_item() {
+        let input = "building_foo = {\n    prerequisites = { tech_a tech_b }\n}\n";
+        let target = target_at(input, "tech_b").unwrap();
+
+        assert_eq!(target.kind, CursorTargetKind::ArrayItem);
+        assert_eq!(target.text, "tech_b");
+        assert_eq!(target.path, vec!["building_foo", "prerequisites"]);
+    }
+
+    #[test]
+    fn test_text_start_skips_quotes() {
+        let input = "building_foo = {\n    icon = \"GFX_foo\"\n    cost = 10\n}\n";
+        let quoted = target_at(input, "GFX_foo").unwrap();
+        let unquoted = target_at(input, "cost").unwrap();
+
+        assert_eq!(quoted.text_start(), input.find("GFX_foo").unwrap());
+        assert_eq!(unquoted.text_start(), input.find("cost").unwrap());
+    }
+
+    #[test]
+    fn test_nothing_outside_tokens() {
+        let input = "building_foo = {\n\n    cost = 10\n}\n";
+        let module = AstModuleCell::from_input(input.to_string());
+        let offset = input.find("\n\n").unwrap() + 1;
+
+        assert!(find_cursor_target(module.borrow_dependent().as_ref().unwrap(), offset).is_none());
+    }
+}
@@ -0,0 +1,636 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, jsonrpc::Result};
+
+use crate::handlers::cache::{
+    DefinitionIndex, DefinitionLocation, SpriteCache, TypeCache, is_sprite_name,
+};
+use crate::handlers::common_validation::{
+    NamespaceValidationResult, detect_skip_root_key_container, is_type_per_file_namespace,
+    resolve_property_path_type, validate_namespace_and_caches,
+};
+use crate::handlers::cursor::{CursorTarget, CursorTargetKind, find_cursor_target};
+use crate::handlers::diagnostics::util::span_to_lsp_range;
+use crate::handlers::scoped_type::{CwtTypeOrSpecial, ScopedType};
+use crate::interner::get_interner;
+use cw_model::{AliasName, CwtType, PatternType, ReferenceType};
+use cw_parser::{AstEntityItem, AstModule, AstNode};
+use lasso::Spur;
+
+use super::document_cache::DocumentCache;
+use super::utils::position_to_offset;
+
+/// Something under the cursor that is defined somewhere else
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefinitionTarget {
+    /// An entity defined in a namespace, e.g. the target of a `<building>` reference
+    Entity { namespace: Spur, key: Spur },
+    /// A value of an `enum[...]`, simple or complex
+    Enum { enum_name: Spur, value: Spur },
+    /// A value of a `value_set[...]`/`value[...]`, which has no single definition
+    ValueSet { key: Spur, value: Spur },
+    /// A scripted variable, including the leading `@`
+    ScriptedVariable { name: Spur },
+    /// A sprite defined in an `interface/*.gfx` file, e.g. `GFX_evt_alien_city`
+    Sprite { name: Spur },
+}
+
+impl DefinitionTarget {
+    /// The text that refers to this target, without any prefix or suffix
+    pub fn name(&self) -> Spur {
+        match self {
+            DefinitionTarget::Entity { key, .. } => *key,
+            DefinitionTarget::Enum { value, .. } => *value,
+            DefinitionTarget::ValueSet { value, .. } => *value,
+            DefinitionTarget::ScriptedVariable { name } => *name,
+            DefinitionTarget::Sprite { name } => *name,
+        }
+    }
+}
+
+pub fn goto_definition(
+    _client: &Client,
+    documents: &Arc<RwLock<HashMap<String, String>>>,
+    document_cache: &DocumentCache,
+    params: GotoDefinitionParams,
+) -> Result<Option<GotoDefinitionResponse>> {
+    let uri = params
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_string();
+    let position = params.text_document_position_params.position;
+
+    let documents = documents.read().expect("Failed to read documents");
+    let content = match documents.get(&uri) {
+        Some(content) => content,
+        None => return Ok(None),
+    };
+
+    let offset = position_to_offset(content, position);
+
+    let cached_document = match document_cache.get(&uri) {
+        Some(cached_document) => cached_document,
+        None => return Ok(None),
+    };
+
+    let ast = match cached_document.borrow_ast() {
+        Ok(ast) => ast,
+        Err(_) => return Ok(None),
+    };
+
+    let target = match find_cursor_target(ast, offset) {
+        Some(target) => target,
+        None => return Ok(None),
+    };
+
+    let namespace_context = match validate_namespace_and_caches(&uri, &cached_document.root_dir) {
+        NamespaceValidationResult::Valid(context) => {
+            Some((context.namespace, context.namespace_type))
+        }
+        _ => None,
+    };
+
+    let definition_targets = find_definition_targets(ast, namespace_context, &target);
+
+    let mut locations = Vec::new();
+    for definition_target in definition_targets {
+        match definition_target {
+            DefinitionTarget::ScriptedVariable { name } => {
+                // Variables defined in the same file shadow the global ones
+                let local = find_local_scripted_variable(ast, name);
+                if !local.is_empty() {
+                    let url = params
+                        .text_document_position_params
+                        .text_document
+                        .uri
+                        .clone();
+                    locations.extend(local.into_iter().map(|span| Location {
+                        uri: url.clone(),
+                        range: span_to_lsp_range(span, content),
+                    }));
+                } else {
+                    locations.extend(
+                        DefinitionIndex::get_scripted_variable(name)
+                            .iter()
+                            .filter_map(to_lsp_location),
+                    );
+                }
+            }
+            other => locations.extend(
+                get_target_locations(other)
+                    .iter()
+                    .filter_map(to_lsp_location),
+            ),
+        }
+    }
+
+    match locations.len() {
+        0 => Ok(None),
+        1 => Ok(Some(GotoDefinitionResponse::Scalar(locations.remove(0)))),
+        _ => Ok(Some(GotoDefinitionResponse::Array(locations))),
+    }
+}
+
+/// Work out what the element under the cursor refers to, based on the CWT type expected at
+/// that position. Falls back to scripted effects and triggers for keys and values that the
+/// type information can't explain.
+pub fn find_definition_targets(
+    module: &AstModule,
+    namespace_context: Option<(Spur, Arc<ScopedType>)>,
+    target: &CursorTarget,
+) -> Vec<DefinitionTarget> {
+    let mut targets: Vec<DefinitionTarget> =
+        find_definition_target_ranges(module, namespace_context, target)
+            .into_iter()
+            .map(|(target, _)| target)
+            .collect();
+    targets.dedup();
+    targets
+}
+
+/// Like `find_definition_targets`, but also returns the byte range in the document that names
+/// each target. For prefixed or suffixed references like `building_<x>_desc` this is only the
+/// part of the token left after stripping the affixes.
+pub fn find_definition_target_ranges(
+    module: &AstModule,
+    namespace_context: Option<(Spur, Arc<ScopedType>)>,
+    target: &CursorTarget,
+) -> Vec<(DefinitionTarget, std::ops::Range<usize>)> {
+    let text_start = target.text_start();
+    find_text_targets(module, namespace_context, target)
+        .into_iter()
+        .map(|(definition_target, range)| {
+            (
+                definition_target,
+                text_start + range.start..text_start + range.end,
+            )
+        })
+        .collect()
+}
+
+/// Find the definition targets of a cursor target, with ranges relative to its text
+fn find_text_targets(
+    module: &AstModule,
+    namespace_context: Option<(Spur, Arc<ScopedType>)>,
+    target: &CursorTarget,
+) -> Vec<(DefinitionTarget, std::ops::Range<usize>)> {
+    let interner = get_interner();
+    let text = interner.get_or_intern(&target.text);
+    let whole_text = 0..target.text.len();
+
+    if target.text.starts_with('@') {
+        return vec![(
+            DefinitionTarget::ScriptedVariable { name: text },
+            whole_text,
+        )];
+    }
+
+    // Sprites are used the same way everywhere, in scripts as well as in `.gui` files
+    if is_sprite_name(&target.text) {
+        return vec![(DefinitionTarget::Sprite { name: text }, whole_text)];
+    }
+
+    let mut targets = Vec::new();
+
+    if let Some((namespace, namespace_type)) = namespace_context {
+        let path: Vec<&str> = target.path.iter().map(|s| s.as_str()).collect();
+
+        match target.kind {
+            CursorTargetKind::Key => {
+                if is_definition_key(&namespace_type, &path) {
+                    targets.push((
+                        DefinitionTarget::Entity {
+                            namespace: TypeCache::get_actual_namespace(namespace),
+                            key: text,
+                        },
+                        whole_text.clone(),
+                    ));
+                } else if let Some(parent_type) = resolve_property_path_type(
+                    module,
+                    namespace,
+                    &namespace_type,
+                    &path[..path.len() - 1],
+                ) {
+                    collect_key_targets(&parent_type, &target.text, &mut targets);
+                }
+            }
+            CursorTargetKind::Value => {
+                if let Some(value_type) =
+                    resolve_property_path_type(module, namespace, &namespace_type, &path)
+                {
+                    collect_value_targets(&value_type, &target.text, &mut targets);
+                }
+            }
+            CursorTargetKind::ArrayItem => {
+                if let Some(block_type) =
+                    resolve_property_path_type(module, namespace, &namespace_type, &path)
+                {
+                    collect_array_item_targets(&block_type, &target.text, &mut targets);
+                }
+            }
+        }
+    }
+
+    if targets.is_empty() {
+        // Scripted effects and triggers can be called from places the type information
+        // doesn't cover, like inline scripts or unresolved scopes
+        for type_name in ["scripted_effect", "scripted_trigger"] {
+            if let Some(namespace) = get_type_namespace(interner.get_or_intern(type_name)) {
+                targets.push((
+                    DefinitionTarget::Entity {
+                        namespace,
+                        key: text,
+                    },
+                    whole_text.clone(),
+                ));
+            }
+        }
+    }
+
+    targets.dedup();
+    targets
+}
+
+/// Get the locations of a definition target from the definition index
+pub fn get_target_locations(target: DefinitionTarget) -> Vec<DefinitionLocation> {
+    let interner = get_interner();
+
+    match target {
+        DefinitionTarget::Entity { namespace, key } => {
+            DefinitionIndex::get_definitions(namespace, key)
+        }
+        DefinitionTarget::Enum { enum_name, value } => {
+            let Some(type_cache) = TypeCache::get() else {
+                return Vec::new();
+            };
+            let Some(enum_def) = type_cache.get_cwt_analyzer().get_enum(enum_name) else {
+                return Vec::new();
+            };
+
+            let location = match &enum_def.complex {
+                Some(complex_def) => DefinitionIndex::find_value_in_namespace(
+                    complex_def.path,
+                    interner.resolve(&value),
+                ),
+                None => DefinitionIndex::find_enum_value(
+                    interner.resolve(&enum_name),
+                    interner.resolve(&value),
+                ),
+            };
+
+            location.into_iter().collect()
+        }
+        DefinitionTarget::ValueSet { .. } => Vec::new(),
+        DefinitionTarget::ScriptedVariable { name } => DefinitionIndex::get_scripted_variable(name),
+        DefinitionTarget::Sprite { name } => SpriteCache::get_definitions(interner.resolve(&name))
+            .into_iter()
+            .map(|sprite| sprite.location)
+            .collect(),
+    }
+}
+
+/// Convert an indexed location into an LSP location. The span's columns already count UTF-16
+/// code units, see [`LineIndex::position`](crate::handlers::cache::LineIndex::position).
+pub fn to_lsp_location(location: &DefinitionLocation) -> Option<Location> {
+    let uri = Url::from_file_path(&location.path).ok()?;
+
+    Some(Location {
+        uri,
+        range: Range {
+            start: Position {
+                line: (location.span.start.line - 1) as u32,
+                character: (location.span.start.column - 1) as u32,
+            },
+            end: Position {
+                line: (location.span.end.line - 1) as u32,
+                character: (location.span.end.column - 1) as u32,
+            },
+        },
+    })
+}
+
+/// Get the namespace (path) that entities of a CWT type are defined in
+fn get_type_namespace(type_name: Spur) -> Option<Spur> {
+    let type_cache = TypeCache::get()?;
+    let type_def = type_cache.get_cwt_analyzer().get_type(type_name)?;
+    type_def.path.map(TypeCache::get_actual_namespace)
+}
+
+/// Turn a `<type>` or `<type.subtype>` key into a target for `value`
+fn type_reference_target(type_key: &str, value: &str) -> Option<DefinitionTarget> {
+    let interner = get_interner();
+    let base_type = type_key.split('.').next().unwrap_or(type_key);
+    let namespace = get_type_namespace(interner.get_or_intern(base_type))?;
+
+    Some(DefinitionTarget::Entity {
+        namespace,
+        key: interner.get_or_intern(value),
+    })
+}
+
+/// Strip an optional prefix and suffix from a value, e.g. `building_<x>_desc`, returning the
+/// byte range of what is left
+pub fn strip_affixes(
+    value: &str,
+    prefix: Option<&str>,
+    suffix: Option<&str>,
+) -> Option<std::ops::Range<usize>> {
+    let start = match prefix {
+        Some(prefix) => value.strip_prefix(prefix).map(|_| prefix.len())?,
+        None => 0,
+    };
+    let end = match suffix {
+        Some(suffix) => value[start..]
+            .strip_suffix(suffix)
+            .map(|_| value.len() - suffix.len())?,
+        None => value.len(),
+    };
+    Some(start..end)
+}
+
+/// Targets found for a cursor target, with the range of the target's name within its text
+type TextTargets = Vec<(DefinitionTarget, std::ops::Range<usize>)>;
+
+/// Whether a key path points at a top-level definition rather than a property inside one
+fn is_definition_key(namespace_type: &Arc<ScopedType>, path: &[&str]) -> bool {
+    if is_type_per_file_namespace(namespace_type) {
+        return false;
+    }
+
+    match path {
+        [_] => true,
+        [container, _] => {
+            detect_skip_root_key_container(namespace_type, get_interner().get_or_intern(container))
+                .is_skip_root_key_container
+        }
+        _ => false,
+    }
+}
+
+fn collect_value_targets(scoped_type: &Arc<ScopedType>, value: &str, out: &mut TextTargets) {
+    match scoped_type.cwt_type() {
+        CwtTypeOrSpecial::ScopedUnion(scoped_types) => {
+            for scoped_type in scoped_types {
+                collect_value_targets(scoped_type, value, out);
+            }
+        }
+        CwtTypeOrSpecial::CwtType(cwt_type) => collect_cwt_value_targets(cwt_type, value, out),
+    }
+}
+
+fn collect_cwt_value_targets(cwt_type: &CwtType, value: &str, out: &mut TextTargets) {
+    let interner = get_interner();
+
+    match cwt_type {
+        CwtType::Reference(ReferenceType::Type { key }) => {
+            out.extend(type_reference_target(key, value).map(|target| (target, 0..value.len())));
+        }
+        CwtType::Reference(ReferenceType::TypeWithAffix {
+            key,
+            prefix,
+            suffix,
+        }) => {
+            if let Some(range) = strip_affixes(value, prefix.as_deref(), suffix.as_deref()) {
+                out.extend(
+                    type_reference_target(key, &value[range.clone()]).map(|target| (target, range)),
+                );
+            }
+        }
+        CwtType::Reference(ReferenceType::Enum { key }) => {
+            out.push((
+                DefinitionTarget::Enum {
+                    enum_name: interner.get_or_intern(key),
+                    value: interner.get_or_intern(value),
+                },
+                0..value.len(),
+            ));
+        }
+        CwtType::Reference(ReferenceType::ValueSet { key })
+        | CwtType::Reference(ReferenceType::Value { key }) => {
+            out.push((
+                DefinitionTarget::ValueSet {
+                    key: interner.get_or_intern(key),
+                    value: interner.get_or_intern(value),
+                },
+                0..value.len(),
+            ));
+        }
+        CwtType::Union(types) => {
+            for cwt_type in types {
+                collect_cwt_value_targets(cwt_type, value, out);
+            }
+        }
+        CwtType::Comparable(base_type) => collect_cwt_value_targets(base_type, value, out),
+        _ => {}
+    }
+}
+
+fn collect_array_item_targets(scoped_type: &Arc<ScopedType>, value: &str, out: &mut TextTargets) {
+    match scoped_type.cwt_type() {
+        CwtTypeOrSpecial::ScopedUnion(scoped_types) => {
+            for scoped_type in scoped_types {
+                collect_array_item_targets(scoped_type, value, out);
+            }
+        }
+        CwtTypeOrSpecial::CwtType(cwt_type) => collect_cwt_array_item_targets(cwt_type, value, out),
+    }
+}
+
+fn collect_cwt_array_item_targets(cwt_type: &CwtType, value: &str, out: &mut TextTargets) {
+    match cwt_type {
+        CwtType::Array(array_type) => {
+            collect_cwt_value_targets(&array_type.element_type, value, out)
+        }
+        CwtType::Block(block_type) => {
+            for flag in &block_type.additional_flags {
+                collect_cwt_value_targets(flag, value, out);
+            }
+        }
+        CwtType::Union(types) => {
+            for cwt_type in types {
+                collect_cwt_array_item_targets(cwt_type, value, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_key_targets(parent_type: &Arc<ScopedType>, key: &str, out: &mut TextTargets) {
+    let Some(type_cache) = TypeCache::get() else {
+        return;
+    };
+    let parent_type = type_cache.get_resolver().resolve_type(parent_type.clone());
+
+    match parent_type.cwt_type() {
+        CwtTypeOrSpecial::ScopedUnion(scoped_types) => {
+            for scoped_type in scoped_types {
+                collect_key_targets(scoped_type, key, out);
+            }
+        }
+        CwtTypeOrSpecial::CwtType(cwt_type) => collect_cwt_key_targets(cwt_type, key, out),
+    }
+}
+
+fn collect_cwt_key_targets(cwt_type: &CwtType, key_str: &str, out: &mut TextTargets) {
+    let Some(type_cache) = TypeCache::get() else {
+        return;
+    };
+    let interner = get_interner();
+    let key = interner.get_or_intern(key_str);
+
+    match cwt_type {
+        CwtType::Block(block_type) => {
+            // Statically named properties are defined by the CWT rules, not by game data
+            if block_type.properties.contains_key(&key) {
+                return;
+            }
+
+            for pattern in &block_type.pattern_properties {
+                if !type_cache
+                    .get_resolver()
+                    .key_matches_pattern_type(key, &pattern.pattern_type)
+                {
+                    continue;
+                }
+
+                match &pattern.pattern_type {
+                    PatternType::Type { key: type_key } => {
+                        out.extend(
+                            type_reference_target(interner.resolve(type_key), key_str)
+                                .map(|target| (target, 0..key_str.len())),
+                        );
+                    }
+                    PatternType::Enum { key: enum_name } => out.push((
+                        DefinitionTarget::Enum {
+                            enum_name: *enum_name,
+                            value: key,
+                        },
+                        0..key_str.len(),
+                    )),
+                    PatternType::AliasName { category } => {
+                        collect_alias_key_targets(*category, key_str, out);
+                    }
+                }
+            }
+        }
+        CwtType::Union(types) => {
+            for cwt_type in types {
+                collect_cwt_key_targets(cwt_type, key_str, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Keys matched by `alias_name[category]` can be dynamic aliases like `alias[effect:<scripted_effect>]`
+fn collect_alias_key_targets(category: Spur, key_str: &str, out: &mut TextTargets) {
+    let Some(type_cache) = TypeCache::get() else {
+        return;
+    };
+    let Some(aliases) = type_cache
+        .get_cwt_analyzer()
+        .get_aliases_for_category(category)
+    else {
+        return;
+    };
+    let interner = get_interner();
+    let key = interner.get_or_intern(key_str);
+
+    for alias_pattern in aliases {
+        match &alias_pattern.name {
+            AliasName::Static(_) => {}
+            AliasName::TypeRef(type_name) => {
+                out.extend(
+                    type_reference_target(interner.resolve(type_name), key_str)
+                        .map(|target| (target, 0..key_str.len())),
+                );
+            }
+            AliasName::TypeRefWithPrefixSuffix(type_name, prefix, suffix) => {
+                let prefix = prefix.map(|prefix| interner.resolve(&prefix));
+                let suffix = suffix.map(|suffix| interner.resolve(&suffix));
+                if let Some(range) = strip_affixes(key_str, prefix, suffix) {
+                    out.extend(
+                        type_reference_target(interner.resolve(type_name), &key_str[range.clone()])
+                            .map(|target| (target, range)),
+                    );
+                }
+            }
+            AliasName::Enum(enum_name) => out.push((
+                DefinitionTarget::Enum {
+                    enum_name: *enum_name,
+                    value: key,
+                },
+                0..key_str.len(),
+            )),
+        }
+    }
+}
+
+/// Find `@name = ...` definitions at the top of the current file
+fn find_local_scripted_variable(module: &AstModule, name: Spur) -> Vec<std::ops::Range<usize>> {
+    let interner = get_interner();
+
+    module
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            AstEntityItem::Expression(expression)
+                if interner.get_or_intern(expression.key.raw_value()) == name =>
+            {
+                Some(expression.key.span_range())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::cache::{DefinitionSource, LineIndex};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_lsp_location_counts_utf16_columns() {
+        let content = "# 🚀 rockets\nfoo = { name = \"🚀\" } building_rocket = {}\n";
+        let start = content.find("building_rocket").unwrap();
+        let location = DefinitionLocation {
+            path: PathBuf::from("/mod/common/buildings/00_buildings.txt"),
+            span: LineIndex::new(content).span(content, start..start + 15),
+            source: DefinitionSource::Mod(PathBuf::from("/mod")),
+            type_name: None,
+        };
+
+        let location = to_lsp_location(&location).unwrap();
+        assert_eq!(
+            location.range,
+            span_to_lsp_range(start..start + 15, content)
+        );
+        assert_eq!(location.range.start, Position::new(1, 22));
+    }
+
+    #[test]
+    fn test_strip_affixes_returns_range_of_name() {
+        let token = "building_in_desc";
+        let range = strip_affixes(token, Some("building_"), Some("_desc")).unwrap();
+
+        // Only the stripped name, not the `in` inside `building`
+        assert_eq!(range, 9..11);
+        assert_eq!(&token[range], "in");
+    }
+
+    #[test]
+    fn test_strip_affixes_requires_both_affixes() {
+        assert_eq!(strip_affixes("tech_lasers", None, None), Some(0..11));
+        assert_eq!(
+            strip_affixes("tech_lasers", Some("tech_"), None),
+            Some(5..11)
+        );
+        assert_eq!(strip_affixes("tech_lasers", Some("building_"), None), None);
+        assert_eq!(strip_affixes("tech_lasers", None, Some("_desc")), None);
+        // The prefix and suffix can't overlap
+        assert_eq!(strip_affixes("a_b", Some("a_"), Some("_b")), None);
+    }
+}
@@ -1,11 +1,19 @@
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 use tower_lsp::Client;
 use url::Url;
 
+pub mod cardinality;
 pub mod diagnostic;
+pub mod duplicates;
+pub mod icon;
+pub mod localisation;
+pub mod modifier_category;
+pub mod name_format;
 pub mod provider;
+pub mod range;
 pub mod scope_validation;
 pub mod structural;
 pub mod type_validation;
@@ -26,3 +34,47 @@ pub async fn generate_diagnostics(
         .publish_diagnostics(Url::parse(uri).unwrap(), diagnostics, None)
         .await;
 }
+
+/// How long a document has to stay unchanged before its diagnostics are generated
+const DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Delays diagnostics until a document stops changing, so fast typing doesn't start a new
+/// diagnostics run for every keystroke
+#[derive(Clone, Default)]
+pub struct DiagnosticsDebouncer {
+    /// The latest change to each document, runs for earlier changes are dropped
+    generations: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl DiagnosticsDebouncer {
+    /// Generate diagnostics for a document once it hasn't changed for a while
+    pub fn schedule(
+        &self,
+        client: Client,
+        documents: Arc<RwLock<HashMap<String, String>>>,
+        uri: String,
+    ) {
+        let generation = {
+            let mut generations = self.generations.lock().unwrap();
+            let generation = generations.entry(uri.clone()).or_insert(0);
+            *generation += 1;
+            *generation
+        };
+
+        let generations = self.generations.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(DIAGNOSTICS_DEBOUNCE).await;
+
+            if generations.lock().unwrap().get(&uri) != Some(&generation) {
+                return;
+            }
+
+            generate_diagnostics(&client, &documents, &uri).await;
+        });
+    }
+
+    /// Forget a closed document, which also drops any run that is still pending for it
+    pub fn cancel(&self, uri: &str) {
+        self.generations.lock().unwrap().remove(uri);
+    }
+}
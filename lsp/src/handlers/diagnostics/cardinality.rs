@@ -0,0 +1,621 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use cw_model::{Cardinality, CwtOptions, CwtType, PatternType, SeverityLevel, SimpleType};
+use cw_parser::{AstEntity, AstEntityItem, AstNode};
+use lasso::Spur;
+use tower_lsp::lsp_types::DiagnosticSeverity;
+
+use crate::handlers::cache::TypeCache;
+use crate::handlers::diagnostics::diagnostic::{
+    DiagnosticFix, UnresolvedDiagnostic, create_cardinality_diagnostic,
+    create_missing_properties_diagnostic,
+};
+use crate::handlers::diagnostics::util::span_to_lsp_range;
+use crate::handlers::scoped_type::{CwtTypeOrSpecialRef, ScopedType};
+use crate::interner::get_interner;
+
+/// A property rule of a block with its cardinality, and the keys in the entity it matched
+struct CardinalityRule<'b> {
+    key: RuleKey<'b>,
+    cardinality: Cardinality,
+    severity: DiagnosticSeverity,
+    property_type: &'b CwtType,
+    occurrences: Vec<Range<usize>>,
+}
+
+enum RuleKey<'b> {
+    Literal(Spur),
+    Pattern(&'b PatternType),
+}
+
+/// Check that every property of a block appears as many times as its cardinality allows.
+/// Properties of the subtypes that matched the entity are included, so a property required only
+/// for one subtype is only demanded when that subtype matches.
+pub fn validate_cardinality<'a>(
+    entity: &AstEntity<'_>,
+    expected_type: &ScopedType,
+    content: &'a str,
+) -> Vec<UnresolvedDiagnostic<'a>> {
+    let mut diagnostics = Vec::new();
+
+    let CwtTypeOrSpecialRef::Block(block_type) = expected_type.cwt_type_for_matching() else {
+        return diagnostics;
+    };
+    let interner = get_interner();
+
+    let mut rules: Vec<CardinalityRule> = Vec::new();
+    let mut literal_keys = HashSet::new();
+
+    for (key, property) in block_type.properties.iter() {
+        literal_keys.insert(key);
+        add_rule(
+            &mut rules,
+            RuleKey::Literal(key),
+            &property.options,
+            &property.property_type,
+        );
+    }
+    for pattern in &block_type.pattern_properties {
+        add_rule(
+            &mut rules,
+            RuleKey::Pattern(&pattern.pattern_type),
+            &pattern.options,
+            &pattern.value_type,
+        );
+    }
+    for subtype in expected_type.subtypes() {
+        if let Some(subtype_properties) = block_type.subtype_properties.get(subtype) {
+            for (key, property) in subtype_properties.iter() {
+                literal_keys.insert(key);
+                add_rule(
+                    &mut rules,
+                    RuleKey::Literal(key),
+                    &property.options,
+                    &property.property_type,
+                );
+            }
+        }
+        if let Some(subtype_patterns) = block_type.subtype_pattern_properties.get(subtype) {
+            for pattern in subtype_patterns {
+                add_rule(
+                    &mut rules,
+                    RuleKey::Pattern(&pattern.pattern_type),
+                    &pattern.options,
+                    &pattern.value_type,
+                );
+            }
+        }
+    }
+
+    if rules.is_empty() {
+        return diagnostics;
+    }
+
+    // Inline scripts, arguments and conditional blocks can provide any property, so nothing can
+    // be said about properties that seem to be missing
+    let mut may_be_incomplete = false;
+    for item in &entity.items {
+        match item {
+            AstEntityItem::Expression(expr) => {
+                let key_name = expr.key.raw_value();
+                if key_name == "inline_script" || key_name.contains('$') {
+                    may_be_incomplete = true;
+                    continue;
+                }
+
+                // Literal keys take precedence over patterns, like in property navigation
+                let key = interner.get_or_intern(key_name);
+                let rule = match rules.iter().position(
+                    |rule| matches!(rule.key, RuleKey::Literal(literal) if literal == key),
+                ) {
+                    Some(index) => Some(index),
+                    None if literal_keys.contains(&key) => None,
+                    None => rules.iter().position(|rule| match rule.key {
+                        RuleKey::Pattern(pattern_type) => TypeCache::get().is_some_and(|cache| {
+                            cache
+                                .get_resolver()
+                                .key_matches_pattern_type(key, pattern_type)
+                        }),
+                        RuleKey::Literal(_) => false,
+                    }),
+                };
+
+                if let Some(index) = rule {
+                    rules[index].occurrences.push(expr.key.span_range());
+                }
+            }
+            AstEntityItem::Conditional(_) => may_be_incomplete = true,
+            AstEntityItem::Item(_) => {}
+        }
+    }
+
+    let type_name = expected_type.type_name_for_display();
+    let mut missing: Vec<&CardinalityRule> = Vec::new();
+
+    for rule in &rules {
+        let count = rule.occurrences.len() as u32;
+
+        if let Some(max) = rule.cardinality.max
+            && count > max
+        {
+            let message = format!(
+                "{} can appear at most {} in {}, found {}",
+                rule_display(&rule.key),
+                times(max),
+                type_name,
+                count
+            );
+            for span in &rule.occurrences[max as usize..] {
+                diagnostics.push(create_cardinality_diagnostic(
+                    span.clone(),
+                    &message,
+                    rule.severity,
+                    content,
+                ));
+            }
+        }
+
+        let min = rule.cardinality.min.unwrap_or(0);
+        if may_be_incomplete || count >= min {
+            continue;
+        }
+
+        match rule.key {
+            RuleKey::Literal(_) if count == 0 => missing.push(rule),
+            _ => {
+                let message = format!(
+                    "{} must appear at least {} in {}, found {}",
+                    rule_display(&rule.key),
+                    times(min),
+                    type_name,
+                    count
+                );
+                let span = match rule.occurrences.first() {
+                    Some(span) => span.clone(),
+                    None => opening_brace(entity, content),
+                };
+                diagnostics.push(create_cardinality_diagnostic(
+                    span,
+                    &message,
+                    rule.severity,
+                    content,
+                ));
+            }
+        }
+    }
+
+    // Missing properties are grouped by severity, with a quick fix to add them all at once
+    missing.sort_by_key(|rule| rule_display(&rule.key));
+    let mut severities = Vec::new();
+    for rule in &missing {
+        if !severities.contains(&rule.severity) {
+            severities.push(rule.severity);
+        }
+    }
+    for severity in severities {
+        let group: Vec<&CardinalityRule> = missing
+            .iter()
+            .filter(|rule| rule.severity == severity)
+            .copied()
+            .collect();
+        diagnostics.push(create_missing_properties_diagnostic_with_fix(
+            entity, &group, &type_name, severity, content,
+        ));
+    }
+
+    diagnostics
+}
+
+fn add_rule<'b>(
+    rules: &mut Vec<CardinalityRule<'b>>,
+    key: RuleKey<'b>,
+    options: &'b CwtOptions,
+    property_type: &'b CwtType,
+) {
+    let cardinality = options.cardinality_or_default();
+
+    // Subtype properties override the base property with the same key
+    if let RuleKey::Literal(key) = key {
+        rules.retain(|rule| !matches!(rule.key, RuleKey::Literal(existing) if existing == key));
+    }
+
+    rules.push(CardinalityRule {
+        key,
+        severity: rule_severity(options, &cardinality),
+        cardinality,
+        property_type,
+        occurrences: Vec::new(),
+    });
+}
+
+/// The `## severity` of a property wins, otherwise soft bounds (`~1..2`) are only warnings
+fn rule_severity(options: &CwtOptions, cardinality: &Cardinality) -> DiagnosticSeverity {
+    match &options.severity {
+        Some(SeverityLevel::Error) => DiagnosticSeverity::ERROR,
+        Some(SeverityLevel::Warning) => DiagnosticSeverity::WARNING,
+        Some(SeverityLevel::Information) => DiagnosticSeverity::INFORMATION,
+        Some(SeverityLevel::Hint) => DiagnosticSeverity::HINT,
+        None if cardinality.soft => DiagnosticSeverity::WARNING,
+        None => DiagnosticSeverity::ERROR,
+    }
+}
+
+fn rule_display(key: &RuleKey) -> String {
+    let interner = get_interner();
+    match key {
+        RuleKey::Literal(key) => format!("'{}'", interner.resolve(key)),
+        RuleKey::Pattern(PatternType::AliasName { category }) => {
+            format!("alias_name[{}]", interner.resolve(category))
+        }
+        RuleKey::Pattern(PatternType::Enum { key }) => format!("enum[{}]", interner.resolve(key)),
+        RuleKey::Pattern(PatternType::Type { key }) => format!("<{}>", interner.resolve(key)),
+    }
+}
+
+fn times(count: u32) -> String {
+    match count {
+        1 => "once".to_string(),
+        2 => "twice".to_string(),
+        _ => format!("{} times", count),
+    }
+}
+
+/// The opening brace of a block, where diagnostics about the block as a whole are reported
+fn opening_brace(entity: &AstEntity<'_>, content: &str) -> Range<usize> {
+    match content[entity.span.clone()].find('{') {
+        Some(offset) => entity.span.start + offset..entity.span.start + offset + 1,
+        None => entity.span.clone(),
+    }
+}
+
+fn create_missing_properties_diagnostic_with_fix<'a>(
+    entity: &AstEntity<'_>,
+    rules: &[&CardinalityRule],
+    type_name: &str,
+    severity: DiagnosticSeverity,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    let interner = get_interner();
+    let (keys, properties): (Vec<String>, Vec<String>) = rules
+        .iter()
+        .filter_map(|rule| match rule.key {
+            RuleKey::Literal(key) => {
+                let key = interner.resolve(&key);
+                let property = format!("{} = {}", key, placeholder_value(rule.property_type));
+                Some((key.to_string(), property))
+            }
+            RuleKey::Pattern(_) => None,
+        })
+        .unzip();
+
+    let open_brace = opening_brace(entity, content);
+    let diagnostic = create_missing_properties_diagnostic(
+        open_brace.clone(),
+        &keys,
+        type_name,
+        severity,
+        content,
+    );
+
+    let Some(close_brace) = content[entity.span.clone()]
+        .rfind('}')
+        .map(|offset| entity.span.start + offset)
+    else {
+        return diagnostic;
+    };
+
+    let line_start = content[..close_brace]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    let closing_indent = &content[line_start..close_brace];
+    let (insert_at, text) = if closing_indent.trim().is_empty() && line_start > open_brace.start {
+        // The block spans multiple lines, add each property on its own line
+        let indent_unit = if content.contains("\n\t") {
+            "\t"
+        } else {
+            "    "
+        };
+        let text = properties
+            .iter()
+            .map(|property| format!("{}{}{}\n", closing_indent, indent_unit, property))
+            .collect::<String>();
+        (line_start, text)
+    } else {
+        let separator = if content[..close_brace].ends_with(char::is_whitespace) {
+            ""
+        } else {
+            " "
+        };
+        (
+            close_brace,
+            format!("{}{} ", separator, properties.join(" ")),
+        )
+    };
+
+    diagnostic.with_fix(DiagnosticFix::InsertProperties {
+        keys,
+        position: span_to_lsp_range(insert_at..insert_at, content).start,
+        text,
+    })
+}
+
+/// A value to insert for a new property, which the user is expected to fill in
+fn placeholder_value(property_type: &CwtType) -> String {
+    let interner = get_interner();
+    match property_type {
+        CwtType::Simple(SimpleType::Bool) => "yes".to_string(),
+        CwtType::Simple(
+            SimpleType::Int
+            | SimpleType::Float
+            | SimpleType::ValueField
+            | SimpleType::IntValueField
+            | SimpleType::VariableField
+            | SimpleType::IntVariableField,
+        ) => "0".to_string(),
+        CwtType::Simple(SimpleType::DateField) => "2200.1.1".to_string(),
+        CwtType::Ranged(_, range) if !range.contains(0.0) => match range.min.as_f64() {
+            Some(_) => range.min.to_string(),
+            None => range.max.to_string(),
+        },
+        CwtType::Ranged(_, _) => "0".to_string(),
+        CwtType::Literal(value) => interner.resolve(value).to_string(),
+        CwtType::LiteralSet(values) => values
+            .iter()
+            .map(|value| interner.resolve(value))
+            .min()
+            .unwrap_or("\"\"")
+            .to_string(),
+        CwtType::Block(_) | CwtType::Array(_) => "{ }".to_string(),
+        CwtType::Comparable(base_type) => placeholder_value(base_type),
+        CwtType::Union(types) => types
+            .first()
+            .map(|union_type| placeholder_value(union_type))
+            .unwrap_or_else(|| "\"\"".to_string()),
+        _ => "\"\"".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use cw_model::{BlockType, Property, SpurMap};
+    use cw_parser::{AstModuleCell, AstValue};
+
+    use super::*;
+    use crate::handlers::scope::ScopeStack;
+
+    fn property(cardinality: Cardinality, severity: Option<SeverityLevel>) -> Property {
+        Property {
+            property_type: Arc::new(CwtType::Simple(SimpleType::Int)),
+            options: CwtOptions {
+                cardinality: Some(cardinality),
+                severity,
+                ..Default::default()
+            },
+            documentation: None,
+        }
+    }
+
+    /// A block with a required `cost`, an optional `tier`, a softly required `weight`, and a
+    /// `ship_size` that is only required for the `ship` subtype
+    fn test_block_type(subtypes: &[&str]) -> ScopedType {
+        let interner = get_interner();
+        let mut properties = SpurMap::new();
+        properties.insert(
+            interner.get_or_intern("cost"),
+            property(Cardinality::required(), None),
+        );
+        properties.insert(
+            interner.get_or_intern("tier"),
+            property(Cardinality::optional(), None),
+        );
+        properties.insert(
+            interner.get_or_intern("weight"),
+            property(Cardinality::custom(Some(1), Some(2), true), None),
+        );
+
+        let mut ship_properties = SpurMap::new();
+        ship_properties.insert(
+            interner.get_or_intern("ship_size"),
+            property(Cardinality::required(), Some(SeverityLevel::Information)),
+        );
+        block_scoped_type(properties, ship_properties, subtypes)
+    }
+
+    /// A block with the given properties, and `ship_properties` for the `ship` subtype
+    fn block_scoped_type(
+        properties: SpurMap<Property>,
+        ship_properties: SpurMap<Property>,
+        subtypes: &[&str],
+    ) -> ScopedType {
+        let interner = get_interner();
+        let mut subtype_properties = SpurMap::new();
+        subtype_properties.insert(interner.get_or_intern("ship"), ship_properties);
+
+        let block_type = BlockType {
+            type_name: None,
+            properties,
+            subtypes: SpurMap::new(),
+            subtype_properties,
+            subtype_pattern_properties: SpurMap::new(),
+            pattern_properties: Vec::new(),
+            localisation: None,
+            modifiers: None,
+            additional_flags: Vec::new(),
+        };
+
+        ScopedType::new_cwt_with_subtypes(
+            Arc::new(CwtType::Block(block_type)),
+            ScopeStack::default_with_root(interner.get_or_intern("country")),
+            subtypes
+                .iter()
+                .map(|subtype| interner.get_or_intern(subtype))
+                .collect::<HashSet<_>>(),
+            None,
+        )
+    }
+
+    fn validate(input: &str, subtypes: &[&str]) -> Vec<(String, DiagnosticSeverity)> {
+        validate_against(input, &test_block_type(subtypes))
+    }
+
+    fn validate_against(
+        input: &str,
+        expected_type: &ScopedType,
+    ) -> Vec<(String, DiagnosticSeverity)> {
+        let module = AstModuleCell::from_input(input.to_string());
+        let ast = module.borrow_dependent().as_ref().unwrap();
+        let AstEntityItem::Expression(expression) = &ast.items[0] else {
+            panic!("expected an expression");
+        };
+        let AstValue::Entity(entity) = &expression.value else {
+            panic!("expected an entity");
+        };
+
+        validate_cardinality(entity, expected_type, input)
+            .into_iter()
+            .map(|diagnostic| (diagnostic.message, diagnostic.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_missing_and_repeated_properties() {
+        let diagnostics = validate("foo = {\n\ttier = 1\n\ttier = 2\n\tweight = 1\n}\n", &[]);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(
+            diagnostics[0]
+                .0
+                .starts_with("'tier' can appear at most once")
+        );
+        assert_eq!(diagnostics[0].1, DiagnosticSeverity::ERROR);
+        assert!(
+            diagnostics[1]
+                .0
+                .starts_with("Missing required property 'cost'")
+        );
+    }
+
+    #[test]
+    fn test_soft_cardinality_is_a_warning() {
+        let diagnostics = validate("foo = { cost = 1 weight = 1 weight = 2 weight = 3 }", &[]);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]
+                .0
+                .starts_with("'weight' can appear at most twice")
+        );
+        assert_eq!(diagnostics[0].1, DiagnosticSeverity::WARNING);
+    }
+
+    #[test]
+    fn test_subtype_properties_only_required_for_matching_subtype() {
+        let input = "foo = { cost = 1 weight = 1 }";
+        assert!(validate(input, &[]).is_empty());
+
+        let diagnostics = validate(input, &["ship"]);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]
+                .0
+                .starts_with("Missing required property 'ship_size'")
+        );
+        assert_eq!(diagnostics[0].1, DiagnosticSeverity::INFORMATION);
+    }
+
+    #[test]
+    fn test_properties_without_cardinality_are_required_once() {
+        let interner = get_interner();
+        let mut properties = SpurMap::new();
+        properties.insert(
+            interner.get_or_intern("icon"),
+            Property {
+                options: CwtOptions::default(),
+                ..property(Cardinality::required(), None)
+            },
+        );
+        let expected_type = block_scoped_type(properties, SpurMap::new(), &[]);
+
+        assert!(validate_against("foo = { icon = 1 }", &expected_type).is_empty());
+        let diagnostics = validate_against("foo = { }", &expected_type);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]
+                .0
+                .starts_with("Missing required property 'icon'")
+        );
+        let diagnostics = validate_against("foo = { icon = 1 icon = 2 }", &expected_type);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]
+                .0
+                .starts_with("'icon' can appear at most once")
+        );
+    }
+
+    #[test]
+    fn test_subtype_property_without_cardinality_overrides_base() {
+        let interner = get_interner();
+        let mut properties = SpurMap::new();
+        properties.insert(
+            interner.get_or_intern("tier"),
+            property(Cardinality::optional(), None),
+        );
+        let mut ship_properties = SpurMap::new();
+        ship_properties.insert(
+            interner.get_or_intern("tier"),
+            Property {
+                options: CwtOptions::default(),
+                ..property(Cardinality::required(), None)
+            },
+        );
+
+        let expected_type = block_scoped_type(properties.clone(), ship_properties.clone(), &[]);
+        assert!(validate_against("foo = { }", &expected_type).is_empty());
+
+        let expected_type = block_scoped_type(properties, ship_properties, &["ship"]);
+        let diagnostics = validate_against("foo = { }", &expected_type);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]
+                .0
+                .starts_with("Missing required property 'tier'")
+        );
+    }
+
+    #[test]
+    fn test_inline_script_may_provide_properties() {
+        assert!(validate("foo = { inline_script = my_script }", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_missing_properties_fix() {
+        let input = "foo = {\n\ttier = 1\n}\n";
+        let module = AstModuleCell::from_input(input.to_string());
+        let ast = module.borrow_dependent().as_ref().unwrap();
+        let AstEntityItem::Expression(expression) = &ast.items[0] else {
+            panic!("expected an expression");
+        };
+        let AstValue::Entity(entity) = &expression.value else {
+            panic!("expected an entity");
+        };
+
+        // The softly required `weight` gets its own warning
+        let diagnostics = validate_cardinality(entity, &test_block_type(&[]), input);
+        assert_eq!(diagnostics.len(), 2);
+        let fix: DiagnosticFix =
+            serde_json::from_value(diagnostics[0].data.clone().unwrap()).unwrap();
+        assert_eq!(
+            fix,
+            DiagnosticFix::InsertProperties {
+                keys: vec!["cost".to_string()],
+                position: tower_lsp::lsp_types::Position::new(2, 0),
+                text: "\tcost = 0\n".to_string(),
+            }
+        );
+    }
+}
@@ -1,7 +1,8 @@
 use std::ops::Range;
 
 use lasso::Spur;
-use tower_lsp::lsp_types::{DiagnosticSeverity, NumberOrString};
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{DiagnosticSeverity, NumberOrString, Position};
 
 use crate::{handlers::diagnostics::util::span_to_lsp_range, interner::get_interner};
 
@@ -12,6 +13,30 @@ pub struct UnresolvedDiagnostic<'a> {
     pub content: &'a str,
     pub severity: DiagnosticSeverity,
     pub code: Option<NumberOrString>,
+    /// Serialized [`DiagnosticFix`], so code actions don't have to re-run validation
+    pub data: Option<serde_json::Value>,
+}
+
+/// A quick fix for a diagnostic, stored in the diagnostic's `data` field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "fix", rename_all = "snake_case")]
+pub enum DiagnosticFix {
+    /// Replace the diagnostic range with one of the suggestions, best match first
+    Replace { suggestions: Vec<String> },
+    /// Insert `text` at `position` to add the missing `keys` to a block
+    InsertProperties {
+        keys: Vec<String>,
+        position: Position,
+        text: String,
+    },
+}
+
+impl<'a> UnresolvedDiagnostic<'a> {
+    /// Attach a quick fix to this diagnostic
+    pub fn with_fix(mut self, fix: DiagnosticFix) -> Self {
+        self.data = serde_json::to_value(fix).ok();
+        self
+    }
 }
 
 impl<'a> From<UnresolvedDiagnostic<'a>> for tower_lsp::lsp_types::Diagnostic {
@@ -26,7 +51,7 @@ impl<'a> From<UnresolvedDiagnostic<'a>> for tower_lsp::lsp_types::Diagnostic {
             source: None,
             related_information: None,
             tags: None,
-            data: None,
+            data: diagnostic.data,
         }
     }
 }
@@ -43,6 +68,7 @@ pub fn create_type_mismatch_diagnostic<'a>(
         content,
         severity: DiagnosticSeverity::ERROR,
         code: Some(NumberOrString::String("type-mismatch".to_string())),
+        data: None,
     }
 }
 
@@ -58,6 +84,105 @@ pub fn create_value_mismatch_diagnostic<'a>(
         content,
         severity: DiagnosticSeverity::WARNING,
         code: Some(NumberOrString::String("value-mismatch".to_string())),
+        data: None,
+    }
+}
+
+/// Create a diagnostic for a trigger or effect used in a scope it doesn't support
+pub fn create_scope_mismatch_diagnostic<'a>(
+    span: Range<usize>,
+    message: &str,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    UnresolvedDiagnostic {
+        span,
+        message: message.to_string(),
+        content,
+        severity: DiagnosticSeverity::WARNING,
+        code: Some(NumberOrString::String("scope-mismatch".to_string())),
+        data: None,
+    }
+}
+
+/// Create a diagnostic for a localisation key that isn't defined
+pub fn create_missing_localisation_diagnostic<'a>(
+    span: Range<usize>,
+    message: &str,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    UnresolvedDiagnostic {
+        span,
+        message: message.to_string(),
+        content,
+        severity: DiagnosticSeverity::WARNING,
+        code: Some(NumberOrString::String("missing-localisation".to_string())),
+        data: None,
+    }
+}
+
+/// Create a diagnostic for malformed commands, icons or colour codes in localisation text
+pub fn create_localisation_syntax_diagnostic<'a>(
+    span: Range<usize>,
+    message: &str,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    UnresolvedDiagnostic {
+        span,
+        message: message.to_string(),
+        content,
+        severity: DiagnosticSeverity::WARNING,
+        code: Some(NumberOrString::String("localisation-syntax".to_string())),
+        data: None,
+    }
+}
+
+/// Create a diagnostic for a localisation key that is defined more than once
+pub fn create_duplicate_localisation_diagnostic<'a>(
+    span: Range<usize>,
+    message: &str,
+    severity: DiagnosticSeverity,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    UnresolvedDiagnostic {
+        span,
+        message: message.to_string(),
+        content,
+        severity,
+        code: Some(NumberOrString::String("duplicate-localisation".to_string())),
+        data: None,
+    }
+}
+
+/// Create a diagnostic for a definition that has the same name as another definition of its type
+pub fn create_duplicate_definition_diagnostic<'a>(
+    span: Range<usize>,
+    message: &str,
+    severity: DiagnosticSeverity,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    UnresolvedDiagnostic {
+        span,
+        message: message.to_string(),
+        content,
+        severity,
+        code: Some(NumberOrString::String("duplicate-definition".to_string())),
+        data: None,
+    }
+}
+
+/// Create a diagnostic for an English localisation key that other languages don't translate
+pub fn create_missing_translation_diagnostic<'a>(
+    span: Range<usize>,
+    message: &str,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    UnresolvedDiagnostic {
+        span,
+        message: message.to_string(),
+        content,
+        severity: DiagnosticSeverity::WARNING,
+        code: Some(NumberOrString::String("missing-translation".to_string())),
+        data: None,
     }
 }
 
@@ -78,6 +203,55 @@ pub fn create_unexpected_key_diagnostic<'a>(
         content,
         severity: DiagnosticSeverity::WARNING,
         code: Some(NumberOrString::String("unexpected-key".to_string())),
+        data: None,
+    }
+}
+
+/// Create a diagnostic for required properties that are missing from a block
+pub fn create_missing_properties_diagnostic<'a>(
+    span: Range<usize>,
+    keys: &[String],
+    type_name: &str,
+    severity: DiagnosticSeverity,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    let message = if keys.len() == 1 {
+        format!("Missing required property '{}' in {}", keys[0], type_name)
+    } else {
+        format!(
+            "Missing required properties in {}: {}",
+            type_name,
+            keys.iter()
+                .map(|key| format!("'{}'", key))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
+    UnresolvedDiagnostic {
+        span,
+        message,
+        content,
+        severity,
+        code: Some(NumberOrString::String("missing-property".to_string())),
+        data: None,
+    }
+}
+
+/// Create a diagnostic for a property that appears more or fewer times than its cardinality allows
+pub fn create_cardinality_diagnostic<'a>(
+    span: Range<usize>,
+    message: &str,
+    severity: DiagnosticSeverity,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    UnresolvedDiagnostic {
+        span,
+        message: message.to_string(),
+        content,
+        severity,
+        code: Some(NumberOrString::String("cardinality".to_string())),
+        data: None,
     }
 }
 
@@ -104,6 +278,7 @@ pub fn create_diagnostic_from_parse_error<'a>(
         message: message.clone(),
         content,
         severity: DiagnosticSeverity::ERROR,
-        code: None,
+        code: Some(NumberOrString::String("parse-error".to_string())),
+        data: None,
     }
 }
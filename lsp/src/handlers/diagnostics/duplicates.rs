@@ -0,0 +1,353 @@
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use cw_model::EntityMergeMode;
+use cw_parser::AstModule;
+use lasso::Spur;
+use tower_lsp::lsp_types::DiagnosticSeverity;
+
+use crate::base_game::game;
+use crate::handlers::cache::{
+    DefinitionIndex, DefinitionSource, LineIndex, TypeCache, find_module_definitions,
+};
+use crate::handlers::diagnostics::diagnostic::{
+    UnresolvedDiagnostic, create_duplicate_definition_diagnostic,
+};
+use crate::interner::get_interner;
+
+/// A definition that has the same name and type as others, placed in the game's load order
+#[derive(Debug, Clone)]
+struct Contender {
+    path: PathBuf,
+    /// The base game loads first, then mods in load order
+    source_rank: usize,
+    /// Position of the definition within its file
+    offset: usize,
+    /// Line of the definition within its file (0-based)
+    line: usize,
+}
+
+impl Contender {
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    }
+
+    /// Files in a folder load in order of their names, so `00_` prefixes load first and `zz_`
+    /// prefixes last, whichever mod they're from
+    fn load_order(&self, other: &Contender) -> Ordering {
+        self.file_name()
+            .cmp(&other.file_name())
+            .then(self.source_rank.cmp(&other.source_rank))
+            .then(self.offset.cmp(&other.offset))
+    }
+}
+
+/// Check for definitions that have the same name as another definition of their type, in this
+/// file, elsewhere in the mod, or in the base game, and explain which one the game uses.
+/// Overriding a base game definition is how mods work, so that is only reported as information.
+/// Duplicates of a `unique` type within a mod, and overrides in folders that can't be overridden
+/// one definition at a time, are errors.
+pub fn validate_duplicate_definitions<'a>(
+    module: &AstModule<'_>,
+    namespace: Spur,
+    path: &Path,
+    root_dir: &Path,
+    content: &'a str,
+) -> Vec<UnresolvedDiagnostic<'a>> {
+    let mut diagnostics = Vec::new();
+
+    if !DefinitionIndex::is_initialized() {
+        return diagnostics;
+    }
+    let Some(type_cache) = TypeCache::get() else {
+        return diagnostics;
+    };
+    let interner = get_interner();
+
+    let namespace = TypeCache::get_actual_namespace(namespace);
+    let merge_mode = game::get_entity_merge_mode(interner.resolve(&namespace));
+    if matches!(
+        merge_mode,
+        EntityMergeMode::Merge | EntityMergeMode::MergeShallow | EntityMergeMode::Duplicate
+    ) {
+        return diagnostics;
+    }
+
+    // Sources are ranked by load order, with this file's source added if it hasn't been indexed
+    let file_sources = DefinitionIndex::get_namespace_file_sources(namespace);
+    let this_source = file_sources
+        .iter()
+        .find(|(file, _)| file == path)
+        .map(|(_, source)| source.clone())
+        .unwrap_or_else(|| DefinitionSource::Mod(root_dir.to_path_buf()));
+    let mut sources: Vec<DefinitionSource> = Vec::new();
+    for source in file_sources
+        .iter()
+        .map(|(_, source)| source)
+        .chain(std::iter::once(&this_source))
+    {
+        if !sources.contains(source) {
+            sources.push(source.clone());
+        }
+    }
+    let rank = |source: &DefinitionSource| sources.iter().position(|s| s == source).unwrap_or(0);
+    let this_rank = rank(&this_source);
+
+    // A file with the same name in a later source replaces the whole file
+    let replaced = |contender: &Contender| {
+        file_sources.iter().any(|(file, source)| {
+            file != &contender.path
+                && rank(source) > contender.source_rank
+                && file.file_name() == contender.path.file_name()
+        })
+    };
+
+    let line_index = LineIndex::new(content);
+    let definitions = find_module_definitions(module, namespace);
+    for definition in &definitions {
+        let Some(type_name) = definition.type_name else {
+            continue;
+        };
+        let name = interner.resolve(&definition.name);
+        if name.contains('$') {
+            continue;
+        }
+
+        let this = Contender {
+            path: path.to_path_buf(),
+            source_rank: this_rank,
+            offset: definition.name_range.start,
+            line: line_index.line(definition.name_range.start),
+        };
+        let mut contenders = vec![this.clone()];
+        contenders.extend(
+            definitions
+                .iter()
+                .filter(|other| {
+                    other.name == definition.name
+                        && other.type_name == Some(type_name)
+                        && other.name_range != definition.name_range
+                })
+                .map(|other| Contender {
+                    offset: other.name_range.start,
+                    line: line_index.line(other.name_range.start),
+                    ..this.clone()
+                }),
+        );
+        contenders.extend(
+            DefinitionIndex::get_definitions(namespace, definition.name)
+                .into_iter()
+                .filter(|location| location.type_name == Some(type_name) && location.path != path)
+                .map(|location| Contender {
+                    path: location.path,
+                    source_rank: rank(&location.source),
+                    offset: location.span.start.offset,
+                    line: location.span.start.line.saturating_sub(1),
+                })
+                .filter(|contender| !replaced(contender)),
+        );
+        if contenders.len() < 2 {
+            continue;
+        }
+
+        let type_display = interner.resolve(&type_name);
+        let unique = type_cache
+            .get_cwt_analyzer()
+            .get_type(type_name)
+            .is_some_and(|type_def| type_def.options.unique);
+
+        let (severity, message) =
+            conflict_message(&this, &contenders, merge_mode, name, type_display, unique);
+        diagnostics.push(create_duplicate_definition_diagnostic(
+            definition.name_range.clone(),
+            &message,
+            severity,
+            content,
+        ));
+    }
+
+    diagnostics
+}
+
+/// The definition the game uses out of several with the same name
+fn winner(contenders: &[Contender], merge_mode: EntityMergeMode) -> &Contender {
+    let mut ordered: Vec<&Contender> = contenders.iter().collect();
+    ordered.sort_by(|a, b| a.load_order(b));
+
+    match merge_mode {
+        EntityMergeMode::FIOS | EntityMergeMode::FIOSKeyed(_) | EntityMergeMode::No => ordered[0],
+        _ => ordered[ordered.len() - 1],
+    }
+}
+
+/// Explain how a definition relates to the others with its name, and how serious that is
+fn conflict_message(
+    this: &Contender,
+    contenders: &[Contender],
+    merge_mode: EntityMergeMode,
+    name: &str,
+    type_name: &str,
+    unique: bool,
+) -> (DiagnosticSeverity, String) {
+    let location =
+        |contender: &Contender| format!("{}:{}", contender.path.display(), contender.line + 1);
+    let others: Vec<&Contender> = contenders
+        .iter()
+        .filter(|other| other.path != this.path || other.offset != this.offset)
+        .collect();
+    let same_source = others
+        .iter()
+        .any(|other| other.source_rank == this.source_rank);
+
+    // Overriding the base game is still fine for unique types
+    if unique && same_source {
+        return (
+            DiagnosticSeverity::ERROR,
+            format!(
+                "Duplicate {} '{}', it is also defined in {} and {} definitions must be unique",
+                type_name,
+                name,
+                location(others[0]),
+                type_name
+            ),
+        );
+    }
+
+    if merge_mode == EntityMergeMode::No {
+        return (
+            DiagnosticSeverity::ERROR,
+            format!(
+                "{} '{}' is also defined in {}, and {} can't be overridden one at a time. Override the whole file by giving it the same name instead",
+                type_name,
+                name,
+                location(others[0]),
+                type_name
+            ),
+        );
+    }
+
+    let winner = winner(contenders, merge_mode);
+    let first_wins = matches!(
+        merge_mode,
+        EntityMergeMode::FIOS | EntityMergeMode::FIOSKeyed(_)
+    );
+    let rule = match merge_mode {
+        EntityMergeMode::Unknown => "the last definition probably wins",
+        _ if first_wins => "the first definition wins",
+        _ => "the last definition wins",
+    };
+
+    if winner.path == this.path && winner.offset == this.offset {
+        let severity = if same_source {
+            DiagnosticSeverity::WARNING
+        } else {
+            DiagnosticSeverity::INFORMATION
+        };
+        let overridden: Vec<String> = others.iter().map(|other| location(other)).collect();
+        return (
+            severity,
+            format!(
+                "{} '{}' overrides the definition in {}, {}",
+                type_name,
+                name,
+                overridden.join(", "),
+                rule
+            ),
+        );
+    }
+
+    let hint = if winner.path == this.path {
+        String::new()
+    } else if first_wins {
+        format!(
+            ", rename this file to load before '{}', e.g. with a 00_ prefix",
+            winner.file_name()
+        )
+    } else {
+        format!(
+            ", rename this file to load after '{}', e.g. with a zz_ prefix",
+            winner.file_name()
+        )
+    };
+    (
+        DiagnosticSeverity::WARNING,
+        format!(
+            "{} '{}' is shadowed by the definition in {}, files load by name and {}{}",
+            type_name,
+            name,
+            location(winner),
+            rule,
+            hint
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contender(path: &str, source_rank: usize, offset: usize) -> Contender {
+        Contender {
+            path: path.into(),
+            source_rank,
+            offset,
+            line: 0,
+        }
+    }
+
+    #[test]
+    fn test_load_order_winner() {
+        let base = contender("/game/common/armies/00_armies.txt", 0, 10);
+        let early = contender("/mod/common/armies/00_my_armies.txt", 1, 0);
+        let late = contender("/mod/common/armies/zz_my_armies.txt", 1, 0);
+
+        let contenders = [base.clone(), early.clone()];
+        assert_eq!(
+            winner(&contenders, EntityMergeMode::LIOS).path,
+            early.path,
+            "files load by name, then by source"
+        );
+        assert_eq!(winner(&contenders, EntityMergeMode::FIOS).path, base.path);
+
+        let contenders = [late.clone(), base.clone()];
+        assert_eq!(winner(&contenders, EntityMergeMode::LIOS).path, late.path);
+        assert_eq!(winner(&contenders, EntityMergeMode::FIOS).path, base.path);
+
+        // Within a file, definitions load top to bottom
+        let second = contender("/game/common/armies/00_armies.txt", 0, 50);
+        let contenders = [second.clone(), base.clone()];
+        assert_eq!(winner(&contenders, EntityMergeMode::LIOS).offset, 50);
+        assert_eq!(winner(&contenders, EntityMergeMode::FIOS).offset, 10);
+    }
+
+    #[test]
+    fn test_shadowed_message() {
+        let base = contender("/game/common/static_modifiers/00_static.txt", 0, 0);
+        let this = contender("/mod/common/static_modifiers/my_static.txt", 1, 0);
+
+        let (severity, message) = conflict_message(
+            &this,
+            &[base.clone(), this.clone()],
+            EntityMergeMode::FIOS,
+            "my_modifier",
+            "static_modifier",
+            false,
+        );
+        assert_eq!(severity, DiagnosticSeverity::WARNING);
+        assert!(message.contains("shadowed"));
+        assert!(message.contains("00_ prefix"));
+
+        let (severity, _) = conflict_message(
+            &this,
+            &[base, this.clone()],
+            EntityMergeMode::LIOS,
+            "my_modifier",
+            "static_modifier",
+            false,
+        );
+        assert_eq!(severity, DiagnosticSeverity::INFORMATION);
+    }
+}
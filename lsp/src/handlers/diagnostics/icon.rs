@@ -0,0 +1,159 @@
+use std::ops::Range;
+
+use cw_parser::AstModule;
+
+use crate::handlers::cache::{FileIndex, SpriteCache, find_module_sprites, is_sprite_name};
+use crate::handlers::diagnostics::diagnostic::{
+    UnresolvedDiagnostic, create_type_mismatch_diagnostic,
+};
+
+/// The texture formats an icon can be stored as
+const ICON_EXTENSIONS: &[&str] = &["dds", "png"];
+
+/// Check that an icon exists. `GFX_` values are sprite names from `interface/*.gfx` files, other
+/// values are file names under `path` (`icon[gfx/interface/icons/traits]`) or, without a path,
+/// file paths. Mod files are in the file index alongside the base game, so icons a mod adds or
+/// overrides are found too.
+pub fn validate_icon<'a>(
+    value: &str,
+    path: Option<&str>,
+    span: Range<usize>,
+    content: &'a str,
+) -> Option<UnresolvedDiagnostic<'a>> {
+    if value.is_empty() || value.starts_with('@') || value.contains('$') {
+        return None;
+    }
+
+    if is_sprite_name(value) {
+        return validate_sprite(value, span, content);
+    }
+
+    let candidates = icon_file_candidates(value, path);
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let Some(file_index) = FileIndex::get() else {
+        return Some(create_type_mismatch_diagnostic(
+            span,
+            "File index not initialized, cannot validate icon path",
+            content,
+        ));
+    };
+    let file_index = file_index.read().unwrap();
+
+    if candidates
+        .iter()
+        .any(|candidate| file_index.file_exists(candidate))
+    {
+        return None;
+    }
+
+    let message = match path {
+        Some(path) => format!(
+            "Icon '{}' does not exist in '{}' (expected {})",
+            value,
+            path,
+            candidates.join(" or ")
+        ),
+        None => format!("Icon file '{}' does not exist", value),
+    };
+    Some(create_type_mismatch_diagnostic(span, &message, content))
+}
+
+/// Check that a `GFX_` sprite is defined in an `interface/*.gfx` file
+fn validate_sprite<'a>(
+    name: &str,
+    span: Range<usize>,
+    content: &'a str,
+) -> Option<UnresolvedDiagnostic<'a>> {
+    if !SpriteCache::is_initialized() || !SpriteCache::has_sprites() || SpriteCache::contains(name)
+    {
+        return None;
+    }
+
+    Some(create_type_mismatch_diagnostic(
+        span,
+        &format!(
+            "Sprite '{}' is not defined by any spriteType in interface/*.gfx",
+            name
+        ),
+        content,
+    ))
+}
+
+/// Check that the texture of every sprite in a `.gfx` file exists
+pub fn validate_sprite_textures<'a>(
+    module: &AstModule<'_>,
+    content: &'a str,
+) -> Vec<UnresolvedDiagnostic<'a>> {
+    let Some(file_index) = FileIndex::get() else {
+        return Vec::new();
+    };
+    let file_index = file_index.read().unwrap();
+
+    find_module_sprites(module)
+        .into_iter()
+        .filter_map(|sprite| {
+            let (texture_file, range) = sprite.texture_file?;
+            if file_index.file_exists(texture_file) {
+                return None;
+            }
+
+            Some(create_type_mismatch_diagnostic(
+                range,
+                &format!(
+                    "Texture '{}' of sprite '{}' does not exist",
+                    texture_file, sprite.name
+                ),
+                content,
+            ))
+        })
+        .collect()
+}
+
+/// The files an icon value can refer to. Values that already have an extension are used as is.
+/// Without a path, only values that look like file paths are checked.
+fn icon_file_candidates(value: &str, path: Option<&str>) -> Vec<String> {
+    let file = value.replace('\\', "/");
+    let full_path = match path.map(|path| path.trim_end_matches('/')) {
+        Some("") | None if !file.contains('/') => return Vec::new(),
+        Some("") | None => file,
+        Some(path) => format!("{}/{}", path, file),
+    };
+
+    let has_extension = ICON_EXTENSIONS.iter().any(|extension| {
+        full_path
+            .rsplit_once('.')
+            .is_some_and(|(_, existing)| existing.eq_ignore_ascii_case(extension))
+    });
+    if has_extension {
+        return vec![full_path];
+    }
+
+    ICON_EXTENSIONS
+        .iter()
+        .map(|extension| format!("{}.{}", full_path, extension))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icon_file_candidates() {
+        assert_eq!(
+            icon_file_candidates("trait_strong", Some("gfx/interface/icons/traits/")),
+            vec![
+                "gfx/interface/icons/traits/trait_strong.dds",
+                "gfx/interface/icons/traits/trait_strong.png"
+            ]
+        );
+        assert_eq!(
+            icon_file_candidates("gfx/interface/icons/foo.DDS", None),
+            vec!["gfx/interface/icons/foo.DDS"]
+        );
+        assert!(icon_file_candidates("trait_strong", None).is_empty());
+    }
+}
@@ -0,0 +1,485 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+use cw_model::{LocalisationEntry, is_replace_path, language_from_filename};
+use cw_parser::localisation::{AstLocalisationEntry, AstLocalisationFile};
+use cw_parser::{AstModule, CwParseError};
+use lasso::Spur;
+use tower_lsp::lsp_types::DiagnosticSeverity;
+
+use crate::base_game::game;
+use crate::handlers::cache::{
+    LineIndex, LocalisationCache, TypeCache, definition_localisation, find_module_definitions,
+};
+use crate::handlers::diagnostics::diagnostic::{
+    UnresolvedDiagnostic, create_diagnostic_from_parse_error,
+    create_duplicate_localisation_diagnostic, create_localisation_syntax_diagnostic,
+    create_missing_localisation_diagnostic, create_missing_translation_diagnostic,
+};
+use crate::interner::get_interner;
+
+/// The language other languages are compared against for missing translations
+const REFERENCE_LANGUAGE: &str = "english";
+
+/// Check that a localisation key used as a value is defined for every configured language.
+/// Inline localisation can also be literal text, which is left alone.
+pub fn validate_localisation_key<'a>(
+    value: &str,
+    span: Range<usize>,
+    inline: bool,
+    content: &'a str,
+) -> Option<UnresolvedDiagnostic<'a>> {
+    if !LocalisationCache::is_initialized()
+        || value.is_empty()
+        || value.starts_with('@')
+        || value.contains('$')
+        || (inline && is_literal_text(value))
+    {
+        return None;
+    }
+
+    let missing = LocalisationCache::missing_languages(get_interner().get_or_intern(value));
+    if missing.is_empty() {
+        return None;
+    }
+
+    let message = format!(
+        "Localisation key '{}' is not defined{}",
+        value,
+        languages_suffix(&missing)
+    );
+    Some(create_missing_localisation_diagnostic(
+        span, &message, content,
+    ))
+}
+
+/// Check that every definition in a file has the localisation its type requires, e.g. the name
+/// and description of a technology
+pub fn validate_localisation_requirements<'a>(
+    module: &AstModule<'_>,
+    namespace: Spur,
+    content: &'a str,
+) -> Vec<UnresolvedDiagnostic<'a>> {
+    let mut diagnostics = Vec::new();
+
+    if !LocalisationCache::is_initialized() {
+        return diagnostics;
+    }
+    let Some(type_cache) = TypeCache::get() else {
+        return diagnostics;
+    };
+    let interner = get_interner();
+
+    for definition in find_module_definitions(module, namespace) {
+        let Some(type_name) = definition.type_name else {
+            continue;
+        };
+        let Some(type_def) = type_cache.get_cwt_analyzer().get_type(type_name) else {
+            continue;
+        };
+        let name = interner.resolve(&definition.name);
+        if name.contains('$') {
+            continue;
+        }
+
+        for localisation in definition_localisation(type_def, name, definition.entity) {
+            if !localisation.required {
+                continue;
+            }
+
+            let missing =
+                LocalisationCache::missing_languages(interner.get_or_intern(&localisation.key));
+            if missing.is_empty() {
+                continue;
+            }
+
+            let message = format!(
+                "Missing required localisation '{}' ({}) for {} '{}'{}",
+                localisation.key,
+                interner.resolve(&localisation.name),
+                interner.resolve(&type_name),
+                name,
+                languages_suffix(&missing)
+            );
+            diagnostics.push(create_missing_localisation_diagnostic(
+                definition.name_range.clone(),
+                &message,
+                content,
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// Check a localisation file: lines that can't be parsed, malformed text, keys that are defined
+/// more than once, and English keys that other languages don't translate
+pub fn validate_localisation_file<'a>(
+    path: &Path,
+    content: &'a str,
+) -> Vec<UnresolvedDiagnostic<'a>> {
+    let file = AstLocalisationFile::from_input(content);
+    let mut diagnostics: Vec<UnresolvedDiagnostic<'a>> = file
+        .errors
+        .iter()
+        .map(|error| {
+            create_diagnostic_from_parse_error(&CwParseError::Parse(error.clone()), content)
+        })
+        .collect();
+
+    let colour_codes = game::get_text_colour_codes();
+    for entry in &file.entries {
+        for (span, message) in validate_text(entry.value, entry.value_span.start, colour_codes) {
+            diagnostics.push(create_localisation_syntax_diagnostic(
+                span, &message, content,
+            ));
+        }
+    }
+
+    let Some(language) = file
+        .language()
+        .map(str::to_string)
+        .or_else(|| language_from_filename(path))
+    else {
+        return diagnostics;
+    };
+
+    let interner = get_interner();
+    let line_index = LineIndex::new(content);
+    let mut first_lines: HashMap<Spur, usize> = HashMap::new();
+    let mut unique_entries = Vec::new();
+    for entry in &file.entries {
+        let key = interner.get_or_intern(entry.key);
+        let line = line_index.line(entry.key_span.start);
+        match first_lines.get(&key) {
+            Some(first_line) => diagnostics.push(create_duplicate_localisation_diagnostic(
+                entry.key_span.clone(),
+                &format!(
+                    "Duplicate key '{}', the definition on line {} is used",
+                    entry.key,
+                    first_line + 1
+                ),
+                DiagnosticSeverity::WARNING,
+                content,
+            )),
+            None => {
+                first_lines.insert(key, line);
+                unique_entries.push((key, entry));
+            }
+        }
+    }
+
+    // Other files are only known once the localisation has been loaded
+    if !LocalisationCache::is_initialized() {
+        return diagnostics;
+    }
+
+    let index = LocalisationCache::get();
+
+    for (key, entry) in &unique_entries {
+        if let Some((severity, message)) =
+            override_message(entry, index.get_all(&language, *key), path)
+        {
+            diagnostics.push(create_duplicate_localisation_diagnostic(
+                entry.key_span.clone(),
+                &message,
+                severity,
+                content,
+            ));
+        }
+    }
+
+    if language == REFERENCE_LANGUAGE {
+        let mut other_languages: Vec<&str> = index
+            .languages()
+            .filter(|other| *other != REFERENCE_LANGUAGE)
+            .collect();
+        other_languages.sort();
+
+        for (key, entry) in &unique_entries {
+            let missing: Vec<String> = other_languages
+                .iter()
+                .filter(|other| index.get_all(other, *key).is_empty())
+                .map(|other| format!("l_{}", other))
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+
+            diagnostics.push(create_missing_translation_diagnostic(
+                entry.key_span.clone(),
+                &format!(
+                    "'{}' is not translated to {}",
+                    entry.key,
+                    missing.join(", ")
+                ),
+                content,
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// Explain how a key defined in this file relates to its definitions in other files. The game
+/// uses the first definition, unless there are definitions in a `replace` folder, in which case
+/// the last of those wins.
+fn override_message(
+    entry: &AstLocalisationEntry<'_>,
+    definitions: &[LocalisationEntry],
+    path: &Path,
+) -> Option<(DiagnosticSeverity, String)> {
+    let others: Vec<&LocalisationEntry> = definitions
+        .iter()
+        .filter(|definition| definition.path != path)
+        .collect();
+    if others.is_empty() {
+        return None;
+    }
+
+    // Files that haven't been saved yet aren't in the index, and load after everything else
+    let this = definitions
+        .iter()
+        .find(|definition| definition.path == path);
+    let this_replace = this.map_or_else(
+        || is_replace_path(path, game::get_localisation_folder()),
+        |this| this.replace,
+    );
+
+    let winner = if this.is_none() && this_replace {
+        None
+    } else {
+        definitions
+            .iter()
+            .rev()
+            .find(|definition| definition.replace)
+            .or_else(|| definitions.first())
+            .filter(|winner| winner.path != path)
+    };
+
+    let Some(winner) = winner else {
+        // Overriding from a `replace` folder is what it's there for
+        if this_replace {
+            return None;
+        }
+
+        let ignored = others[0];
+        return Some((
+            DiagnosticSeverity::INFORMATION,
+            format!(
+                "'{}' is also defined in {}:{}, which is ignored because this file loads first",
+                entry.key,
+                ignored.path.display(),
+                ignored.line + 1
+            ),
+        ));
+    };
+
+    let hint = if this_replace || winner.replace {
+        String::new()
+    } else {
+        format!(
+            ", move this definition to a {}/replace folder to override it",
+            game::get_localisation_folder()
+        )
+    };
+    Some((
+        DiagnosticSeverity::WARNING,
+        format!(
+            "'{}' is ignored because it is already defined in {}:{}{}",
+            entry.key,
+            winner.path.display(),
+            winner.line + 1,
+            hint
+        ),
+    ))
+}
+
+/// Check the commands, variables, icons and colour codes in the raw text of a localisation value.
+/// `offset` is the position of the text in the file.
+fn validate_text(text: &str, offset: usize, colour_codes: &[char]) -> Vec<(Range<usize>, String)> {
+    let mut problems = Vec::new();
+    let span = |start: usize, end: usize| offset + start..offset + end;
+
+    let mut open_command: Option<usize> = None;
+    let mut open_variable: Option<usize> = None;
+    let mut open_icon: Option<usize> = None;
+
+    let mut chars = text.char_indices();
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            '[' => {
+                if let Some(start) = open_command.replace(index) {
+                    problems.push((span(start, start + 1), "Unclosed '[' command".to_string()));
+                }
+            }
+            ']' => match open_command.take() {
+                Some(start) if text[start + 1..index].trim().is_empty() => {
+                    problems.push((span(start, index + 1), "Empty '[]' command".to_string()));
+                }
+                Some(_) => {}
+                None => problems.push((
+                    span(index, index + 1),
+                    "']' without a matching '['".to_string(),
+                )),
+            },
+            '$' => match open_variable.take() {
+                Some(start) if text[start + 1..index].contains(char::is_whitespace) => {
+                    problems.push((
+                        span(start, index + 1),
+                        format!(
+                            "Invalid variable '{}', variable names can't contain spaces",
+                            &text[start..index + 1]
+                        ),
+                    ));
+                }
+                Some(_) => {}
+                None => open_variable = Some(index),
+            },
+            '£' => match open_icon.take() {
+                Some(start) if text[start + 2..index].contains(char::is_whitespace) => {
+                    problems.push((
+                        span(start, index + 2),
+                        format!(
+                            "Invalid icon '{}', icon names can't contain spaces",
+                            &text[start..index + 2]
+                        ),
+                    ));
+                }
+                Some(_) => {}
+                None => open_icon = Some(index),
+            },
+            '§' if !colour_codes.is_empty() => match chars.next() {
+                Some((_, code)) if colour_codes.contains(&code) => {}
+                Some((code_index, code)) => problems.push((
+                    span(index, code_index + code.len_utf8()),
+                    format!("Unknown colour code '§{}'", code),
+                )),
+                None => problems.push((
+                    span(index, index + ch.len_utf8()),
+                    "Missing colour code after '§'".to_string(),
+                )),
+            },
+            _ => {}
+        }
+    }
+
+    if let Some(start) = open_command {
+        problems.push((span(start, start + 1), "Unclosed '[' command".to_string()));
+    }
+    if let Some(start) = open_variable {
+        problems.push((span(start, start + 1), "Unclosed '$' variable".to_string()));
+    }
+    if let Some(start) = open_icon {
+        problems.push((span(start, start + 2), "Unclosed '£' icon".to_string()));
+    }
+
+    problems
+}
+
+/// Whether an inline localisation value is text to show as is rather than a key
+fn is_literal_text(value: &str) -> bool {
+    value.contains(|ch: char| ch.is_whitespace() || ch == '[')
+}
+
+/// Names the languages a key is missing from, unless it's missing from all of them
+fn languages_suffix(missing: &[&str]) -> String {
+    if missing.len() == LocalisationCache::languages().len() {
+        return String::new();
+    }
+
+    let languages: Vec<String> = missing
+        .iter()
+        .map(|language| format!("l_{}", language))
+        .collect();
+    format!(" for {}", languages.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(text: &str) -> Vec<(Range<usize>, String)> {
+        validate_text(text, 0, &['!', 'G', 'Y'])
+    }
+
+    #[test]
+    fn test_valid_text() {
+        assert!(problems("§GGreen§! [Root.GetName] costs $COST|Y$ £energy£ \\[literal").is_empty());
+    }
+
+    #[test]
+    fn test_malformed_commands() {
+        assert_eq!(
+            problems("$A B$ [] ] [open"),
+            vec![
+                (
+                    0..5,
+                    "Invalid variable '$A B$', variable names can't contain spaces".to_string()
+                ),
+                (6..8, "Empty '[]' command".to_string()),
+                (9..10, "']' without a matching '['".to_string()),
+                (11..12, "Unclosed '[' command".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_colour_code() {
+        let found = problems("§Xoops§! £unclosed");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0], (0..3, "Unknown colour code '§X'".to_string()));
+        assert_eq!(found[1].1, "Unclosed '£' icon");
+    }
+
+    #[test]
+    fn test_override_order() {
+        let file = AstLocalisationFile::from_input("l_english:\n KEY:0 \"mine\"\n");
+        let entry = &file.entries[0];
+        let definition = |path: &str, replace: bool| LocalisationEntry {
+            text: String::new(),
+            path: path.into(),
+            span: 0..3,
+            line: 1,
+            replace,
+        };
+        let base = definition("/game/localisation/english/a_l_english.yml", false);
+        let this = Path::new("/mod/localisation/english/b_l_english.yml");
+
+        // The base game loads first, so a plain mod definition is ignored
+        let (severity, message) = override_message(
+            entry,
+            &[base.clone(), definition(this.to_str().unwrap(), false)],
+            this,
+        )
+        .unwrap();
+        assert_eq!(severity, DiagnosticSeverity::WARNING);
+        assert!(message.contains("localisation/replace"));
+
+        // Unless it's in a replace folder
+        let this = Path::new("/mod/localisation/replace/b_l_english.yml");
+        assert_eq!(
+            override_message(entry, std::slice::from_ref(&base), this),
+            None
+        );
+        assert_eq!(
+            override_message(
+                entry,
+                &[base, definition(this.to_str().unwrap(), true)],
+                this
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_inline_literal_text() {
+        assert!(is_literal_text("Some literal text"));
+        assert!(is_literal_text("[Root.GetName]"));
+        assert!(!is_literal_text("tech_lasers_1"));
+    }
+}
@@ -0,0 +1,154 @@
+use std::ops::Range;
+
+use cw_model::types::{CwtAnalyzer, PatternType};
+use lasso::Spur;
+
+use crate::handlers::cache::TypeCache;
+use crate::handlers::diagnostics::diagnostic::{
+    UnresolvedDiagnostic, create_scope_mismatch_diagnostic,
+};
+use crate::handlers::diagnostics::scope_validation::is_scope_allowed;
+use crate::handlers::modifiers::get_modifier_categories;
+use crate::handlers::scoped_type::{CwtTypeOrSpecialRef, ScopedType};
+use crate::handlers::settings::Settings;
+use crate::handlers::utils::contains_scripted_argument;
+use crate::interner::get_interner;
+
+/// Validate that a modifier in a modifier block like `modifier = { }` applies to the scope of the
+/// block, e.g. that a ship component doesn't use a `Pops` modifier. The scopes come from the
+/// `modifier_categories` of the config. `expected_type` is the resolved type of the block
+/// containing the key.
+pub fn validate_modifier_category<'a>(
+    key: Spur,
+    expected_type: &ScopedType,
+    span: Range<usize>,
+    content: &'a str,
+) -> Option<UnresolvedDiagnostic<'a>> {
+    if contains_scripted_argument(key) || !TypeCache::is_initialized() {
+        return None;
+    }
+
+    let CwtTypeOrSpecialRef::Block(block) = expected_type.cwt_type_for_matching() else {
+        return None;
+    };
+
+    // Literal properties aren't modifiers, even when a modifier of the same name exists
+    if block.properties.contains_key(&key) {
+        return None;
+    }
+
+    let interner = get_interner();
+    let modifier = interner.get_or_intern("modifier");
+    let accepts_modifiers = block
+        .pattern_properties
+        .iter()
+        .chain(
+            expected_type
+                .subtypes()
+                .iter()
+                .filter_map(|subtype| block.subtype_pattern_properties.get(subtype))
+                .flatten(),
+        )
+        .any(|pattern| match pattern.pattern_type {
+            PatternType::AliasName { category } => category == modifier,
+            PatternType::Type { key } => key == modifier,
+            PatternType::Enum { .. } => false,
+        });
+    if !accepts_modifiers {
+        return None;
+    }
+
+    let current_scope = expected_type.scope_stack().current_scope().scope_type;
+    if interner.resolve(&current_scope) == "any" {
+        return None;
+    }
+    if interner.resolve(&current_scope) == "unknown" && !Settings::global().report_unknown_scopes {
+        return None;
+    }
+
+    let categories = get_modifier_categories(key)?;
+    let analyzer = TypeCache::get().unwrap().get_cwt_analyzer();
+    let supported_scopes = category_scopes(&categories, analyzer)?;
+    if is_scope_allowed(&supported_scopes, current_scope, analyzer) {
+        return None;
+    }
+
+    let join = |spurs: &[Spur]| {
+        spurs
+            .iter()
+            .map(|spur| interner.resolve(spur).to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    Some(create_scope_mismatch_diagnostic(
+        span,
+        &format!(
+            "Modifier '{}' ({}) doesn't apply in {} scope, its categories apply to: {}",
+            interner.resolve(&key),
+            join(&categories),
+            interner.resolve(&current_scope),
+            join(&supported_scopes)
+        ),
+        content,
+    ))
+}
+
+/// The scopes the categories of a modifier apply to together, or None if any category isn't in
+/// the config and so could apply anywhere
+fn category_scopes(categories: &[Spur], analyzer: &CwtAnalyzer) -> Option<Vec<Spur>> {
+    let mut scopes = Vec::new();
+    for category in categories {
+        let definition = analyzer.get_modifier_category(*category)?;
+        for scope in &definition.supported_scopes {
+            if !scopes.contains(scope) {
+                scopes.push(*scope);
+            }
+        }
+    }
+
+    if scopes.is_empty() {
+        None
+    } else {
+        Some(scopes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cw_model::ModifierCategoryDefinition;
+
+    #[test]
+    fn test_category_scopes() {
+        let interner = get_interner();
+        let pops = interner.get_or_intern("Pops");
+        let ships = interner.get_or_intern("Military Ships");
+        let scope = |name: &str| interner.get_or_intern(name);
+
+        let mut analyzer = CwtAnalyzer::new();
+        let categories = &mut analyzer.get_analysis_data_mut().modifier_categories;
+        categories.insert(
+            pops,
+            ModifierCategoryDefinition::new(pops, vec![scope("pop"), scope("planet")]),
+        );
+        categories.insert(
+            ships,
+            ModifierCategoryDefinition::new(ships, vec![scope("ship"), scope("planet")]),
+        );
+
+        let scopes = category_scopes(&[pops], &analyzer).unwrap();
+        assert!(is_scope_allowed(&scopes, scope("planet"), &analyzer));
+        assert!(!is_scope_allowed(&scopes, scope("ship"), &analyzer));
+
+        assert_eq!(
+            category_scopes(&[pops, ships], &analyzer),
+            Some(vec![scope("pop"), scope("planet"), scope("ship")])
+        );
+
+        // Categories missing from the config don't restrict anything
+        assert_eq!(
+            category_scopes(&[pops, scope("Habitability")], &analyzer),
+            None
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use std::ops::Range;
+
+use crate::handlers::cache::{FullAnalysis, LocalisationCache};
+use crate::handlers::diagnostics::diagnostic::{
+    UnresolvedDiagnostic, create_type_mismatch_diagnostic,
+};
+use crate::interner::get_interner;
+
+/// A `<key>` in a name format, and where it is within the format
+#[derive(Debug, PartialEq)]
+struct FormatReference<'s> {
+    key: &'s str,
+    span: Range<usize>,
+}
+
+/// Check a name format like `"{<imperial_mil> [This.GetSpeciesName]}"`: braces, `<key>`
+/// references and `[...]` commands have to be well formed, and every referenced key has to be
+/// the key of a parts list of the format's `family` (like `empire_name_parts_list`) or be
+/// localised. `offset` is the position of the text in the file.
+pub fn validate_stellaris_name_format<'a>(
+    family: &str,
+    text: &str,
+    offset: usize,
+    content: &'a str,
+) -> Vec<UnresolvedDiagnostic<'a>> {
+    let span = |range: Range<usize>| offset + range.start..offset + range.end;
+
+    if text.contains('$') {
+        return Vec::new();
+    }
+
+    let (references, problems) = parse_name_format(text);
+    let mut diagnostics: Vec<UnresolvedDiagnostic<'a>> = problems
+        .into_iter()
+        .map(|(range, message)| create_type_mismatch_diagnostic(span(range), &message, content))
+        .collect();
+
+    if references.is_empty() {
+        return diagnostics;
+    }
+
+    // The parts list keys are only known once the full analysis has run. Localisation can only
+    // add to the accepted keys, so it is no reason to report keys on its own.
+    if !FullAnalysis::is_initialized() {
+        return diagnostics;
+    }
+
+    let interner = get_interner();
+    let family_key = interner.get_or_intern(family);
+    let localisation = LocalisationCache::is_initialized().then(LocalisationCache::get);
+
+    for reference in references {
+        let key = interner.get_or_intern(reference.key);
+        if FullAnalysis::has_name_format_key(family_key, key) == Some(true)
+            || localisation
+                .as_ref()
+                .is_some_and(|localisation| localisation.contains_key(key))
+        {
+            continue;
+        }
+
+        diagnostics.push(create_type_mismatch_diagnostic(
+            span(reference.span),
+            &format!(
+                "Unknown name format key '{}', it is not the key of a {}_name_parts_list or localised",
+                reference.key, family
+            ),
+            content,
+        ));
+    }
+
+    diagnostics
+}
+
+/// Split a name format into its `<key>` references and the problems with its structure, with
+/// spans relative to the text
+fn parse_name_format(text: &str) -> (Vec<FormatReference<'_>>, Vec<(Range<usize>, String)>) {
+    let mut references = Vec::new();
+    let mut problems = Vec::new();
+
+    if !text.starts_with('{') || !text.ends_with('}') {
+        problems.push((
+            0..text.len(),
+            "Name format must start and end with curly braces".to_string(),
+        ));
+        return (references, problems);
+    }
+
+    let mut open_braces: Vec<usize> = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '{' => open_braces.push(index),
+            '}' => {
+                if open_braces.pop().is_none() {
+                    problems.push((index..index + 1, "'}' without a matching '{'".to_string()));
+                } else if open_braces.is_empty() && index + 1 < text.len() {
+                    problems.push((
+                        index + 1..text.len(),
+                        "Text after the closing '}' of the name format".to_string(),
+                    ));
+                    break;
+                }
+            }
+            '<' | '[' => {
+                let close = if ch == '<' { '>' } else { ']' };
+                let end = text[index + 1..]
+                    .find(|other| other == close || "{}<>[]".contains(other))
+                    .map(|end| index + 1 + end)
+                    .filter(|end| text[*end..].starts_with(close));
+                let Some(end) = end else {
+                    let kind = if ch == '<' { "reference" } else { "command" };
+                    problems.push((index..index + 1, format!("Unclosed '{}' {}", ch, kind)));
+                    continue;
+                };
+
+                let inner = &text[index + 1..end];
+                let span = index..end + 1;
+                if ch == '<' {
+                    if let Some(problem) = reference_problem(inner) {
+                        problems.push((span, problem));
+                    } else {
+                        references.push(FormatReference {
+                            key: inner,
+                            span: index + 1..end,
+                        });
+                    }
+                } else if let Some(problem) = command_problem(inner) {
+                    problems.push((span, problem));
+                }
+
+                while chars.next_if(|(next, _)| *next <= end).is_some() {}
+            }
+            '>' => problems.push((index..index + 1, "'>' without a matching '<'".to_string())),
+            ']' => problems.push((index..index + 1, "']' without a matching '['".to_string())),
+            _ => {}
+        }
+    }
+
+    for start in open_braces {
+        problems.push((start..start + 1, "Unclosed '{'".to_string()));
+    }
+
+    (references, problems)
+}
+
+fn reference_problem(key: &str) -> Option<String> {
+    if key.trim().is_empty() {
+        Some("Empty '<>' reference".to_string())
+    } else if key.contains(char::is_whitespace) {
+        Some(format!(
+            "Invalid reference '<{}>', keys can't contain spaces",
+            key
+        ))
+    } else {
+        None
+    }
+}
+
+/// Commands are scope chains ending in a function, like `This.Owner.GetName`
+fn command_problem(command: &str) -> Option<String> {
+    if command.trim().is_empty() {
+        return Some("Empty '[]' command".to_string());
+    }
+
+    let valid_segment = |segment: &str| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == ':' || ch == '@')
+    };
+    if command.split('.').all(valid_segment) {
+        None
+    } else {
+        Some(format!(
+            "Invalid command '[{}]', expected a chain like [This.GetName]",
+            command
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_name_format() {
+        let (references, problems) =
+            parse_name_format("{<imperial_mil> {[This.GetSpeciesName] <empire_name>}}");
+        assert!(problems.is_empty());
+        assert_eq!(
+            references,
+            vec![
+                FormatReference {
+                    key: "imperial_mil",
+                    span: 2..14
+                },
+                FormatReference {
+                    key: "empire_name",
+                    span: 40..51
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_name_format() {
+        let (references, problems) = parse_name_format("{<> [This..GetName] <open {x}");
+        assert!(references.is_empty());
+        assert_eq!(
+            problems,
+            vec![
+                (1..3, "Empty '<>' reference".to_string()),
+                (
+                    4..19,
+                    "Invalid command '[This..GetName]', expected a chain like [This.GetName]"
+                        .to_string()
+                ),
+                (20..21, "Unclosed '<' reference".to_string()),
+                (0..1, "Unclosed '{'".to_string()),
+            ]
+        );
+
+        let (_, problems) = parse_name_format("{a} {b}");
+        assert_eq!(
+            problems,
+            vec![(
+                3..7,
+                "Text after the closing '}' of the name format".to_string()
+            )]
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use cw_model::entity_from_module_ast;
 use cw_parser::{AstEntityItem, AstModule, AstNode, AstValue};
 
-use crate::handlers::cache::TypeCache;
+use crate::handlers::cache::{TypeCache, is_sprite_file};
 use crate::handlers::common_validation::{
     NamespaceValidationResult, apply_file_level_subtype_narrowing, create_variable_assignment_type,
     detect_skip_root_key_container, filter_and_narrow_entity_type, is_type_per_file_namespace,
@@ -10,8 +10,15 @@ use crate::handlers::common_validation::{
 use crate::handlers::diagnostics::diagnostic::{
     UnresolvedDiagnostic, create_diagnostic_from_parse_error, create_unexpected_key_diagnostic,
 };
+use crate::handlers::diagnostics::duplicates::validate_duplicate_definitions;
+use crate::handlers::diagnostics::icon::validate_sprite_textures;
+use crate::handlers::diagnostics::localisation::{
+    validate_localisation_file, validate_localisation_requirements,
+};
 use crate::handlers::diagnostics::type_validation::validate_entity_value;
 use crate::handlers::scoped_type::{CwtTypeOrSpecialRef, PropertyNavigationResult, ScopedType};
+use crate::handlers::settings::Settings;
+use crate::handlers::utils::{extract_namespace_from_uri, localisation_path};
 use crate::interner::get_interner;
 use std::collections::HashMap;
 use std::path::Path;
@@ -46,6 +53,13 @@ impl DiagnosticsProvider {
 
         let documents_guard = self.documents.read().unwrap();
         if let Some(content) = documents_guard.get(uri) {
+            if let Some(path) = localisation_path(uri) {
+                return validate_localisation_file(&path, content)
+                    .into_iter()
+                    .map(|d| d.into())
+                    .collect();
+            }
+
             let mut diagnostics = Vec::new();
 
             // First, try to parse the content
@@ -62,6 +76,12 @@ impl DiagnosticsProvider {
 
                         diagnostics.extend(type_diagnostics);
                     }
+                    if base_dir
+                        .as_deref()
+                        .is_some_and(|base_dir| is_sprite_document(uri, base_dir))
+                    {
+                        diagnostics.extend(validate_sprite_textures(&module, content));
+                    }
                 }
                 Err(error) => {
                     if self.log {
@@ -287,6 +307,19 @@ impl DiagnosticsProvider {
                 };
             }
         }
+
+        if Settings::global().validate_localisation {
+            diagnostics.extend(validate_localisation_requirements(
+                module, namespace, content,
+            ));
+        }
+
+        if let Some(path) = Url::parse(uri).ok().and_then(|url| url.to_file_path().ok()) {
+            diagnostics.extend(validate_duplicate_definitions(
+                module, namespace, &path, root_dir, content,
+            ));
+        }
+
         diagnostics
     }
 
@@ -303,6 +336,10 @@ impl DiagnosticsProvider {
             eprintln!("🔍 Starting diagnostics generation for content: {}", uri);
         }
 
+        if let Some(path) = localisation_path(uri) {
+            return validate_localisation_file(&path, content);
+        }
+
         let mut diagnostics = Vec::new();
 
         // First, try to parse the content
@@ -316,6 +353,9 @@ impl DiagnosticsProvider {
                 let type_diagnostics =
                     self.generate_type_diagnostics(&module, uri, content, root_dir);
                 diagnostics.extend(type_diagnostics);
+                if is_sprite_document(uri, root_dir) {
+                    diagnostics.extend(validate_sprite_textures(&module, content));
+                }
             }
             Err(error) => {
                 if self.log {
@@ -340,3 +380,9 @@ impl DiagnosticsProvider {
         diagnostics
     }
 }
+
+/// Whether a document is a `.gfx` file that defines sprites, e.g. `interface/eventpictures.gfx`
+fn is_sprite_document(uri: &str, root_dir: &Path) -> bool {
+    extract_namespace_from_uri(uri, root_dir)
+        .is_some_and(|namespace| is_sprite_file(&namespace, uri))
+}
@@ -0,0 +1,162 @@
+use cw_model::{CwtType, Range, Value};
+use cw_parser::{AstNode, AstValue};
+use lasso::Spur;
+
+use crate::handlers::cache::{EntityRestructurer, GameDataCache, ModDataCache};
+use crate::handlers::diagnostics::diagnostic::{
+    UnresolvedDiagnostic, create_value_mismatch_diagnostic,
+};
+use crate::handlers::scoped_type::{CwtTypeOrSpecial, ScopedType};
+use crate::interner::get_interner;
+
+/// Get the range a value of a type has to be in, e.g. for `int[0..inf]`
+pub fn type_range(expected_type: &ScopedType) -> Option<&Range> {
+    match expected_type.cwt_type() {
+        CwtTypeOrSpecial::CwtType(cwt_type) => match &**cwt_type {
+            CwtType::Ranged(_, range) => Some(range),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Check that a number is within the range of its rule. For scripted variables like `@weight`,
+/// the value of the variable is checked instead.
+pub fn validate_range<'a>(
+    value: &AstValue<'_>,
+    range: &Range,
+    content: &'a str,
+    namespace: Spur,
+) -> Option<UnresolvedDiagnostic<'a>> {
+    let interner = get_interner();
+
+    let (number, variable) = match value {
+        AstValue::Number(n) => (n.value.value.to_string(), None),
+        AstValue::String(s) if s.raw_value().starts_with('@') => {
+            let variable = interner.get_or_intern(s.raw_value());
+            (
+                scripted_variable_value(variable, namespace)?,
+                Some(s.raw_value()),
+            )
+        }
+        _ => return None,
+    };
+
+    let parsed: f64 = number.parse().ok()?;
+    if range.contains(parsed) {
+        return None;
+    }
+
+    let bound = if range.min.as_f64().is_some_and(|min| parsed < min) {
+        format!("below the minimum of {}", range.min)
+    } else {
+        format!("above the maximum of {}", range.max)
+    };
+    let message = match variable {
+        Some(variable) => format!(
+            "Value of {} ({}) is {}, expected a value in {}",
+            variable, number, bound, range
+        ),
+        None => format!(
+            "Value {} is {}, expected a value in {}",
+            number, bound, range
+        ),
+    };
+
+    Some(create_value_mismatch_diagnostic(
+        value.span_range(),
+        &message,
+        content,
+    ))
+}
+
+/// Get the number a scripted variable is set to. Variables of the file's namespace shadow global
+/// ones, and mod variables shadow the base game's.
+fn scripted_variable_value(variable: Spur, namespace: Spur) -> Option<String> {
+    let value = EntityRestructurer::get_namespace_scripted_variables(namespace)
+        .and_then(|variables| variables.get(&variable).cloned())
+        .or_else(|| {
+            ModDataCache::get_scripted_variables()
+                .get(&variable)
+                .cloned()
+        })
+        .or_else(|| {
+            GameDataCache::get()?
+                .scripted_variables
+                .get(&variable)
+                .cloned()
+        })?;
+
+    match value {
+        Value::Number(number) => Some(get_interner().resolve(&number).to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cw_parser::AstModuleCell;
+
+    use super::*;
+
+    /// The message of the diagnostic for the value of `weight = <value>`, if any
+    fn range_error(value: &str, range: &Range) -> Option<String> {
+        let content = format!("weight = {}", value);
+        let module = AstModuleCell::from_input(content.clone());
+        let ast = module.borrow_dependent().as_ref().unwrap();
+        let cw_parser::AstEntityItem::Expression(expression) = &ast.items[0] else {
+            panic!("Expected an expression");
+        };
+
+        let namespace = get_interner().get_or_intern("game/common/buildings");
+        validate_range(&expression.value, range, &content, namespace)
+            .map(|diagnostic| diagnostic.message)
+    }
+
+    #[test]
+    fn test_literal_range() {
+        let range = Range::int_range(0, 10);
+
+        assert_eq!(range_error("0", &range), None);
+        assert_eq!(range_error("10", &range), None);
+        assert_eq!(
+            range_error("-1", &range).as_deref(),
+            Some("Value -1 is below the minimum of 0, expected a value in 0..10")
+        );
+        assert_eq!(
+            range_error("10.5", &range).as_deref(),
+            Some("Value 10.5 is above the maximum of 10, expected a value in 0..10")
+        );
+
+        // Only numbers have a range
+        assert_eq!(range_error("high", &range), None);
+    }
+
+    #[test]
+    fn test_scripted_variable_range() {
+        let interner = get_interner();
+        {
+            let mut mod_data = ModDataCache::get().write().unwrap();
+            mod_data.scripted_variables.insert(
+                interner.get_or_intern("@range_test_low"),
+                Value::Number(interner.get_or_intern("5")),
+            );
+            mod_data.scripted_variables.insert(
+                interner.get_or_intern("@range_test_high"),
+                Value::Number(interner.get_or_intern("50")),
+            );
+        }
+        let range = Range::int_range(0, 10);
+
+        assert_eq!(range_error("@range_test_low", &range), None);
+        assert_eq!(
+            range_error("@range_test_high", &range).as_deref(),
+            Some(
+                "Value of @range_test_high (50) is above the maximum of 10, expected a value in 0..10"
+            )
+        );
+
+        // Unknown variables are reported elsewhere
+        assert_eq!(range_error("@range_test_missing", &range), None);
+    }
+}
@@ -1,19 +1,133 @@
 use std::ops::Range;
 
-use cw_model::types::CwtAnalyzer;
+use cw_model::types::{CwtAnalyzer, PatternType};
 use lasso::Spur;
 
 use crate::{
     handlers::{
         cache::TypeCache,
-        diagnostics::diagnostic::{UnresolvedDiagnostic, create_value_mismatch_diagnostic},
+        diagnostics::diagnostic::{
+            UnresolvedDiagnostic, create_scope_mismatch_diagnostic,
+            create_value_mismatch_diagnostic,
+        },
         scope::ScopeStack,
+        scoped_type::{CwtTypeOrSpecialRef, ScopedType},
         settings::Settings,
         utils::contains_scripted_argument,
     },
     interner::get_interner,
 };
 
+/// Validate that a key matching an alias (a trigger, effect, ...) is used in a scope allowed by
+/// the `## scope` option of its definitions. `expected_type` is the resolved type of the block
+/// containing the key.
+pub fn validate_alias_scope<'a>(
+    key: Spur,
+    expected_type: &ScopedType,
+    span: Range<usize>,
+    content: &'a str,
+) -> Option<UnresolvedDiagnostic<'a>> {
+    if contains_scripted_argument(key) || !TypeCache::is_initialized() {
+        return None;
+    }
+
+    let CwtTypeOrSpecialRef::Block(block) = expected_type.cwt_type_for_matching() else {
+        return None;
+    };
+
+    // Literal properties aren't aliases, even when an alias of the same name exists
+    if block.properties.contains_key(&key) {
+        return None;
+    }
+
+    let cache = TypeCache::get().unwrap();
+    let resolver = cache.get_resolver();
+    let analyzer = cache.get_cwt_analyzer();
+    let interner = get_interner();
+
+    let current_scope = expected_type.scope_stack().current_scope().scope_type;
+    if interner.resolve(&current_scope) == "any" {
+        return None;
+    }
+
+    // Links navigate to another scope rather than acting on the current one
+    if resolver.is_link_property(key, current_scope).is_some() {
+        return None;
+    }
+
+    let alias_categories = block
+        .pattern_properties
+        .iter()
+        .chain(
+            expected_type
+                .subtypes()
+                .iter()
+                .filter_map(|subtype| block.subtype_pattern_properties.get(subtype))
+                .flatten(),
+        )
+        .filter_map(|pattern| match pattern.pattern_type {
+            PatternType::AliasName { category } => Some(category),
+            _ => None,
+        });
+
+    let mut allowed_scopes: Vec<Spur> = Vec::new();
+    for category in alias_categories {
+        for alias in resolver.get_matching_alias_definitions(key, category) {
+            // Any definition without a scope constraint makes the key valid everywhere
+            let scopes = alias.options.scope.as_ref()?;
+            for scope in scopes {
+                if !allowed_scopes.contains(scope) {
+                    allowed_scopes.push(*scope);
+                }
+            }
+        }
+    }
+
+    if allowed_scopes.is_empty() || is_scope_allowed(&allowed_scopes, current_scope, analyzer) {
+        return None;
+    }
+
+    if interner.resolve(&current_scope) == "unknown" && !Settings::global().report_unknown_scopes {
+        return None;
+    }
+
+    Some(create_scope_mismatch_diagnostic(
+        span,
+        &format!(
+            "'{}' is not valid in {} scope, expected one of: {}",
+            interner.resolve(&key),
+            interner.resolve(&current_scope),
+            allowed_scopes
+                .iter()
+                .map(|scope| interner.resolve(scope).to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        content,
+    ))
+}
+
+/// Check if a scope is one of the allowed scopes, where `any` and `all` allow every scope and
+/// scope groups allow each of their members
+pub fn is_scope_allowed(allowed_scopes: &[Spur], scope: Spur, analyzer: &CwtAnalyzer) -> bool {
+    let interner = get_interner();
+    let scope = analyzer.resolve_scope_name(scope).unwrap_or(scope);
+
+    allowed_scopes.iter().any(|allowed| {
+        if matches!(interner.resolve(allowed), "any" | "all") {
+            return true;
+        }
+
+        match analyzer.get_scope_group(*allowed) {
+            Some(scope_group) => scope_group
+                .members
+                .iter()
+                .any(|member| analyzer.resolve_scope_name(*member).unwrap_or(*member) == scope),
+            None => analyzer.resolve_scope_name(*allowed).unwrap_or(*allowed) == scope,
+        }
+    })
+}
+
 /// Validate a scope reference value (handles dotted paths like "prev.from")
 pub fn validate_scope_reference<'a>(
     value: Spur,
@@ -144,7 +258,7 @@ pub fn validate_scopegroup_reference<'a>(
 }
 
 /// Validate that a scope path is structurally valid (handles dotted paths)
-fn validate_scope_path<'a>(
+pub fn validate_scope_path<'a>(
     value: Spur,
     scope_stack: &ScopeStack,
     span: Range<usize>,
@@ -450,3 +564,79 @@ fn navigate_from_scope(
             .join(", ")
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cw_parser::CwtModule;
+
+    fn analyzer() -> CwtAnalyzer {
+        let cwt_text = r#"
+scopes = {
+    Country = {
+        aliases = { country }
+    }
+    Planet = {
+        aliases = { planet }
+    }
+    Ship = {
+        aliases = { ship }
+    }
+}
+
+scope_groups = {
+    celestial_coordinate = {
+        planet ship
+    }
+}
+        "#;
+
+        let mut analyzer = CwtAnalyzer::new();
+        let module = CwtModule::from_input(cwt_text).unwrap();
+        let _ = analyzer.convert_module(&module, get_interner());
+        analyzer
+    }
+
+    #[test]
+    fn test_is_scope_allowed() {
+        let analyzer = analyzer();
+        let interner = get_interner();
+        let scope = |name: &str| interner.get_or_intern(name);
+
+        assert!(is_scope_allowed(
+            &[scope("country")],
+            scope("country"),
+            &analyzer
+        ));
+        assert!(!is_scope_allowed(
+            &[scope("country")],
+            scope("planet"),
+            &analyzer
+        ));
+        assert!(is_scope_allowed(
+            &[scope("country"), scope("planet")],
+            scope("planet"),
+            &analyzer
+        ));
+        assert!(is_scope_allowed(&[scope("any")], scope("ship"), &analyzer));
+        assert!(is_scope_allowed(&[scope("all")], scope("ship"), &analyzer));
+    }
+
+    #[test]
+    fn test_is_scope_allowed_with_scope_groups() {
+        let analyzer = analyzer();
+        let interner = get_interner();
+        let scope = |name: &str| interner.get_or_intern(name);
+
+        assert!(is_scope_allowed(
+            &[scope("celestial_coordinate")],
+            scope("ship"),
+            &analyzer
+        ));
+        assert!(!is_scope_allowed(
+            &[scope("celestial_coordinate")],
+            scope("country"),
+            &analyzer
+        ));
+    }
+}
@@ -255,6 +255,8 @@ fn is_value_compatible_with_simple_type_structurally(
         (AstValue::Number(_), SimpleType::Float) => true,
         (AstValue::Number(_), SimpleType::PercentageField) => true,
         (AstValue::Number(_), SimpleType::IntValueField) => true,
+        (AstValue::Number(_), SimpleType::VariableField) => true,
+        (AstValue::Number(_), SimpleType::IntVariableField) => true,
 
         // Specialized types
         (AstValue::Entity(e), SimpleType::Color) if e.has_tag("rgb") || e.has_tag("hsv") => true,
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use std::collections::HashSet;
 
 use cw_model::{CwtType, ReferenceType};
-use cw_parser::{AstEntityItem, AstNode, AstValue};
+use cw_parser::{AstEntity, AstEntityItem, AstNode, AstValue};
 use lasso::Spur;
 
 use crate::handlers::diagnostics::diagnostic::UnresolvedDiagnostic;
@@ -11,12 +11,20 @@ use crate::handlers::utils::contains_scripted_argument;
 use crate::handlers::{
     cache::{FileIndex, TypeCache},
     diagnostics::{
+        cardinality::validate_cardinality,
         diagnostic::{
-            create_type_mismatch_diagnostic, create_unexpected_key_diagnostic,
+            DiagnosticFix, create_type_mismatch_diagnostic, create_unexpected_key_diagnostic,
             create_value_mismatch_diagnostic,
         },
-        scope_validation::{validate_scope_reference, validate_scopegroup_reference},
+        icon::validate_icon,
+        modifier_category::validate_modifier_category,
+        name_format::validate_stellaris_name_format,
+        range::{type_range, validate_range},
+        scope_validation::{
+            validate_alias_scope, validate_scope_reference, validate_scopegroup_reference,
+        },
         structural::{calculate_structural_compatibility_score, is_value_structurally_compatible},
+        util::closest_matches,
         value::is_value_compatible_with_simple_type,
     },
     scope::ScopeStack,
@@ -38,6 +46,7 @@ fn extract_possible_values(cwt_type: &CwtType) -> Vec<String> {
             sorted_values
         }
         CwtType::Simple(simple_type) => vec![format!("<{:?}>", simple_type)],
+        CwtType::Ranged(simple_type, range) => vec![format!("<{:?}[{}]>", simple_type, range)],
         CwtType::Block(_) => vec!["<block>".to_string()],
         CwtType::Array(_) => vec!["<array>".to_string()],
         CwtType::Union(types) => {
@@ -78,6 +87,8 @@ pub fn validate_entity_value<'a>(
 
     match value {
         AstValue::Entity(entity) => {
+            let resolved_type = cache.resolve_type(expected_type.clone());
+
             // Validate each property in the entity
             for item in &entity.items {
                 if let AstEntityItem::Expression(expr) = item {
@@ -87,6 +98,24 @@ pub fn validate_entity_value<'a>(
                         .get_resolver()
                         .navigate_to_property(expected_type.clone(), key_name)
                     {
+                        if let Some(diagnostic) = validate_alias_scope(
+                            key_name,
+                            &resolved_type,
+                            expr.key.span_range(),
+                            content,
+                        ) {
+                            diagnostics.push(diagnostic);
+                        }
+
+                        if let Some(diagnostic) = validate_modifier_category(
+                            key_name,
+                            &resolved_type,
+                            expr.key.span_range(),
+                            content,
+                        ) {
+                            diagnostics.push(diagnostic);
+                        }
+
                         // Validate the value against the property type
                         let value_diagnostics = validate_value_against_type(
                             &expr.value,
@@ -97,16 +126,36 @@ pub fn validate_entity_value<'a>(
                         );
                         diagnostics.extend(value_diagnostics);
                     } else {
-                        let diagnostic = create_unexpected_key_diagnostic(
+                        let mut diagnostic = create_unexpected_key_diagnostic(
                             expr.key.span_range(),
                             key_name,
                             &expected_type.type_name_for_display(),
                             content,
                         );
+                        if let Some(fix) = unexpected_key_fix(&resolved_type, expr.key.raw_value())
+                        {
+                            diagnostic = diagnostic.with_fix(fix);
+                        }
                         diagnostics.push(diagnostic);
                     }
                 }
             }
+
+            // Bare values in blocks like `prerequisites = { tech_a tech_b }`
+            if let CwtTypeOrSpecialRef::Block(block_type) = resolved_type.cwt_type_for_matching()
+                && !block_type.additional_flags.is_empty()
+            {
+                diagnostics.extend(validate_array_elements(
+                    entity,
+                    &block_type.additional_flags,
+                    &resolved_type,
+                    content,
+                    namespace,
+                    depth,
+                ));
+            }
+
+            diagnostics.extend(validate_cardinality(entity, &resolved_type, content));
         }
         _ => {
             // For non-entity values, validate the value directly against the expected type
@@ -119,6 +168,91 @@ pub fn validate_entity_value<'a>(
     diagnostics
 }
 
+/// Validate each bare value of a block, like `{ tech_a tech_b }`, against the types its elements
+/// can have. Every element is checked on its own so that only the bad ones are reported.
+fn validate_array_elements<'a>(
+    entity: &AstEntity<'_>,
+    element_types: &[Arc<CwtType>],
+    expected_type: &Arc<ScopedType>,
+    content: &'a str,
+    namespace: Spur,
+    depth: usize,
+) -> Vec<UnresolvedDiagnostic<'a>> {
+    let element_types: Vec<Arc<ScopedType>> = element_types
+        .iter()
+        .map(|element_type| {
+            Arc::new(ScopedType::new_cwt(
+                element_type.clone(),
+                expected_type.scope_stack().clone(),
+                expected_type.in_scripted_effect_block().cloned(),
+            ))
+        })
+        .collect();
+
+    entity
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            AstEntityItem::Item(element) => Some(validate_array_element(
+                element,
+                &element_types,
+                content,
+                namespace,
+                depth + 1,
+            )),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Validate one element of a block against each type it can have. An element that matches none
+/// of several types gets a single diagnostic listing what all of them accept.
+fn validate_array_element<'a>(
+    element: &AstValue<'_>,
+    element_types: &[Arc<ScopedType>],
+    content: &'a str,
+    namespace: Spur,
+    depth: usize,
+) -> Vec<UnresolvedDiagnostic<'a>> {
+    let mut results: Vec<Vec<UnresolvedDiagnostic<'a>>> = element_types
+        .iter()
+        .map(|element_type| {
+            validate_value_against_type(element, element_type.clone(), content, namespace, depth)
+        })
+        .collect();
+
+    if results.iter().any(|diagnostics| diagnostics.is_empty()) {
+        return Vec::new();
+    }
+    if results.len() == 1 {
+        return results.pop().unwrap_or_default();
+    }
+
+    let cache = TypeCache::get().unwrap();
+    let mut possible_values: Vec<String> = element_types
+        .iter()
+        .flat_map(
+            |element_type| match cache.resolve_type(element_type.clone()).cwt_type() {
+                CwtTypeOrSpecial::CwtType(cwt_type) => extract_possible_values(cwt_type),
+                _ => Vec::new(),
+            },
+        )
+        .collect();
+    possible_values.sort();
+    possible_values.dedup();
+
+    vec![create_value_mismatch_diagnostic(
+        element.span_range(),
+        &format!(
+            "Expected one of {} but got '{}'",
+            possible_values.join(", "),
+            &content[element.span_range()]
+        ),
+        content,
+    )]
+}
+
 /// Helper function to validate a value against multiple union types with structural scoring
 fn validate_union_types<'a>(
     value: &AstValue<'_>,
@@ -305,7 +439,7 @@ fn validate_value_against_type<'a>(
             if !contains_scripted_argument(string_value) {
                 if !valid_values.contains(&string_value) {
                     let valid_list: Vec<_> = valid_values.iter().collect();
-                    let diagnostic = create_value_mismatch_diagnostic(
+                    let mut diagnostic = create_value_mismatch_diagnostic(
                         value.span_range(),
                         &format!(
                             "Expected one of {} but got '{}'",
@@ -318,6 +452,20 @@ fn validate_value_against_type<'a>(
                         ),
                         content,
                     );
+
+                    // Values with an `@` suffix aren't replaced, the fix would drop the suffix
+                    let suggestions = closest_matches(
+                        interner.resolve(&string_value),
+                        valid_list.iter().map(|v| interner.resolve(v)),
+                        3,
+                    );
+                    if !suggestions.is_empty()
+                        && content
+                            .get(value.span_range())
+                            .is_some_and(|raw_value| !raw_value.contains('@'))
+                    {
+                        diagnostic = diagnostic.with_fix(DiagnosticFix::Replace { suggestions });
+                    }
                     diagnostics.push(diagnostic);
                 }
             }
@@ -365,16 +513,39 @@ fn validate_value_against_type<'a>(
                 Some(namespace),
             ) {
                 diagnostics.push(diagnostic);
+            } else if let Some(range) = type_range(&resolved_type)
+                // Only values of the right type can be out of range
+                && let Some(diagnostic) = validate_range(value, range, content, namespace)
+            {
+                diagnostics.push(diagnostic);
             }
         }
 
         // Array type validation
-        (CwtTypeOrSpecialRef::Array(array_type), AstValue::Entity(_entity)) => {
-            // Arrays in CW are represented as entities with numbered keys
-            // For now, we'll just validate that it's an entity - more complex validation would require
-            // checking that all keys are valid indices and values match the element type
-            let _element_type = &array_type.element_type;
-            // TODO: Implement array element validation
+        (CwtTypeOrSpecialRef::Array(array_type), AstValue::Entity(entity)) => {
+            diagnostics.extend(validate_array_elements(
+                entity,
+                std::slice::from_ref(&array_type.element_type),
+                &expected_type,
+                content,
+                namespace,
+                depth,
+            ));
+
+            // Unlike blocks, arrays can't have any properties
+            for item in &entity.items {
+                if let AstEntityItem::Expression(expr) = item {
+                    let diagnostic = create_type_mismatch_diagnostic(
+                        expr.span_range(),
+                        &format!(
+                            "Expected a list of values, but got '{} = ...'",
+                            expr.key.raw_value()
+                        ),
+                        content,
+                    );
+                    diagnostics.push(diagnostic);
+                }
+            }
         }
         (CwtTypeOrSpecialRef::Array(_), _) => {
             let diagnostic = create_type_mismatch_diagnostic(
@@ -492,35 +663,12 @@ fn validate_value_against_type<'a>(
             }
             ReferenceType::Icon { path } => {
                 if let AstValue::String(string_value) = value {
-                    if let Some(file_index) = FileIndex::get() {
-                        let file_index = file_index.read().unwrap();
-
-                        // Construct the full path for the icon file (append .dds extension)
-                        let icon_filename = string_value.raw_value();
-                        let icon_filename_with_ext = format!("{}.dds", icon_filename);
-                        let full_path = if path.is_empty() {
-                            icon_filename_with_ext
-                        } else {
-                            format!("{}/{}", path.trim_end_matches('/'), icon_filename_with_ext)
-                        };
-
-                        if !file_index.file_exists(&full_path) {
-                            let diagnostic = create_type_mismatch_diagnostic(
-                                value.span_range(),
-                                &format!(
-                                    "Icon file '{}' does not exist in path '{}'",
-                                    icon_filename, path
-                                ),
-                                content,
-                            );
-                            diagnostics.push(diagnostic);
-                        }
-                    } else {
-                        let diagnostic = create_type_mismatch_diagnostic(
-                            value.span_range(),
-                            "File index not initialized, cannot validate icon path",
-                            content,
-                        );
+                    if let Some(diagnostic) = validate_icon(
+                        string_value.raw_value(),
+                        Some(path),
+                        value.span_range(),
+                        content,
+                    ) {
                         diagnostics.push(diagnostic);
                     }
                 } else {
@@ -585,15 +733,14 @@ fn validate_value_against_type<'a>(
             }
             ReferenceType::StellarisNameFormat { key } => {
                 if let AstValue::String(string_value) = value {
-                    if let Some(diagnostic) = validate_stellaris_name_format(
-                        string_value.raw_value(),
+                    // The span of quoted strings includes the quotes
+                    let offset = value.span_range().start + usize::from(string_value.is_quoted);
+                    diagnostics.extend(validate_stellaris_name_format(
                         key,
-                        expected_type.scope_stack(),
-                        value.span_range(),
+                        string_value.raw_value(),
+                        offset,
                         content,
-                    ) {
-                        diagnostics.push(diagnostic);
-                    }
+                    ));
                 } else {
                     let diagnostic = create_type_mismatch_diagnostic(
                         value.span_range(),
@@ -638,21 +785,186 @@ fn validate_value_against_type<'a>(
     diagnostics
 }
 
-fn validate_stellaris_name_format<'a>(
-    raw_value: &str,
-    _key: &str,
-    _scope_stack: &ScopeStack,
-    _span_range: std::ops::Range<usize>,
-    content: &'a str,
-) -> Option<UnresolvedDiagnostic<'a>> {
-    // TODO... they're complicated
-    if !raw_value.starts_with("{") || !raw_value.ends_with("}") {
-        return Some(create_type_mismatch_diagnostic(
-            _span_range,
-            "Name format must start and end with curly braces",
-            content,
+/// Suggest the closest keys a block accepts in place of an unexpected key
+fn unexpected_key_fix(expected_type: &ScopedType, key: &str) -> Option<DiagnosticFix> {
+    let CwtTypeOrSpecialRef::Block(block_type) = expected_type.cwt_type_for_matching() else {
+        return None;
+    };
+    let interner = get_interner();
+
+    let mut known_keys: Vec<&str> = block_type
+        .properties
+        .iter()
+        .map(|(key, _)| interner.resolve(&key))
+        .collect();
+    for subtype in expected_type.subtypes() {
+        if let Some(subtype_properties) = block_type.subtype_properties.get(subtype) {
+            known_keys.extend(
+                subtype_properties
+                    .iter()
+                    .map(|(key, _)| interner.resolve(&key)),
+            );
+        }
+    }
+
+    let suggestions = closest_matches(key, known_keys, 3);
+    if suggestions.is_empty() {
+        None
+    } else {
+        Some(DiagnosticFix::Replace { suggestions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cw_parser::AstModuleCell;
+
+    use super::*;
+
+    const CWT: &str = r#"
+types = {
+    type[building] = {
+        path = "game/common/buildings"
+    }
+}
+
+enums = {
+    enum[techs] = {
+        tech_a
+        tech_b
+    }
+    enum[flags] = {
+        flag_a
+    }
+}
+
+building = {
+    ## cardinality = 0..1
+    prerequisites = {
+        enum[techs]
+    }
+    ## cardinality = 0..1
+    flags = {
+        enum[techs]
+        enum[flags]
+    }
+    ## cardinality = 0..1
+    cost = int[0..100]
+    ## cardinality = 0..1
+    levels = {
+        int[1..5]
+    }
+    ## cardinality = 0..1
+    upkeep = float[0.0..10.0]
+    ## cardinality = 0..1
+    upkeep = {
+        amount = float
+    }
+}
+"#;
+
+    /// The underlined text and message of each diagnostic for a building with the given body
+    fn building_diagnostics(body: &str) -> Vec<(String, String)> {
+        TypeCache::set_for_test(CWT);
+        let interner = get_interner();
+        let cache = TypeCache::get().unwrap();
+        let building = cache
+            .get_cwt_analyzer()
+            .get_type(interner.get_or_intern("building"))
+            .unwrap();
+        let building_type = Arc::new(ScopedType::new_cwt(
+            building.rules.clone(),
+            Default::default(),
+            None,
         ));
+
+        let content = format!("building_foo = {{ {} }}", body);
+        let module = AstModuleCell::from_input(content.clone());
+        let ast = module.borrow_dependent().as_ref().unwrap();
+        let AstEntityItem::Expression(expression) = &ast.items[0] else {
+            panic!("Expected an expression");
+        };
+
+        validate_entity_value(
+            &expression.value,
+            building_type,
+            &content,
+            interner.get_or_intern("game/common/buildings"),
+            0,
+        )
+        .into_iter()
+        .map(|diagnostic| (content[diagnostic.span].to_string(), diagnostic.message))
+        .collect()
+    }
+
+    /// The underlined text of each diagnostic for a building with the given body
+    fn building_errors(body: &str) -> Vec<String> {
+        building_diagnostics(body)
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect()
     }
 
-    None
+    #[test]
+    fn test_array_elements_are_checked_individually() {
+        assert!(building_errors("prerequisites = { tech_a tech_b }").is_empty());
+        assert_eq!(
+            building_errors("prerequisites = { tech_a typo }"),
+            vec!["typo"]
+        );
+        assert_eq!(
+            building_errors("prerequisites = { typo tech_b other_typo }"),
+            vec!["typo", "other_typo"]
+        );
+    }
+
+    #[test]
+    fn test_array_elements_of_union_type() {
+        assert!(building_errors("flags = { tech_a flag_a }").is_empty());
+        assert_eq!(building_errors("flags = { flag_a typo }"), vec!["typo"]);
+    }
+
+    #[test]
+    fn test_union_array_element_lists_all_values() {
+        assert_eq!(
+            building_diagnostics("flags = { typo }"),
+            vec![(
+                "typo".to_string(),
+                r#"Expected one of "flag_a", "tech_a", "tech_b" but got 'typo'"#.to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_keys_in_array_are_rejected() {
+        assert_eq!(
+            building_errors("prerequisites = { tech_a key = tech_b }"),
+            vec!["key"]
+        );
+    }
+
+    #[test]
+    fn test_property_range() {
+        assert!(building_errors("cost = 100").is_empty());
+        assert_eq!(
+            building_diagnostics("cost = 101"),
+            vec![(
+                "101".to_string(),
+                "Value 101 is above the maximum of 100, expected a value in 0..100".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_array_element_range() {
+        assert!(building_errors("levels = { 1 5 }").is_empty());
+        assert_eq!(building_errors("levels = { 1 0 6 }"), vec!["0", "6"]);
+    }
+
+    #[test]
+    fn test_union_alternative_range() {
+        assert!(building_errors("upkeep = 2.5").is_empty());
+        assert!(building_errors("upkeep = { amount = 20 }").is_empty());
+        assert_eq!(building_errors("upkeep = 20"), vec!["20"]);
+    }
 }
@@ -10,6 +10,7 @@ pub fn get_type_name(cwt_type: &CwtType) -> String {
     let interner = get_interner();
     match cwt_type {
         CwtType::Simple(simple_type) => format!("{:?}", simple_type),
+        CwtType::Ranged(simple_type, range) => format!("{:?}[{}]", simple_type, range),
         CwtType::Block(_) => "block".to_string(),
         CwtType::Literal(value) => format!("'{}'", interner.resolve(value)),
         CwtType::LiteralSet(values) => {
@@ -93,7 +94,8 @@ fn offset_to_position(content: &str, offset: usize) -> Position {
             line += 1;
             character = 0;
         } else {
-            character += 1;
+            // LSP positions count UTF-16 code units
+            character += ch.len_utf16();
         }
     }
 
@@ -102,3 +104,70 @@ fn offset_to_position(content: &str, offset: usize) -> Position {
         character: character as u32,
     }
 }
+
+/// Find the candidates closest to a misspelled `name`, best match first. Only candidates within a
+/// few edits of `name` are returned, so an unrelated name doesn't get suggested.
+pub fn closest_matches<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+    limit: usize,
+) -> Vec<String> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(&name, &candidate.to_lowercase());
+            (distance > 0 && distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+
+    matches.sort();
+    matches.dedup_by(|a, b| a.1 == b.1);
+    matches
+        .into_iter()
+        .take(limit)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_matches() {
+        let candidates = ["potential", "prerequisites", "cost", "category", "costs"];
+
+        assert_eq!(
+            closest_matches("potentail", candidates, 3),
+            vec!["potential"]
+        );
+        assert_eq!(
+            closest_matches("cots", candidates, 3),
+            vec!["costs", "cost"]
+        );
+        assert!(closest_matches("weight_modifier", candidates, 3).is_empty());
+        // An exact match isn't a suggestion
+        assert!(closest_matches("Cost", ["cost"], 3).is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use cw_model::SimpleType;
+use cw_model::{GameDate, SimpleType};
 use cw_parser::{AstNode, AstValue};
 use lasso::Spur;
 
@@ -9,7 +9,12 @@ use crate::{
         cache::{
             EntityRestructurer, FileIndex, FullAnalysis, GameDataCache, ModDataCache, TypeCache,
         },
-        diagnostics::diagnostic::{UnresolvedDiagnostic, create_type_mismatch_diagnostic},
+        diagnostics::{
+            diagnostic::{DiagnosticFix, UnresolvedDiagnostic, create_type_mismatch_diagnostic},
+            icon::validate_icon,
+            localisation::validate_localisation_key,
+            scope_validation::validate_scope_path,
+        },
         scope::ScopeStack,
         settings::Settings,
         utils::contains_scripted_argument,
@@ -27,38 +32,16 @@ pub fn is_value_compatible_with_simple_type<'a>(
 ) -> Option<UnresolvedDiagnostic<'a>> {
     let interner = get_interner();
     match (value, simple_type) {
-        (AstValue::String(_), SimpleType::Localisation) => {
+        (AstValue::String(string), SimpleType::Localisation | SimpleType::LocalisationSynced) => {
             if Settings::global().validate_localisation {
-                // TODO: Implement proper localisation validation
-                Some(create_type_mismatch_diagnostic(
-                    value.span_range(),
-                    "Localisation validation not yet implemented",
-                    content,
-                ))
+                validate_localisation_key(string.raw_value(), value.span_range(), false, content)
             } else {
                 None
             }
         }
-        (AstValue::String(_), SimpleType::LocalisationSynced) => {
+        (AstValue::String(string), SimpleType::LocalisationInline) => {
             if Settings::global().validate_localisation {
-                // TODO: Implement proper localisation validation
-                Some(create_type_mismatch_diagnostic(
-                    value.span_range(),
-                    "Localisation synced validation not yet implemented",
-                    content,
-                ))
-            } else {
-                None
-            }
-        }
-        (AstValue::String(_), SimpleType::LocalisationInline) => {
-            if Settings::global().validate_localisation {
-                // TODO: Implement proper localisation validation
-                Some(create_type_mismatch_diagnostic(
-                    value.span_range(),
-                    "Inline localisation validation not yet implemented",
-                    content,
-                ))
+                validate_localisation_key(string.raw_value(), value.span_range(), true, content)
             } else {
                 None
             }
@@ -83,22 +66,17 @@ pub fn is_value_compatible_with_simple_type<'a>(
                 ))
             }
         }
-        (AstValue::String(_), SimpleType::Icon) => {
-            // TODO: Implement proper icon validation
-            Some(create_type_mismatch_diagnostic(
-                value.span_range(),
-                "Icon validation not yet implemented",
-                content,
-            ))
-        }
-        (AstValue::String(_), SimpleType::VariableField) => {
-            // TODO: Implement proper variable field validation
-            Some(create_type_mismatch_diagnostic(
-                value.span_range(),
-                "Variable field validation not yet implemented",
-                content,
-            ))
+        (AstValue::String(string), SimpleType::Icon) => {
+            validate_icon(string.raw_value(), None, value.span_range(), content)
         }
+        (AstValue::String(s), SimpleType::VariableField) => validate_variable_field_string(
+            interner.get_or_intern(s.raw_value()),
+            value.span_range(),
+            content,
+            scope_manager,
+            current_namespace,
+            false,
+        ),
         (AstValue::String(scope_field), SimpleType::ScopeField) => {
             let field_name = interner.get_or_intern(scope_field.raw_value());
 
@@ -130,23 +108,40 @@ pub fn is_value_compatible_with_simple_type<'a>(
                 ))
             }
         }
-        (AstValue::String(_), SimpleType::DateField) => {
-            // TODO: Implement proper date field validation
-            Some(create_type_mismatch_diagnostic(
-                value.span_range(),
-                "Date field validation not yet implemented",
-                content,
-            ))
-        }
+        (AstValue::String(s), SimpleType::DateField) => validate_date(
+            interner.get_or_intern(s.raw_value()),
+            value.span_range(),
+            content,
+            current_namespace,
+        ),
+        // Incomplete dates like `2200.1` are parsed as numbers
+        (AstValue::Number(n), SimpleType::DateField) => validate_date(
+            interner.get_or_intern(n.value.value),
+            value.span_range(),
+            content,
+            current_namespace,
+        ),
         (AstValue::String(_), SimpleType::Scalar) => None, // Valid
         (AstValue::Number(_), SimpleType::Scalar) => None, // Valid
-        (AstValue::String(_), SimpleType::IntVariableField) => {
-            // TODO: Implement proper int variable field validation
-            Some(create_type_mismatch_diagnostic(
-                value.span_range(),
-                "Int variable field validation not yet implemented",
-                content,
-            ))
+        (AstValue::String(s), SimpleType::IntVariableField) => validate_variable_field_string(
+            interner.get_or_intern(s.raw_value()),
+            value.span_range(),
+            content,
+            scope_manager,
+            current_namespace,
+            true,
+        ),
+        (AstValue::Number(_), SimpleType::VariableField) => None, // Valid
+        (AstValue::Number(n), SimpleType::IntVariableField) => {
+            if n.value.value.find('.').is_none() {
+                None // Valid integer
+            } else {
+                Some(create_decimal_mismatch_diagnostic(
+                    n.value.value,
+                    value.span_range(),
+                    content,
+                ))
+            }
         }
 
         (AstValue::Number(_), SimpleType::ValueField) => None, // Valid
@@ -163,9 +158,9 @@ pub fn is_value_compatible_with_simple_type<'a>(
             if n.value.value.find('.').is_none() {
                 None // Valid integer
             } else {
-                Some(create_type_mismatch_diagnostic(
+                Some(create_decimal_mismatch_diagnostic(
+                    n.value.value,
                     value.span_range(),
-                    "Expected integer but got decimal number",
                     content,
                 ))
             }
@@ -218,9 +213,9 @@ pub fn is_value_compatible_with_simple_type<'a>(
             if n.value.value.find('.').is_none() {
                 None // Valid integer
             } else {
-                Some(create_type_mismatch_diagnostic(
+                Some(create_decimal_mismatch_diagnostic(
+                    n.value.value,
                     value.span_range(),
-                    "Expected integer but got decimal number",
                     content,
                 ))
             }
@@ -260,6 +255,8 @@ pub fn is_value_compatible_with_simple_type<'a>(
         (AstValue::Maths(_), SimpleType::ValueField) => None, // Valid, calculated value
         (AstValue::Maths(_), SimpleType::IntValueField) => None, // Valid, calculated value
         (AstValue::Maths(_), SimpleType::PercentageField) => None, // Valid, calculated value
+        (AstValue::Maths(_), SimpleType::VariableField) => None, // Valid, calculated value
+        (AstValue::Maths(_), SimpleType::IntVariableField) => None, // Valid, calculated value
 
         // Type mismatches
         (_, simple_type) => Some(create_type_mismatch_diagnostic(
@@ -274,6 +271,70 @@ pub fn is_value_compatible_with_simple_type<'a>(
     }
 }
 
+/// Report a decimal number where an integer is expected, offering the truncated integer as a fix
+fn create_decimal_mismatch_diagnostic<'a>(
+    number: &str,
+    span_range: Range<usize>,
+    content: &'a str,
+) -> UnresolvedDiagnostic<'a> {
+    let diagnostic = create_type_mismatch_diagnostic(
+        span_range,
+        "Expected integer but got decimal number",
+        content,
+    );
+
+    match truncate_decimal(number) {
+        Some(integer) => diagnostic.with_fix(DiagnosticFix::Replace {
+            suggestions: vec![integer],
+        }),
+        None => diagnostic,
+    }
+}
+
+/// Truncate a decimal number literal towards zero, e.g. `1.5` to `1` and `-0.5` to `0`
+fn truncate_decimal(number: &str) -> Option<String> {
+    let (integer, _) = number.split_once('.')?;
+    let (sign, digits) = match integer.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", integer.strip_prefix('+').unwrap_or(integer)),
+    };
+
+    if !digits.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        Some("0".to_string())
+    } else {
+        Some(format!("{}{}", sign, digits))
+    }
+}
+
+/// Validate a date like `2200.1.1`, which can also be a scripted variable or argument
+fn validate_date<'a>(
+    value: Spur,
+    span_range: Range<usize>,
+    content: &'a str,
+    current_namespace: Option<Spur>,
+) -> Option<UnresolvedDiagnostic<'a>> {
+    let value_str = get_interner().resolve(&value);
+    if value_str.starts_with('@') {
+        validate_scripted_variable(value, span_range, content, current_namespace)
+    } else if contains_scripted_argument(value) {
+        None // Argument in scripted effect
+    } else {
+        match value_str.parse::<GameDate>() {
+            Ok(_) => None,
+            Err(error) => Some(create_type_mismatch_diagnostic(
+                span_range,
+                &format!("Invalid date '{}': {}", value_str, error),
+                content,
+            )),
+        }
+    }
+}
+
 /// Validate a scripted variable reference
 fn validate_scripted_variable<'a>(
     variable_name: Spur,
@@ -284,6 +345,69 @@ fn validate_scripted_variable<'a>(
     validate_scripted_variable_exists(variable_name, span_range, content, current_namespace)
 }
 
+/// Validate a variable field string (used by both VariableField and IntVariableField). On top of
+/// everything a value field accepts, variables can be read from another scope, e.g.
+/// `owner.my_var`, in which case the scope chain has to be valid from the current scope.
+fn validate_variable_field_string<'a>(
+    value_str: Spur,
+    span_range: Range<usize>,
+    content: &'a str,
+    scope_manager: &ScopeStack,
+    current_namespace: Option<Spur>,
+    include_integer_in_error: bool,
+) -> Option<UnresolvedDiagnostic<'a>> {
+    let interner = get_interner();
+
+    if let Some(scope_chain) = variable_scope_chain(interner.resolve(&value_str))
+        && let Some(diagnostic) = validate_scope_path(
+            interner.get_or_intern(scope_chain),
+            scope_manager,
+            span_range.clone(),
+            content,
+        )
+    {
+        return Some(diagnostic);
+    }
+
+    validate_value_field_string(
+        value_str,
+        span_range,
+        content,
+        current_namespace,
+        include_integer_in_error,
+    )
+}
+
+/// The scope chain a variable field reads its variable from, e.g. `owner` for `owner.my_var`.
+/// Number literals, scripted variables, arguments and prefixed values like `value:x` don't have
+/// one even when they contain a dot.
+fn variable_scope_chain(value: &str) -> Option<&str> {
+    if is_number_literal(value)
+        || value.starts_with('@')
+        || value.contains(':')
+        || contains_scripted_argument(get_interner().get_or_intern(value))
+    {
+        return None;
+    }
+
+    value
+        .rsplit_once('.')
+        .map(|(scope_chain, _variable)| scope_chain)
+}
+
+/// Check if a string is a plain number like `10`, `-2` or `1.5`, as written in a quoted value
+fn is_number_literal(value: &str) -> bool {
+    let unsigned = value
+        .strip_prefix('-')
+        .or_else(|| value.strip_prefix('+'))
+        .unwrap_or(value);
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+
+    !(integer.is_empty() && fraction.is_empty())
+        && integer.chars().all(|ch| ch.is_ascii_digit())
+        && fraction.chars().all(|ch| ch.is_ascii_digit())
+}
+
 /// Helper function to validate value field strings (used by both ValueField and IntValueField)
 fn validate_value_field_string<'a>(
     value_str: Spur,
@@ -293,7 +417,17 @@ fn validate_value_field_string<'a>(
     include_integer_in_error: bool,
 ) -> Option<UnresolvedDiagnostic<'a>> {
     let interner = get_interner();
-    if interner.resolve(&value_str).starts_with("@") {
+    let value = interner.resolve(&value_str);
+    if is_number_literal(value) {
+        // Only the integer fields reject decimals
+        if include_integer_in_error && value.contains('.') {
+            Some(create_decimal_mismatch_diagnostic(
+                value, span_range, content,
+            ))
+        } else {
+            None
+        }
+    } else if interner.resolve(&value_str).starts_with("@") {
         validate_scripted_variable(value_str, span_range, content, current_namespace)
     } else if contains_scripted_argument(value_str) {
         None // Argument in scripted effect
@@ -447,3 +581,120 @@ pub fn get_value_type_name(value: &AstValue<'_>) -> &'static str {
         AstValue::Maths(_) => "math expression",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_decimal() {
+        assert_eq!(truncate_decimal("1.5").as_deref(), Some("1"));
+        assert_eq!(truncate_decimal("-12.75").as_deref(), Some("-12"));
+        assert_eq!(truncate_decimal("-0.5").as_deref(), Some("0"));
+        assert_eq!(truncate_decimal(".5").as_deref(), Some("0"));
+        assert_eq!(truncate_decimal("10"), None);
+    }
+
+    fn variable_field_error(value: &str, integer_only: bool) -> Option<String> {
+        let content = format!("value = \"{}\"", value);
+        let span = 9..content.len() - 1;
+        let scope_stack = ScopeStack::default_with_root(get_interner().get_or_intern("country"));
+        validate_variable_field_string(
+            get_interner().get_or_intern(value),
+            span,
+            &content,
+            &scope_stack,
+            None,
+            integer_only,
+        )
+        .map(|diagnostic| diagnostic.message)
+    }
+
+    #[test]
+    fn test_variable_scope_chain() {
+        assert_eq!(variable_scope_chain("owner.my_var"), Some("owner"));
+        assert_eq!(
+            variable_scope_chain("root.owner.my_var"),
+            Some("root.owner")
+        );
+        assert_eq!(variable_scope_chain("my_var"), None);
+        assert_eq!(variable_scope_chain("1.5"), None);
+        assert_eq!(variable_scope_chain("-0.25"), None);
+        assert_eq!(variable_scope_chain("@var"), None);
+        assert_eq!(variable_scope_chain("$P$"), None);
+        assert_eq!(variable_scope_chain("owner.$P$"), None);
+        assert_eq!(variable_scope_chain("value:x|P|v|"), None);
+    }
+
+    #[test]
+    fn test_is_number_literal() {
+        assert!(is_number_literal("10"));
+        assert!(is_number_literal("-2"));
+        assert!(is_number_literal("1.5"));
+        assert!(is_number_literal(".5"));
+        assert!(!is_number_literal("-"));
+        assert!(!is_number_literal("."));
+        assert!(!is_number_literal("1.2.3"));
+        assert!(!is_number_literal("owner.my_var"));
+        assert!(!is_number_literal("inf"));
+    }
+
+    #[test]
+    fn test_variable_field_literals() {
+        assert_eq!(variable_field_error("1.5", false), None);
+        assert_eq!(variable_field_error("-3", false), None);
+        assert_eq!(variable_field_error("10", true), None);
+        assert_eq!(
+            variable_field_error("1.5", true).as_deref(),
+            Some("Expected integer but got decimal number")
+        );
+    }
+
+    #[test]
+    fn test_variable_field_references() {
+        // Scripted variables can't be checked without game data and are let through
+        assert_eq!(variable_field_error("@var", false), None);
+        assert_eq!(variable_field_error("$P$", true), None);
+        assert_eq!(variable_field_error("owner.$P$", false), None);
+
+        // The parameters of a parameterised script value aren't part of its name
+        assert_eq!(
+            variable_field_error("value:x|P|v|", false).as_deref(),
+            Some("Script value 'x' does not exist")
+        );
+
+        // The variable is looked up without its scope chain
+        assert_eq!(
+            variable_field_error("owner.my_var", false).as_deref(),
+            Some("Variable 'my_var' in scope path does not exist in any value set")
+        );
+    }
+
+    fn date_error(value: &str) -> Option<String> {
+        let content = format!("start_date = {}", value);
+        let span = 13..content.len();
+        validate_date(get_interner().get_or_intern(value), span, &content, None)
+            .map(|diagnostic| diagnostic.message)
+    }
+
+    #[test]
+    fn test_validate_date() {
+        assert_eq!(date_error("2200.1.1"), None);
+        assert_eq!(date_error("2200.01.31"), None);
+        assert_eq!(date_error("@start_date"), None);
+        assert_eq!(date_error("$DATE$"), None);
+
+        assert_eq!(
+            date_error("2200.0.1").as_deref(),
+            Some("Invalid date '2200.0.1': Invalid month 0, expected 1 to 12")
+        );
+        assert_eq!(
+            date_error("2200.2.29").as_deref(),
+            Some("Invalid date '2200.2.29': Invalid day 29, month 2 has 28 days")
+        );
+        assert_eq!(
+            date_error("2200.1").as_deref(),
+            Some("Invalid date '2200.1': Expected a date like YYYY.MM.DD")
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use super::diagnostics;
+use super::file_changes;
 use super::mod_detection;
 use crate::CwLspServer;
 use crate::handlers::mod_detection::ModLoadResult;
-use crate::handlers::utils::log_message_sync;
+use crate::handlers::utils::{apply_content_change, log_message_sync};
 use tower_lsp::lsp_types::*;
 
 pub fn did_open(server: &CwLspServer, params: DidOpenTextDocumentParams) {
@@ -85,33 +86,58 @@ pub fn did_change(server: &CwLspServer, params: DidChangeTextDocumentParams) {
     let uri = params.text_document.uri.to_string();
     let version = Some(params.text_document.version);
 
-    if let Some(change) = params.content_changes.into_iter().next() {
-        let content = change.text.clone();
-
-        // Update the stored document content
-        server
-            .documents
-            .write()
-            .unwrap()
-            .insert(uri.clone(), content.clone());
-
-        // Update the document cache
-        server
-            .document_cache
-            .update_document(uri.clone(), content, version);
-
-        log_message_sync(
-            &server.client,
-            MessageType::INFO,
-            format!("Document changed: {}", uri),
-        );
-
-        // Generate diagnostics for the changed document (spawn async task)
-        let client = server.client.clone();
-        let documents = server.documents.clone();
-        let uri_clone = uri.clone();
-        tokio::spawn(async move {
-            diagnostics::generate_diagnostics(&client, &documents, &uri_clone).await;
-        });
+    // Apply the changes to the stored document content
+    let content = {
+        let mut documents = server.documents.write().unwrap();
+        let Some(text) = documents.get_mut(&uri) else {
+            return;
+        };
+        for change in params.content_changes {
+            apply_content_change(text, change);
+        }
+        text.clone()
+    };
+
+    // The document is parsed again the next time it is needed
+    server
+        .document_cache
+        .mark_changed(uri.clone(), content, version);
+
+    log_message_sync(
+        &server.client,
+        MessageType::INFO,
+        format!("Document changed: {}", uri),
+    );
+
+    server
+        .diagnostics_debouncer
+        .schedule(server.client.clone(), server.documents.clone(), uri);
+}
+
+pub async fn did_close(server: &CwLspServer, params: DidCloseTextDocumentParams) {
+    let uri = params.text_document.uri;
+
+    server.documents.write().unwrap().remove(uri.as_str());
+    server.document_cache.remove_document(uri.as_str());
+    server.diagnostics_debouncer.cancel(uri.as_str());
+
+    log_message_sync(
+        &server.client,
+        MessageType::INFO,
+        format!("Document closed: {}", uri),
+    );
+
+    // Diagnostics are only kept for open documents
+    server
+        .client
+        .publish_diagnostics(uri, Vec::new(), None)
+        .await;
+}
+
+pub fn did_save(server: &CwLspServer, params: DidSaveTextDocumentParams) {
+    // The saved content is what the rest of the workspace sees, so reload it like any other
+    // file that changed on disk
+    if let Ok(path) = params.text_document.uri.to_file_path() {
+        file_changes::reload_files(server, vec![path]);
     }
 }
@@ -11,8 +11,6 @@ pub struct CachedDocument {
     document: AstModuleCell,
     semantic_tokens: Vec<SemanticToken>,
     pub root_dir: PathBuf,
-
-    #[allow(dead_code)]
     version: Option<i32>,
 }
 
@@ -68,31 +66,85 @@ impl CachedDocument {
 /// Document cache that stores parsed ASTs and derived information
 pub struct DocumentCache {
     cache: RwLock<HashMap<String, Arc<CachedDocument>>>,
+    /// Content of documents that changed since they were last parsed. They are parsed again the
+    /// next time they're needed, so typing doesn't reparse the document on every keystroke.
+    pending: RwLock<HashMap<String, (String, Option<i32>)>>,
 }
 
 impl DocumentCache {
     pub fn new() -> Self {
         Self {
             cache: RwLock::new(HashMap::new()),
+            pending: RwLock::new(HashMap::new()),
         }
     }
 
     pub fn get(&self, uri: &str) -> Option<Arc<CachedDocument>> {
+        self.parse_pending(uri);
+
         let cache = self.cache.read().expect("Failed to read cache");
         cache.get(uri).cloned()
     }
 
-    /// Update or create a cached document
+    /// Record new content for a document, to be parsed when it is next used
+    pub fn mark_changed(&self, uri: String, content: String, version: Option<i32>) {
+        let mut pending = self
+            .pending
+            .write()
+            .expect("Failed to write pending changes");
+        pending.insert(uri, (content, version));
+    }
+
+    fn parse_pending(&self, uri: &str) {
+        // The entry stays pending until it is parsed, so a newer version marked in the meantime
+        // isn't lost
+        let pending = self
+            .pending
+            .read()
+            .expect("Failed to read pending changes")
+            .get(uri)
+            .cloned();
+
+        if let Some((content, version)) = pending {
+            self.update_document(uri.to_string(), content, version);
+        }
+    }
+
+    /// Update or create a cached document. A version older than the cached one is ignored, and
+    /// only pending changes this version supersedes are dropped.
     pub fn update_document(&self, uri: String, content: String, version: Option<i32>) {
+        {
+            let mut pending = self
+                .pending
+                .write()
+                .expect("Failed to write pending changes");
+            if pending
+                .get(&uri)
+                .is_some_and(|(_, pending_version)| !is_newer(*pending_version, version))
+            {
+                pending.remove(&uri);
+            }
+        }
+
         if let Some(cached_doc) = CachedDocument::new(&uri, content, version) {
             let mut cache = self.cache.write().expect("Failed to write cache");
+            if cache
+                .get(&uri)
+                .is_some_and(|cached| is_newer(cached.version, version))
+            {
+                return;
+            }
             cache.insert(uri, Arc::new(cached_doc));
         }
     }
 
     /// Remove a document from cache
-    #[allow(dead_code)]
     pub fn remove_document(&self, uri: &str) {
+        self.pending
+            .write()
+            .expect("Failed to write pending changes")
+            .remove(uri);
+
         let mut cache = self.cache.write().expect("Failed to write cache");
         cache.remove(uri);
     }
@@ -105,6 +157,8 @@ impl DocumentCache {
         version: Option<i32>,
         range: Option<Range>,
     ) -> Vec<SemanticToken> {
+        self.parse_pending(uri);
+
         let cache = self.cache.read().expect("Failed to read cache");
         let cached = cache.get(uri);
 
@@ -226,8 +280,57 @@ impl DocumentCache {
     }
 }
 
+/// Whether version `a` of a document is newer than version `b`. Documents without a version can
+/// always be replaced.
+fn is_newer(a: Option<i32>, b: Option<i32>) -> bool {
+    matches!((a, b), (Some(a), Some(b)) if a > b)
+}
+
 impl Default for DocumentCache {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn document_uri(temp_dir: &TempDir) -> String {
+        fs::write(temp_dir.path().join("descriptor.mod"), "name=\"Test Mod\"").unwrap();
+        let path = temp_dir.path().join("common/buildings/test.txt");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        Url::from_file_path(path).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_older_version_does_not_replace_newer() {
+        let temp_dir = TempDir::new().unwrap();
+        let uri = document_uri(&temp_dir);
+        let cache = DocumentCache::new();
+
+        cache.update_document(uri.clone(), "a = 3".to_string(), Some(3));
+        cache.update_document(uri.clone(), "a = 2".to_string(), Some(2));
+
+        assert_eq!(cache.get(&uri).unwrap().borrow_input(), "a = 3");
+    }
+
+    #[test]
+    fn test_change_marked_while_parsing_is_kept() {
+        let temp_dir = TempDir::new().unwrap();
+        let uri = document_uri(&temp_dir);
+        let cache = DocumentCache::new();
+
+        // Version 2 is being parsed when version 3 comes in
+        cache.mark_changed(uri.clone(), "a = 3".to_string(), Some(3));
+        cache.update_document(uri.clone(), "a = 2".to_string(), Some(2));
+
+        assert!(cache.pending.read().unwrap().contains_key(&uri));
+        let document = cache.get(&uri).unwrap();
+        assert_eq!(document.borrow_input(), "a = 3");
+        assert!(document.is_valid_for_version(Some(3)));
+        assert!(cache.pending.read().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use cw_parser::{AstEntity, AstEntityItem, AstNode, AstValue};
+use lasso::Spur;
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, jsonrpc::Result};
+
+use crate::handlers::cache::find_module_definitions;
+use crate::handlers::diagnostics::util::span_to_lsp_range;
+use crate::handlers::utils::extract_namespace_from_uri;
+use crate::interner::get_interner;
+
+use super::document_cache::DocumentCache;
+
+pub fn document_symbol(
+    _client: &Client,
+    _documents: &Arc<RwLock<HashMap<String, String>>>,
+    document_cache: &DocumentCache,
+    params: DocumentSymbolParams,
+) -> Result<Option<DocumentSymbolResponse>> {
+    let uri = params.text_document.uri.to_string();
+
+    let cached_document = match document_cache.get(&uri) {
+        Some(cached_document) => cached_document,
+        None => return Ok(None),
+    };
+
+    let ast = match cached_document.borrow_ast() {
+        Ok(ast) => ast,
+        Err(_) => return Ok(None),
+    };
+    let content = cached_document.borrow_input();
+
+    let interner = get_interner();
+    let namespace = extract_namespace_from_uri(&uri, &cached_document.root_dir)
+        .unwrap_or_else(|| "game".to_string());
+    let namespace = interner.get_or_intern(namespace);
+
+    let mut symbols = Vec::new();
+
+    for item in &ast.items {
+        if let AstEntityItem::Expression(expression) = item
+            && expression.key.raw_value().starts_with('@')
+        {
+            symbols.push(new_symbol(
+                expression.key.raw_value().to_string(),
+                SymbolKind::CONSTANT,
+                None,
+                span_to_lsp_range(expression.span_range(), content),
+                span_to_lsp_range(expression.key.span_range(), content),
+                None,
+            ));
+        }
+    }
+
+    for definition in find_module_definitions(ast, namespace) {
+        let type_name = definition
+            .type_name
+            .map(|type_name| interner.resolve(&type_name).to_string());
+
+        symbols.push(new_symbol(
+            interner.resolve(&definition.name).to_string(),
+            definition_symbol_kind(definition.type_name),
+            type_name,
+            span_to_lsp_range(definition.expression.span_range(), content),
+            span_to_lsp_range(definition.name_range, content),
+            Some(block_symbols(definition.entity, content)),
+        ));
+    }
+
+    symbols.sort_by_key(|symbol| symbol.range.start);
+
+    Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+}
+
+/// Pick an icon for a definition based on its CWT type
+pub fn definition_symbol_kind(type_name: Option<Spur>) -> SymbolKind {
+    let Some(type_name) = type_name else {
+        return SymbolKind::OBJECT;
+    };
+
+    match get_interner().resolve(&type_name) {
+        name if name.ends_with("event") => SymbolKind::EVENT,
+        "scripted_effect" | "scripted_trigger" => SymbolKind::FUNCTION,
+        _ => SymbolKind::CLASS,
+    }
+}
+
+/// Nested blocks of an entity, as child symbols
+fn block_symbols(entity: &AstEntity, content: &str) -> Vec<DocumentSymbol> {
+    entity
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            AstEntityItem::Expression(expression) => match &expression.value {
+                AstValue::Entity(child) => Some(new_symbol(
+                    expression.key.raw_value().to_string(),
+                    SymbolKind::OBJECT,
+                    None,
+                    span_to_lsp_range(expression.span_range(), content),
+                    span_to_lsp_range(expression.key.span_range(), content),
+                    Some(block_symbols(child, content)),
+                )),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn new_symbol(
+    name: String,
+    kind: SymbolKind,
+    detail: Option<String>,
+    range: Range,
+    selection_range: Range,
+    children: Option<Vec<DocumentSymbol>>,
+) -> DocumentSymbol {
+    #[allow(deprecated)]
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: children.filter(|children| !children.is_empty()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cw_parser::AstModuleCell;
+
+    #[test]
+    fn test_nested_blocks_become_children() {
+        let input = "tech_foo = {\n    cost = 10\n    potential = {\n        always = yes\n        OR = { always = no }\n    }\n}\n";
+        let module = AstModuleCell::from_input(input.to_string());
+        let ast = module.borrow_dependent().as_ref().unwrap();
+
+        let AstEntityItem::Expression(expression) = &ast.items[0] else {
+            panic!("expected an expression");
+        };
+        let AstValue::Entity(entity) = &expression.value else {
+            panic!("expected an entity");
+        };
+
+        let symbols = block_symbols(entity, input);
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "potential");
+
+        let children = symbols[0].children.as_ref().unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].name, "OR");
+        assert_eq!(children[0].selection_range.start, Position::new(4, 8));
+    }
+}
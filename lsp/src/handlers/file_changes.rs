@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+
+use cw_model::{GameMod, Module};
+use tower_lsp::Client;
+use tower_lsp::lsp_types::*;
+
+use crate::CwLspServer;
+use crate::base_game::game;
+use crate::handlers::cache::game_data::ModDataCache;
+use crate::handlers::cache::{
+    DefinitionIndex, LocalisationCache, SpriteCache, update_file_in_index,
+};
+use crate::handlers::diagnostics::generate_diagnostics;
+use crate::handlers::utils::log_message_sync;
+use crate::interner::get_interner;
+
+/// Reloads rebuild shared caches, so only one runs at a time
+static RELOAD_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn did_change_watched_files(server: &CwLspServer, params: DidChangeWatchedFilesParams) {
+    let documents = server.documents.read().unwrap();
+
+    let paths: Vec<PathBuf> = params
+        .changes
+        .into_iter()
+        // Changes to open documents are picked up when they are saved
+        .filter(|event| {
+            event.typ != FileChangeType::CHANGED || !documents.contains_key(event.uri.as_str())
+        })
+        .filter_map(|event| event.uri.to_file_path().ok())
+        .collect();
+
+    drop(documents);
+
+    if !paths.is_empty() {
+        reload_files(server, paths);
+    }
+}
+
+/// Reload mod files that changed on disk into the mod data, file index, definition index and
+/// localisation, then refresh the diagnostics of all open documents
+pub fn reload_files(server: &CwLspServer, paths: Vec<PathBuf>) {
+    let client = server.client.clone();
+    let documents = server.documents.clone();
+    let mod_cache = server.mod_cache.clone();
+
+    tokio::spawn(async move {
+        let reload_client = client.clone();
+        let reloaded = tokio::task::spawn_blocking(move || {
+            reload_mod_files(&reload_client, &mod_cache, &paths)
+        })
+        .await
+        .unwrap_or(false);
+
+        if !reloaded {
+            return;
+        }
+
+        let uris: Vec<String> = documents.read().unwrap().keys().cloned().collect();
+        for uri in uris {
+            generate_diagnostics(&client, &documents, &uri).await;
+        }
+    });
+}
+
+/// Re-ingest the modules and localisation files backing the given paths into the mods they belong
+/// to, returning whether anything was reloaded
+fn reload_mod_files(
+    client: &Client,
+    mod_cache: &RwLock<HashMap<PathBuf, GameMod>>,
+    paths: &[PathBuf],
+) -> bool {
+    let _guard = RELOAD_LOCK.lock().unwrap();
+
+    let glob_patterns = crate::base_game::game::get_glob_patterns();
+    let mut mod_cache = mod_cache.write().unwrap();
+    let mut changed_namespaces = HashSet::new();
+    let mut reloaded_files = 0;
+
+    for path in paths {
+        update_file_in_index(path);
+
+        let Some((mod_root, game_mod)) = mod_cache
+            .iter_mut()
+            .find(|(mod_root, _)| path.starts_with(mod_root))
+        else {
+            continue;
+        };
+
+        let Ok(relative_path) = path.strip_prefix(mod_root) else {
+            continue;
+        };
+
+        if relative_path.starts_with(game::get_localisation_folder())
+            && LocalisationCache::reload_file(path)
+        {
+            reloaded_files += 1;
+            continue;
+        }
+
+        if !GameMod::is_module_path(&relative_path.to_string_lossy(), &glob_patterns) {
+            continue;
+        }
+
+        let (namespace, module_name) = Module::get_module_info(path, mod_root);
+
+        if path.is_file() {
+            match Module::from_file(path, mod_root, get_interner()) {
+                Ok(module) => {
+                    game_mod.remove_module(&namespace, &module_name);
+                    game_mod.push(module);
+                }
+                Err(e) => {
+                    // Keep the last version that parsed, the parse error is reported as a
+                    // diagnostic once the file is opened
+                    log_message_sync(
+                        client,
+                        MessageType::WARNING,
+                        format!("Failed to reload {}: {}", path.display(), e),
+                    );
+                    continue;
+                }
+            }
+        } else if game_mod.remove_module(&namespace, &module_name).is_none() {
+            continue;
+        }
+
+        DefinitionIndex::reindex_file(mod_root, &namespace, path);
+        SpriteCache::reindex_file(mod_root, &namespace, path);
+        changed_namespaces.insert(namespace);
+        reloaded_files += 1;
+    }
+
+    if reloaded_files == 0 {
+        return false;
+    }
+
+    if !changed_namespaces.is_empty() {
+        let mods: Vec<&GameMod> = mod_cache.values().collect();
+        ModDataCache::reload_namespaces(changed_namespaces.iter().map(String::as_str), &mods);
+    }
+
+    log_message_sync(
+        client,
+        MessageType::INFO,
+        format!("Reloaded {} changed files", reloaded_files),
+    );
+
+    true
+}
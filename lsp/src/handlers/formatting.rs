@@ -5,6 +5,7 @@ use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 
 use crate::handlers::document_cache::DocumentCache;
+use crate::handlers::utils::localisation_path;
 
 pub fn document_formatting(
     _client: &Client,
@@ -13,6 +14,10 @@ pub fn document_formatting(
     params: DocumentFormattingParams,
 ) -> Result<Option<Vec<TextEdit>>> {
     let uri = params.text_document.uri.to_string();
+    // The script formatter would mangle localisation files
+    if localisation_path(&uri).is_some() {
+        return Ok(None);
+    }
     let documents = documents.read().expect("Failed to read documents");
 
     let Some(content) = documents.get(&uri) else {
@@ -48,6 +53,10 @@ pub fn document_range_formatting(
     params: DocumentRangeFormattingParams,
 ) -> Result<Option<Vec<TextEdit>>> {
     let uri = params.text_document.uri.to_string();
+    // The script formatter would mangle localisation files
+    if localisation_path(&uri).is_some() {
+        return Ok(None);
+    }
     let documents = documents.read().expect("Failed to read documents");
 
     let Some(content) = documents.get(&uri) else {
@@ -4,19 +4,25 @@ use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, jsonrpc::Result};
 
 use crate::handlers::cache::types::TypeInfo;
-use crate::handlers::cache::{TypeCache, get_entity_property_type_from_ast};
+use crate::handlers::cache::{
+    DefinitionLocalisation, LocalisationCache, SpriteCache, TypeCache, definition_localisation,
+    find_module_definitions, get_entity_property_type_from_ast, is_sprite_name,
+};
 use crate::handlers::common_validation::{
     NamespaceValidationResult, apply_file_level_subtype_narrowing, build_hover_response,
     detect_skip_root_key_container, filter_and_narrow_entity_type, find_entity_in_module,
     find_nested_entity_in_container, is_type_per_file_namespace, validate_namespace_and_caches,
 };
+use crate::handlers::cursor::find_cursor_target;
+use crate::handlers::modifiers::get_modifier_categories;
 use crate::interner::get_interner;
 
 use super::document_cache::DocumentCache;
-use super::scoped_type::PropertyNavigationResult;
+use super::scoped_type::{CwtTypeOrSpecialRef, PropertyNavigationResult, ScopedType};
 use super::utils::position_to_offset;
-use cw_model::entity_from_module_ast;
-use cw_parser::{AstEntity, AstExpression, AstNode, AstValue, AstVisitor};
+use cw_model::{CwtType, LocalisationIndex, SimpleType, entity_from_module_ast};
+use cw_parser::{AstEntity, AstExpression, AstModule, AstNode, AstValue, AstVisitor};
+use lasso::Spur;
 
 /// A visitor that builds property paths for hover functionality
 struct PropertyPathBuilder<'a, 'ast>
@@ -29,6 +35,10 @@ where
     found_entity_context: Option<&'ast AstEntity<'a>>,
     found_container_key: Option<String>,
     found_entity_key: Option<String>,
+    /// The string value of the found property, which may be a localisation key
+    found_string_value: Option<&'a str>,
+    /// Whether the position is on the value of the found property rather than its key
+    found_in_value: bool,
     original_input: &'a str,
 }
 
@@ -41,6 +51,8 @@ impl<'a, 'ast> PropertyPathBuilder<'a, 'ast> {
             found_entity_context: None,
             found_container_key: None,
             found_entity_key: None,
+            found_string_value: None,
+            found_in_value: false,
             original_input: input,
         }
     }
@@ -68,11 +80,19 @@ where
 {
     fn visit_expression(&mut self, node: &'ast AstExpression<'a>) -> () {
         let key_span = node.key.span(&self.original_input);
+        let string_value = match &node.value {
+            AstValue::String(string) => Some(string.raw_value()),
+            _ => None,
+        };
+        let in_key = self.position_offset >= key_span.start.offset
+            && self.position_offset <= key_span.end.offset;
+        let in_string_value = string_value.is_some() && {
+            let value_span = node.value.span_range();
+            self.position_offset >= value_span.start && self.position_offset <= value_span.end
+        };
 
-        // Check if the position is within this property's key
-        if self.position_offset >= key_span.start.offset
-            && self.position_offset <= key_span.end.offset
-        {
+        // Check if the position is within this property's key, or its value for string values
+        if in_key || in_string_value {
             let full_path = if self.current_path.is_empty() {
                 node.key.raw_value().to_string()
             } else {
@@ -80,6 +100,8 @@ where
             };
 
             self.found_property = Some(full_path);
+            self.found_string_value = string_value;
+            self.found_in_value = !in_key;
 
             // Set container and entity context based on the path level
             if self.current_path.is_empty() {
@@ -166,6 +188,14 @@ pub fn hover(
         None => return Ok(None),
     };
 
+    // Sprites can be used anywhere, including files without type information
+    if let Ok(ast) = cached_document.borrow_ast()
+        && let Some(target) = find_cursor_target(ast, offset)
+        && let Some(hover) = sprite_hover(&target.text)
+    {
+        return Ok(Some(hover));
+    }
+
     // Validate namespace and caches using common validation
     let validation_context = match validate_namespace_and_caches(&uri, &cached_document.root_dir) {
         NamespaceValidationResult::Valid(context) => context,
@@ -443,7 +473,47 @@ pub fn hover(
             }
         };
 
-        if let Some(type_info) = type_info {
+        if let Some(mut type_info) = type_info {
+            let localisation = cached_document
+                .borrow_ast()
+                .ok()
+                .and_then(|ast| definition_localisation_hover(ast, namespace, offset))
+                .or_else(|| {
+                    field_localisation_hover(
+                        type_info.scoped_type.as_deref(),
+                        builder.found_string_value,
+                    )
+                });
+
+            if builder.found_in_value {
+                // Values only have a hover when they are localisation keys
+                let Some(localisation) = localisation else {
+                    return Ok(None);
+                };
+                type_info = TypeInfo {
+                    property_path: type_info.property_path,
+                    scoped_type: None,
+                    documentation: Some(localisation),
+                    source_info: None,
+                };
+            } else if let Some(localisation) = localisation {
+                type_info.documentation = Some(match type_info.documentation {
+                    Some(documentation) => format!("{}\n\n{}", documentation, localisation),
+                    None => localisation,
+                });
+            }
+
+            // Modifiers are keys inside blocks, never definition names
+            if !builder.found_in_value
+                && !is_top_level_key
+                && let Some(modifier) = property_path.rsplit('.').next().and_then(modifier_hover)
+            {
+                type_info.documentation = Some(match type_info.documentation {
+                    Some(documentation) => format!("{}\n\n{}", documentation, modifier),
+                    None => modifier,
+                });
+            }
+
             // Use common hover response builder
             if let Some(hover) = build_hover_response(type_info, &type_cache) {
                 return Ok(Some(hover));
@@ -453,3 +523,292 @@ pub fn hover(
 
     Ok(None)
 }
+
+/// The localisation text of the definition whose name is at the offset, e.g. the name and
+/// description of a technology, in the first configured language
+fn definition_localisation_hover(
+    ast: &AstModule<'_>,
+    namespace: Spur,
+    offset: usize,
+) -> Option<String> {
+    if !LocalisationCache::is_initialized() {
+        return None;
+    }
+
+    let interner = get_interner();
+    let type_cache = TypeCache::get()?;
+    let definition = find_module_definitions(ast, namespace)
+        .into_iter()
+        .find(|definition| {
+            let key_range = definition.expression.key.span_range();
+            (offset >= definition.name_range.start && offset <= definition.name_range.end)
+                || (offset >= key_range.start && offset <= key_range.end)
+        })?;
+    let type_def = type_cache
+        .get_cwt_analyzer()
+        .get_type(definition.type_name?)?;
+
+    let localisation = definition_localisation(
+        type_def,
+        interner.resolve(&definition.name),
+        definition.entity,
+    );
+    let language = LocalisationCache::languages().first()?;
+
+    localisation_lines(localisation, &LocalisationCache::get(), language)
+}
+
+/// The defined texts of a definition's localisation in `language`, primary (usually the name)
+/// first
+fn localisation_lines(
+    mut localisation: Vec<DefinitionLocalisation>,
+    index: &LocalisationIndex,
+    language: &str,
+) -> Option<String> {
+    let interner = get_interner();
+    localisation.sort_by_key(|localisation| !localisation.primary);
+
+    let lines: Vec<String> = localisation
+        .iter()
+        .filter_map(|localisation| {
+            let entry = index.get(language, interner.get_or_intern(&localisation.key))?;
+            Some(format!(
+                "**{}** (`{}`): {}",
+                interner.resolve(&localisation.name),
+                localisation.key,
+                escape_markdown(&entry.text)
+            ))
+        })
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n\n"))
+    }
+}
+
+/// The categories of a modifier, with the scopes each of them applies to
+fn modifier_hover(key: &str) -> Option<String> {
+    let interner = get_interner();
+    let categories = get_modifier_categories(interner.get_or_intern(key))?;
+    if categories.is_empty() {
+        return None;
+    }
+    let analyzer = TypeCache::get()?.get_cwt_analyzer();
+
+    let lines: Vec<String> = categories
+        .iter()
+        .map(|category| {
+            let scopes = analyzer
+                .get_modifier_category(*category)
+                .map(|definition| {
+                    definition
+                        .supported_scopes
+                        .iter()
+                        .map(|scope| interner.resolve(scope).to_string())
+                        .collect::<Vec<String>>()
+                })
+                .unwrap_or_default();
+            if scopes.is_empty() {
+                format!("- {}", interner.resolve(category))
+            } else {
+                format!("- {}: {}", interner.resolve(category), scopes.join(", "))
+            }
+        })
+        .collect();
+
+    Some(format!("**Modifier categories**\n\n{}", lines.join("\n")))
+}
+
+/// The definition of a `GFX_` sprite, with the texture it uses
+fn sprite_hover(name: &str) -> Option<Hover> {
+    if !is_sprite_name(name) {
+        return None;
+    }
+
+    let interner = get_interner();
+    let definitions = SpriteCache::get_definitions(name);
+    if definitions.is_empty() {
+        return None;
+    }
+
+    let sections: Vec<String> = definitions
+        .iter()
+        .map(|sprite| {
+            let mut lines = vec![format!(
+                "**{}** (`{}`)",
+                name,
+                interner.resolve(&sprite.kind)
+            )];
+            if let Some(texture_file) = &sprite.texture_file {
+                lines.push(format!("**Texture**: `{}`", texture_file));
+            }
+            if let Some(frames) = sprite.frames {
+                lines.push(format!("**Frames**: {}", frames));
+            }
+            lines.push(format!("*Defined in {}*", sprite.location.path.display()));
+            lines.join("\n\n")
+        })
+        .collect();
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: sections.join("\n\n---\n\n"),
+        }),
+        range: None,
+    })
+}
+
+/// The text of a localisation key used as the value of a localisation field
+fn field_localisation_hover(
+    scoped_type: Option<&ScopedType>,
+    value: Option<&str>,
+) -> Option<String> {
+    if !LocalisationCache::is_initialized() {
+        return None;
+    }
+
+    let value = value?;
+    if !is_localisation_type(scoped_type?.cwt_type_for_matching()) {
+        return None;
+    }
+
+    let language = LocalisationCache::languages().first()?;
+    field_localisation_text(value, &LocalisationCache::get(), language)
+}
+
+fn field_localisation_text(key: &str, index: &LocalisationIndex, language: &str) -> Option<String> {
+    let entry = index.get(language, get_interner().get_or_intern(key))?;
+    Some(format!(
+        "**Localisation** (`{}`): {}",
+        key,
+        escape_markdown(&entry.text)
+    ))
+}
+
+/// Escape localisation text for Markdown, so that e.g. `[Root.GetName]` commands and `*`s show
+/// up as written
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(
+            ch,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '!' | '&'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+fn is_localisation_type(cwt_type: CwtTypeOrSpecialRef) -> bool {
+    match cwt_type {
+        CwtTypeOrSpecialRef::Simple(simple_type) => matches!(
+            simple_type,
+            SimpleType::Localisation
+                | SimpleType::LocalisationSynced
+                | SimpleType::LocalisationInline
+        ),
+        CwtTypeOrSpecialRef::Union(types) => types.iter().any(|union_type| {
+            matches!(
+                &**union_type,
+                CwtType::Simple(
+                    SimpleType::Localisation
+                        | SimpleType::LocalisationSynced
+                        | SimpleType::LocalisationInline
+                )
+            )
+        }),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn index() -> LocalisationIndex {
+        let mut index = LocalisationIndex::new("localisation");
+        index.add_file(
+            Path::new("/mod/localisation/english/technology_l_english.yml"),
+            "l_english:\n tech_lasers:0 \"*Red* Lasers\"\n tech_lasers_desc:0 \"Fires at [Root.GetName]\"\n",
+            get_interner(),
+        );
+        index
+    }
+
+    fn requirement(name: &str, key: &str, primary: bool) -> DefinitionLocalisation {
+        DefinitionLocalisation {
+            name: get_interner().get_or_intern(name),
+            key: key.to_string(),
+            required: true,
+            primary,
+        }
+    }
+
+    #[test]
+    fn test_is_localisation_type() {
+        assert!(is_localisation_type(CwtTypeOrSpecialRef::Simple(
+            &SimpleType::Localisation
+        )));
+        assert!(is_localisation_type(CwtTypeOrSpecialRef::Simple(
+            &SimpleType::LocalisationInline
+        )));
+        assert!(!is_localisation_type(CwtTypeOrSpecialRef::Simple(
+            &SimpleType::Scalar
+        )));
+
+        let union = vec![
+            Arc::new(CwtType::Simple(SimpleType::Int)),
+            Arc::new(CwtType::Simple(SimpleType::LocalisationSynced)),
+        ];
+        assert!(is_localisation_type(CwtTypeOrSpecialRef::Union(&union)));
+        let union = vec![
+            Arc::new(CwtType::Simple(SimpleType::Int)),
+            Arc::new(CwtType::Simple(SimpleType::Scalar)),
+        ];
+        assert!(!is_localisation_type(CwtTypeOrSpecialRef::Union(&union)));
+    }
+
+    #[test]
+    fn test_localisation_lines_put_primary_first_and_escape_text() {
+        let localisation = vec![
+            requirement("desc", "tech_lasers_desc", false),
+            requirement("name", "tech_lasers", true),
+            requirement("flavour", "tech_lasers_flavour", false),
+        ];
+
+        assert_eq!(
+            localisation_lines(localisation, &index(), "english").as_deref(),
+            Some(
+                "**name** (`tech_lasers`): \\*Red\\* Lasers\n\n\
+                 **desc** (`tech_lasers_desc`): Fires at \\[Root.GetName\\]"
+            )
+        );
+        assert_eq!(
+            localisation_lines(
+                vec![requirement("name", "tech_lasers", true)],
+                &index(),
+                "german"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_field_localisation_text() {
+        assert_eq!(
+            field_localisation_text("TECH_LASERS", &index(), "english").as_deref(),
+            Some("**Localisation** (`TECH_LASERS`): \\*Red\\* Lasers")
+        );
+        assert_eq!(
+            field_localisation_text("tech_missiles", &index(), "english"),
+            None
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use std::time::{Duration, Instant};
 
 use crate::handlers::cache::{
-    EntityRestructurer, FileIndex, FullAnalysis, GameDataCache, TypeCache,
+    EntityRestructurer, FileIndex, FullAnalysis, GameDataCache, LocalisationCache, SpriteCache,
+    TypeCache,
 };
+use crate::handlers::settings::Settings;
 use colored::Colorize;
 
 /// Configuration for the initialization process
@@ -43,12 +45,18 @@ impl CacheInitializer {
         TypeCache::initialize_in_background();
         GameDataCache::initialize_in_background();
         FileIndex::initialize_in_background();
+        LocalisationCache::initialize_in_background();
+        SpriteCache::initialize_in_background();
+
+        // Localisation is only needed up front when diagnostics check it
+        let wait_for_localisation = Settings::global().validate_localisation;
 
         // Wait for caches to be initialized
         let start = Instant::now();
         while !TypeCache::is_initialized()
             || !GameDataCache::is_initialized()
             || !FileIndex::is_initialized()
+            || (wait_for_localisation && !LocalisationCache::is_initialized())
         {
             if let Some(timeout) = config.timeout {
                 if start.elapsed() > timeout {
@@ -1,51 +1,383 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::sync::Arc;
 
 // BaseGame is now accessed through the base_game::game module
 use crate::interner::get_interner;
-use cw_model::{Entity, SpurMap};
+use cw_model::{Entity, Modifier, ModifierSpec, SpurMap, TypeDefinition};
 use lasso::Spur;
 
-use crate::handlers::cache::{GameDataCache, Namespace};
+use crate::handlers::cache::{
+    GameDataCache, ModDataCache, Namespace, TypeCache, matches_type_key_filter,
+    should_skip_root_key,
+};
+use crate::handlers::scoped_type::ScopedType;
 
-/// Integrates modifiers from Stellaris logs into the GameDataCache
-pub fn integrate_modifiers_into_cache(
-    game_data_cache: &mut GameDataCache,
-) -> Result<(), anyhow::Error> {
-    let interner = get_interner();
-    // Load modifiers from Stellaris logs
-    let modifiers = crate::base_game::game::load_modifiers()?;
+/// The artificial namespace the `modifier` type refers to
+const MODIFIERS_NAMESPACE: &str = "game/modifiers";
+
+/// Integrates the base game's modifiers into the GameDataCache: the modifiers from the game logs,
+/// the modifiers listed in the config, and the modifiers generated by definitions like
+/// `job_<job>_add`. Returns the number of modifiers.
+pub fn integrate_modifiers_into_cache(game_data_cache: &mut GameDataCache) -> usize {
+    // The generated modifiers come from the modifier patterns of the CWT types
+    while !TypeCache::is_initialized() {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let type_cache = TypeCache::get().unwrap();
+
+    // The log only exists after running the game with -debug, so it's fine to go without it
+    let mut modifiers = crate::base_game::game::load_modifiers().unwrap_or_else(|e| {
+        eprintln!("Using the modifiers from the config only: {}", e);
+        Vec::new()
+    });
+    modifiers.extend(
+        type_cache
+            .get_cwt_analyzer()
+            .get_modifiers()
+            .values()
+            .cloned(),
+    );
+    modifiers.extend(generated_modifiers(&game_data_cache.namespaces, type_cache));
+
+    let modifiers = merge_modifiers(modifiers);
+    let count = modifiers.len();
+    game_data_cache.namespaces.insert(
+        get_interner().get_or_intern(MODIFIERS_NAMESPACE),
+        modifiers_namespace(&modifiers),
+    );
+    game_data_cache.modifiers = modifiers;
+
+    count
+}
+
+/// Integrates the modifiers generated by the definitions of the loaded mods into the
+/// ModDataCache, replacing the ones generated before
+pub fn integrate_mod_modifiers_into_cache(mod_data_cache: &mut ModDataCache) {
+    let Some(type_cache) = TypeCache::get() else {
+        return;
+    };
+
+    let namespace_key = get_interner().get_or_intern(MODIFIERS_NAMESPACE);
+    mod_data_cache.namespaces.remove(&namespace_key);
+
+    let modifiers = merge_modifiers(generated_modifiers(&mod_data_cache.namespaces, type_cache));
+    if !modifiers.is_empty() {
+        mod_data_cache
+            .namespaces
+            .insert(namespace_key, modifiers_namespace(&modifiers));
+    }
+    mod_data_cache.modifiers = modifiers;
+}
 
-    // Create artificial entities for each modifier
-    let mut modifier_entities = SpurMap::new();
-    let mut entity_keys = Vec::new();
+/// The categories of a modifier, from the base game and the loaded mods, or None if it isn't a
+/// known modifier
+pub fn get_modifier_categories(name: Spur) -> Option<Vec<Spur>> {
+    let mut categories: Option<Vec<Spur>> = None;
+    let mod_data_cache = ModDataCache::get().read().unwrap();
+    let sources = [
+        GameDataCache::get().and_then(|cache| cache.modifiers.get(&name)),
+        mod_data_cache.modifiers.get(&name),
+    ];
 
+    for modifier in sources.into_iter().flatten() {
+        let categories = categories.get_or_insert_with(Vec::new);
+        for category in &modifier.categories {
+            if !categories.contains(category) {
+                categories.push(*category);
+            }
+        }
+    }
+
+    categories
+}
+
+/// Merge modifiers that come from more than one source, keeping all of their categories
+fn merge_modifiers(modifiers: Vec<Modifier>) -> SpurMap<Modifier> {
+    let mut merged: SpurMap<Modifier> = SpurMap::new();
     for modifier in modifiers {
+        match merged.get_mut(&modifier.name) {
+            Some(existing) => {
+                for category in modifier.categories {
+                    if !existing.categories.contains(&category) {
+                        existing.categories.push(category);
+                    }
+                }
+            }
+            None => {
+                merged.insert(modifier.name, modifier);
+            }
+        }
+    }
+
+    merged
+}
+
+/// Create the namespace of artificial entities for modifiers
+fn modifiers_namespace(modifiers: &SpurMap<Modifier>) -> Namespace {
+    let mut namespace = Namespace::new();
+    for name in modifiers.keys() {
         // Create an empty entity for the modifier
-        let entity = Entity::new();
+        namespace.entities.insert(name, Arc::new(Entity::new()));
+    }
+    namespace.update_keys();
+
+    namespace
+}
 
-        modifier_entities.insert(modifier.name.clone(), Arc::new(entity));
-        entity_keys.push(modifier.name);
+/// The modifiers generated by the definitions in the given namespaces, from the modifier
+/// patterns of their types
+fn generated_modifiers(namespaces: &SpurMap<Namespace>, type_cache: &TypeCache) -> Vec<Modifier> {
+    let interner = get_interner();
+    let resolver = type_cache.get_resolver();
+    let mut modifiers = Vec::new();
+
+    for (_, type_def) in type_cache.get_cwt_analyzer().get_types().iter() {
+        let spec = &type_def.modifiers;
+        if spec.modifiers.is_empty() && spec.subtypes.is_empty() {
+            continue;
+        }
+        let Some(namespace) = type_def
+            .path
+            .and_then(|path| namespaces.get(&TypeCache::get_actual_namespace(path)))
+        else {
+            continue;
+        };
+
+        let scoped_type = Arc::new(ScopedType::new_cwt(
+            type_def.rules.clone(),
+            Default::default(),
+            None,
+        ));
+        for (name, entity) in type_definitions(namespace, type_def) {
+            let subtypes = if spec.subtypes.is_empty() {
+                HashSet::new()
+            } else {
+                resolver.determine_matching_subtypes(scoped_type.clone(), entity)
+            };
+            modifiers.extend(expand_modifier_patterns(
+                spec,
+                interner.resolve(&name),
+                &subtypes,
+            ));
+        }
     }
 
-    // Create entity keys set
-    let entity_keys_set = Arc::new(entity_keys.iter().cloned().collect::<HashSet<Spur>>());
+    modifiers
+}
+
+/// The definitions of a type in its namespace with their names, descending into the containers
+/// its `skip_root_key` skips like `find_module_definitions` does, and named by its `name_field`
+fn type_definitions<'a>(
+    namespace: &'a Namespace,
+    type_def: &TypeDefinition,
+) -> Vec<(Spur, &'a Entity)> {
+    let interner = get_interner();
+    let mut definitions = Vec::new();
+
+    let mut add_definition = |key: Spur, entity: &'a Entity| {
+        // Other types can share the folder
+        let is_definition = type_def
+            .rule_options
+            .type_key_filter
+            .as_ref()
+            .is_none_or(|filter| matches_type_key_filter(key, filter))
+            && type_def.options.starts_with.is_none_or(|prefix| {
+                interner
+                    .resolve(&key)
+                    .starts_with(interner.resolve(&prefix))
+            });
+        if !is_definition {
+            return;
+        }
 
-    // Create the modifiers namespace
-    let modifiers_namespace = Namespace {
-        entities: modifier_entities,
-        entity_keys,
-        entity_keys_set,
-        scripted_variables: SpurMap::new(),
-        modules: HashMap::new(),
-        values: Vec::new(),
+        let name = type_def
+            .name_field
+            .and_then(|name_field| entity.properties.kv.get(&name_field))
+            .and_then(|values| values.0.first())
+            .and_then(|value| value.value.as_string().copied())
+            .unwrap_or(key);
+        definitions.push((name, entity));
     };
 
-    // Insert the modifiers namespace into the game data cache
-    game_data_cache.namespaces.insert(
-        interner.get_or_intern("game/modifiers"),
-        modifiers_namespace,
-    );
+    for (key, entity) in namespace.entities.iter() {
+        let key = unnumbered_key(key, namespace);
+        if should_skip_root_key(key, &type_def.skip_root_key) {
+            for (child_key, values) in entity.properties.kv.iter() {
+                for value in values.0.iter() {
+                    if let Some(child) = value.value.as_entity() {
+                        add_definition(child_key, child);
+                    }
+                }
+            }
+        } else if type_def.skip_root_key.is_none() {
+            add_definition(key, entity);
+        }
+    }
+
+    definitions
+}
+
+/// The key an entity has in its file. Entities with the same key are numbered in the namespace,
+/// so the second `tile_blockers` container is `tile_blockers_2`.
+fn unnumbered_key(key: Spur, namespace: &Namespace) -> Spur {
+    let interner = get_interner();
+    interner
+        .resolve(&key)
+        .rsplit_once('_')
+        .filter(|(_, number)| number.parse::<usize>().is_ok_and(|number| number > 1))
+        .map(|(base, _)| interner.get_or_intern(base))
+        .filter(|base| namespace.entities.contains_key(base))
+        .unwrap_or(key)
+}
+
+/// Expand a type's modifier patterns for one of its definitions, e.g. `job_$_add` is
+/// `job_miner_add` for the job `miner`. Subtype patterns only apply to definitions of that
+/// subtype.
+fn expand_modifier_patterns(
+    spec: &ModifierSpec,
+    name: &str,
+    subtypes: &HashSet<Spur>,
+) -> Vec<Modifier> {
+    let interner = get_interner();
+
+    spec.modifiers
+        .iter()
+        .chain(
+            spec.subtypes
+                .iter()
+                .filter(|(subtype, _)| subtypes.contains(subtype))
+                .flat_map(|(_, patterns)| patterns.iter()),
+        )
+        .map(|(pattern, category)| {
+            let modifier = interner.resolve(&pattern).replace('$', name);
+            Modifier::new(interner.get_or_intern(modifier), vec![*category])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cw_model::Value;
 
-    Ok(())
+    #[test]
+    fn test_expand_modifier_patterns() {
+        let interner = get_interner();
+        let mut spec = ModifierSpec::default();
+        spec.modifiers.insert(
+            interner.get_or_intern("job_$_add"),
+            interner.get_or_intern("planet"),
+        );
+        let mut capital_patterns = SpurMap::new();
+        capital_patterns.insert(
+            interner.get_or_intern("$_capital_mult"),
+            interner.get_or_intern("country"),
+        );
+        spec.subtypes
+            .insert(interner.get_or_intern("capital"), capital_patterns);
+
+        let modifiers = expand_modifier_patterns(&spec, "miner", &HashSet::new());
+        assert_eq!(
+            modifiers,
+            vec![Modifier::new(
+                interner.get_or_intern("job_miner_add"),
+                vec![interner.get_or_intern("planet")]
+            )]
+        );
+
+        let subtypes = HashSet::from([interner.get_or_intern("capital")]);
+        let mut names: Vec<String> = expand_modifier_patterns(&spec, "miner", &subtypes)
+            .iter()
+            .map(|modifier| interner.resolve(&modifier.name).to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["job_miner_add", "miner_capital_mult"]);
+    }
+
+    #[test]
+    fn test_merge_modifiers() {
+        let interner = get_interner();
+        let name = interner.get_or_intern("pop_happiness");
+        let pops = interner.get_or_intern("Pops");
+        let planets = interner.get_or_intern("Planets");
+
+        let merged = merge_modifiers(vec![
+            Modifier::new(name, vec![pops]),
+            Modifier::new(name, vec![pops, planets]),
+        ]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged.get(&name).unwrap().categories, vec![pops, planets]);
+    }
+
+    #[test]
+    fn test_generated_modifiers_use_definition_names() {
+        TypeCache::set_for_test(
+            r#"
+types = {
+    type[deposit] = {
+        path = "game/common/deposits"
+        name_field = "key"
+        modifiers = {
+            $_deposit_mult = planet
+        }
+    }
+    type[tile_blocker] = {
+        path = "game/common/tile_blockers"
+        skip_root_key = tile_blockers
+        modifiers = {
+            $_clear_speed = planet
+        }
+    }
+}
+"#,
+        );
+        let interner = get_interner();
+
+        let mut deposits = Namespace::new();
+        deposits.entities.insert(
+            interner.get_or_intern("d_minerals"),
+            Arc::new(Entity::new().with_property(
+                "key",
+                Value::String(interner.get_or_intern("minerals_big")),
+                interner,
+            )),
+        );
+
+        // The second container with the same key is numbered in the namespace
+        let mut tile_blockers = Namespace::new();
+        for (container, blocker) in [
+            ("tile_blockers", "mountains"),
+            ("tile_blockers_2", "crater"),
+        ] {
+            tile_blockers.entities.insert(
+                interner.get_or_intern(container),
+                Arc::new(Entity::new().with_property(
+                    blocker,
+                    Value::Entity(Entity::new()),
+                    interner,
+                )),
+            );
+        }
+
+        let mut namespaces = SpurMap::new();
+        namespaces.insert(interner.get_or_intern("game/common/deposits"), deposits);
+        namespaces.insert(
+            interner.get_or_intern("game/common/tile_blockers"),
+            tile_blockers,
+        );
+
+        let mut names: Vec<String> = generated_modifiers(&namespaces, TypeCache::get().unwrap())
+            .iter()
+            .map(|modifier| interner.resolve(&modifier.name).to_string())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "crater_clear_speed",
+                "minerals_big_deposit_mult",
+                "mountains_clear_speed"
+            ]
+        );
+    }
 }
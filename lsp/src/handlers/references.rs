@@ -0,0 +1,240 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use cw_parser::AstModuleCell;
+use lasso::Spur;
+use rayon::prelude::*;
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, jsonrpc::Result};
+
+use crate::handlers::cache::{DefinitionIndex, TypeCache, find_word_occurrences};
+use crate::handlers::common_validation::{
+    NamespaceValidationResult, validate_namespace_and_caches,
+};
+use crate::handlers::cursor::find_cursor_target;
+use crate::handlers::definition::{
+    DefinitionTarget, find_definition_target_ranges, find_definition_targets, get_target_locations,
+    to_lsp_location,
+};
+use crate::handlers::diagnostics::util::span_to_lsp_range;
+use crate::interner::get_interner;
+
+use super::document_cache::DocumentCache;
+use super::utils::position_to_offset;
+
+pub fn find_references(
+    _client: &Client,
+    documents: &Arc<RwLock<HashMap<String, String>>>,
+    document_cache: &DocumentCache,
+    params: ReferenceParams,
+) -> Result<Option<Vec<Location>>> {
+    let uri = params.text_document_position.text_document.uri.to_string();
+    let position = params.text_document_position.position;
+
+    // A snapshot, so that edits aren't blocked while every file of the workspace is searched
+    let documents = documents.read().expect("Failed to read documents").clone();
+    let content = match documents.get(&uri) {
+        Some(content) => content,
+        None => return Ok(None),
+    };
+
+    let cached_document = match document_cache.get(&uri) {
+        Some(cached_document) => cached_document,
+        None => return Ok(None),
+    };
+
+    let ast = match cached_document.borrow_ast() {
+        Ok(ast) => ast,
+        Err(_) => return Ok(None),
+    };
+
+    let offset = position_to_offset(content, position);
+    let target = match find_cursor_target(ast, offset) {
+        Some(target) => target,
+        None => return Ok(None),
+    };
+
+    let namespace_context = match validate_namespace_and_caches(&uri, &cached_document.root_dir) {
+        NamespaceValidationResult::Valid(context) => {
+            Some((context.namespace, context.namespace_type))
+        }
+        _ => None,
+    };
+
+    let definition_targets = find_definition_targets(ast, namespace_context, &target);
+    if definition_targets.is_empty() {
+        return Ok(None);
+    }
+
+    let mut locations = Vec::new();
+    let mut names: Vec<Spur> = definition_targets
+        .iter()
+        .map(|target| target.name())
+        .collect();
+    names.dedup();
+    for name in names {
+        let targets: Vec<DefinitionTarget> = definition_targets
+            .iter()
+            .filter(|target| target.name() == name)
+            .copied()
+            .collect();
+        locations.extend(find_usages(
+            get_interner().resolve(&name),
+            &targets,
+            &documents,
+            document_cache,
+        ));
+    }
+
+    let declarations: Vec<Location> = definition_targets
+        .iter()
+        .flat_map(|target| get_target_locations(*target))
+        .filter_map(|location| to_lsp_location(&location))
+        .collect();
+
+    // Definition keys resolve to the same target as their usages, so they are found by the
+    // usage search as well and have to be filtered out or added explicitly
+    locations.retain(|location| !declarations.contains(location));
+    if params.context.include_declaration {
+        locations.extend(declarations);
+    }
+
+    if locations.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(locations))
+    }
+}
+
+/// A file that might contain usages of a definition
+struct CandidateFile {
+    url: Url,
+    namespace: Option<Spur>,
+    /// Set for files that aren't open in the editor and have to be read from disk
+    path: Option<PathBuf>,
+}
+
+/// Find every place in the workspace where `text` is used as one of `targets`, returning the
+/// range of `text` itself even when it is part of a longer token. Files are pre-filtered by a
+/// plain text search and only parsed and type checked when they contain it.
+pub fn find_usages(
+    text: &str,
+    targets: &[DefinitionTarget],
+    documents: &HashMap<String, String>,
+    document_cache: &DocumentCache,
+) -> Vec<Location> {
+    let mut candidates = Vec::new();
+    let mut indexed_uris = HashSet::new();
+
+    for (namespace, path) in DefinitionIndex::get_all_files() {
+        let Ok(url) = Url::from_file_path(&path) else {
+            continue;
+        };
+        indexed_uris.insert(url.to_string());
+
+        // Open documents take precedence over what is on disk
+        let path = (!documents.contains_key(url.as_str())).then_some(path);
+        candidates.push(CandidateFile {
+            url,
+            namespace: Some(namespace),
+            path,
+        });
+    }
+
+    // Open documents that haven't been indexed, e.g. files of a mod that isn't loaded
+    for uri in documents.keys() {
+        if indexed_uris.contains(uri) {
+            continue;
+        }
+        let Ok(url) = Url::parse(uri) else {
+            continue;
+        };
+        let namespace =
+            document_cache.get(uri).and_then(
+                |cached_document| match validate_namespace_and_caches(
+                    uri,
+                    &cached_document.root_dir,
+                ) {
+                    NamespaceValidationResult::Valid(context) => Some(context.namespace),
+                    _ => None,
+                },
+            );
+        candidates.push(CandidateFile {
+            url,
+            namespace,
+            path: None,
+        });
+    }
+
+    let mut locations: Vec<Location> = candidates
+        .par_iter()
+        .flat_map(|candidate| {
+            let content = match &candidate.path {
+                Some(path) => std::fs::read_to_string(path).ok(),
+                None => documents.get(candidate.url.as_str()).cloned(),
+            };
+            match content {
+                Some(content) => find_usages_in_content(candidate, &content, text, targets),
+                None => Vec::new(),
+            }
+        })
+        .collect();
+
+    locations.sort_by(|a, b| (a.uri.as_str(), a.range.start).cmp(&(b.uri.as_str(), b.range.start)));
+    locations.dedup();
+    locations
+}
+
+fn find_usages_in_content(
+    candidate: &CandidateFile,
+    content: &str,
+    text: &str,
+    targets: &[DefinitionTarget],
+) -> Vec<Location> {
+    let occurrences = find_word_occurrences(content, text);
+    if occurrences.is_empty() {
+        return Vec::new();
+    }
+
+    let module = AstModuleCell::from_input(content.to_string());
+    let Ok(ast) = module.borrow_dependent().as_ref() else {
+        return Vec::new();
+    };
+
+    let namespace_context = candidate.namespace.and_then(|namespace| {
+        TypeCache::get()?
+            .get_namespace_type(namespace, Some(candidate.url.as_str()))
+            .map(|namespace_type| (namespace, namespace_type))
+    });
+
+    // Several occurrences can fall within the same token, e.g. `in` within `building_in_desc`,
+    // so each token is resolved once and only the ranges naming one of the targets are kept
+    let mut seen_tokens = HashSet::new();
+    let mut ranges = Vec::new();
+    for offset in occurrences {
+        let Some(cursor_target) = find_cursor_target(ast, offset) else {
+            continue;
+        };
+        if !seen_tokens.insert(cursor_target.span.clone()) {
+            continue;
+        }
+
+        ranges.extend(
+            find_definition_target_ranges(ast, namespace_context.clone(), &cursor_target)
+                .into_iter()
+                .filter(|(found, _)| targets.contains(found))
+                .map(|(_, range)| range),
+        );
+    }
+    ranges.sort_by_key(|range| range.start);
+    ranges.dedup();
+
+    ranges
+        .into_iter()
+        .map(|range| Location {
+            uri: candidate.url.clone(),
+            range: span_to_lsp_range(range, content),
+        })
+        .collect()
+}
@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use tower_lsp::Client;
+use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
+use tower_lsp::lsp_types::*;
+
+use crate::handlers::cache::{DefinitionLocation, DefinitionSource};
+use crate::handlers::common_validation::{
+    NamespaceValidationResult, validate_namespace_and_caches,
+};
+use crate::handlers::cursor::find_cursor_target;
+use crate::handlers::definition::{
+    DefinitionTarget, find_definition_target_ranges, get_target_locations, to_lsp_location,
+};
+use crate::handlers::diagnostics::util::span_to_lsp_range;
+use crate::handlers::mod_detection::is_base_game_file;
+use crate::handlers::references::find_usages;
+use crate::interner::get_interner;
+
+use super::document_cache::DocumentCache;
+use super::utils::position_to_offset;
+
+/// A definition that can be renamed, found under the cursor
+struct RenameTarget {
+    target: DefinitionTarget,
+    /// Byte range of the definition key within the document, which may be only part of the
+    /// token under the cursor for prefixed or suffixed references
+    range: Range<usize>,
+}
+
+pub fn prepare_rename(
+    _client: &Client,
+    documents: &Arc<RwLock<HashMap<String, String>>>,
+    document_cache: &DocumentCache,
+    params: TextDocumentPositionParams,
+) -> Result<Option<PrepareRenameResponse>> {
+    let uri = params.text_document.uri.to_string();
+
+    let documents = documents.read().expect("Failed to read documents");
+    let content = match documents.get(&uri) {
+        Some(content) => content,
+        None => return Ok(None),
+    };
+
+    let rename_target = find_rename_target(content, &uri, document_cache, params.position)?;
+
+    Ok(Some(prepare_rename_response(content, &rename_target)))
+}
+
+/// The range to rename and the current name, as spelled in the document
+fn prepare_rename_response(content: &str, rename_target: &RenameTarget) -> PrepareRenameResponse {
+    PrepareRenameResponse::RangeWithPlaceholder {
+        range: span_to_lsp_range(rename_target.range.clone(), content),
+        placeholder: content[rename_target.range.clone()].to_string(),
+    }
+}
+
+pub fn rename(
+    _client: &Client,
+    documents: &Arc<RwLock<HashMap<String, String>>>,
+    document_cache: &DocumentCache,
+    params: RenameParams,
+) -> Result<Option<WorkspaceEdit>> {
+    let uri = params.text_document_position.text_document.uri.to_string();
+    let new_name = params.new_name.trim();
+
+    if new_name.is_empty()
+        || new_name
+            .chars()
+            .any(|ch| ch.is_whitespace() || matches!(ch, '"' | '{' | '}' | '=' | '#'))
+    {
+        return Err(Error::invalid_params(format!(
+            "'{}' is not a valid name",
+            params.new_name
+        )));
+    }
+
+    // A snapshot, so that edits aren't blocked while every file of the workspace is searched
+    let documents = documents.read().expect("Failed to read documents").clone();
+    let content = match documents.get(&uri) {
+        Some(content) => content,
+        None => return Ok(None),
+    };
+
+    let rename_target = find_rename_target(
+        content,
+        &uri,
+        document_cache,
+        params.text_document_position.position,
+    )?;
+    let target = rename_target.target;
+
+    let mut locations = find_usages(
+        get_interner().resolve(&target.name()),
+        &[target],
+        &documents,
+        document_cache,
+    );
+    // The usage search already covers definition keys in open documents, where the indexed
+    // positions may be out of date
+    locations.extend(
+        get_target_locations(target)
+            .iter()
+            .filter_map(to_lsp_location)
+            .filter(|location| !documents.contains_key(location.uri.as_str())),
+    );
+
+    Ok(Some(WorkspaceEdit {
+        changes: Some(rename_edits(locations, new_name, is_base_game_file)),
+        ..Default::default()
+    }))
+}
+
+/// Turn the locations of a definition into one edit per distinct range, leaving read-only
+/// files alone
+fn rename_edits(
+    locations: Vec<Location>,
+    new_name: &str,
+    is_read_only: impl Fn(&Path) -> bool,
+) -> HashMap<Url, Vec<TextEdit>> {
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for location in locations {
+        // Base game files are read-only, anything there keeps referring to the old name
+        if location
+            .uri
+            .to_file_path()
+            .is_ok_and(|path| is_read_only(&path))
+        {
+            continue;
+        }
+
+        let edits = changes.entry(location.uri).or_default();
+        if !edits.iter().any(|edit| edit.range == location.range) {
+            edits.push(TextEdit {
+                range: location.range,
+                new_text: new_name.to_string(),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Find the entity definition under the cursor, refusing anything that can't be renamed
+fn find_rename_target(
+    content: &str,
+    uri: &str,
+    document_cache: &DocumentCache,
+    position: Position,
+) -> Result<RenameTarget> {
+    let cached_document = document_cache
+        .get(uri)
+        .ok_or_else(|| rename_error("The document has not been parsed yet"))?;
+    let ast = cached_document
+        .borrow_ast()
+        .map_err(|_| rename_error("The document has syntax errors"))?;
+
+    let offset = position_to_offset(content, position);
+    let cursor_target =
+        find_cursor_target(ast, offset).ok_or_else(|| rename_error("Nothing to rename here"))?;
+
+    let namespace_context = match validate_namespace_and_caches(uri, &cached_document.root_dir) {
+        NamespaceValidationResult::Valid(context) => (context.namespace, context.namespace_type),
+        NamespaceValidationResult::CachesNotInitialized => {
+            return Err(rename_error("Game data is still loading"));
+        }
+        _ => return Err(rename_error("This file has no known type")),
+    };
+
+    select_rename_target(
+        find_definition_target_ranges(ast, Some(namespace_context), &cursor_target),
+        get_target_locations,
+        is_base_game_file,
+    )
+}
+
+/// Pick the first entity target that has indexed definitions, refusing definitions from the
+/// base game. The range comes from the affix stripping done while resolving the token, so it
+/// covers only the definition key within a prefixed or suffixed token.
+fn select_rename_target(
+    candidates: Vec<(DefinitionTarget, Range<usize>)>,
+    get_locations: impl Fn(DefinitionTarget) -> Vec<DefinitionLocation>,
+    is_base_game_path: impl Fn(&Path) -> bool,
+) -> Result<RenameTarget> {
+    let (target, range, locations) = candidates
+        .into_iter()
+        .filter(|(target, _)| matches!(target, DefinitionTarget::Entity { .. }))
+        .map(|(target, range)| (target, range, get_locations(target)))
+        .find(|(_, _, locations)| !locations.is_empty())
+        .ok_or_else(|| rename_error("Only definitions with a known type can be renamed"))?;
+
+    if locations.iter().any(|location| {
+        location.source == DefinitionSource::BaseGame || is_base_game_path(&location.path)
+    }) {
+        return Err(rename_error(&format!(
+            "'{}' is defined in the base game and can't be renamed",
+            get_interner().resolve(&target.name())
+        )));
+    }
+
+    Ok(RenameTarget { target, range })
+}
+
+fn rename_error(message: &str) -> Error {
+    Error {
+        code: ErrorCode::InvalidRequest,
+        message: message.to_string().into(),
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::handlers::cache::LineIndex;
+    use crate::handlers::definition::strip_affixes;
+
+    fn building(name: &str) -> DefinitionTarget {
+        let interner = get_interner();
+        DefinitionTarget::Entity {
+            namespace: interner.get_or_intern("game/common/buildings"),
+            key: interner.get_or_intern(name),
+        }
+    }
+
+    fn location(path: &str, source: DefinitionSource) -> DefinitionLocation {
+        let content = "building_in = {}\n";
+        DefinitionLocation {
+            path: PathBuf::from(path),
+            span: LineIndex::new(content).span(content, 0..11),
+            source,
+            type_name: None,
+        }
+    }
+
+    fn lsp_location(uri: &Url, line: u32, start: u32, end: u32) -> Location {
+        Location {
+            uri: uri.clone(),
+            range: tower_lsp::lsp_types::Range {
+                start: Position::new(line, start),
+                end: Position::new(line, end),
+            },
+        }
+    }
+
+    #[test]
+    fn test_prepare_rename_affixed_token() {
+        let content = "desc = building_Lab_desc\n";
+        let token_start = content.find("building_Lab_desc").unwrap();
+        // Keys are matched case insensitively, so the placeholder keeps the document's spelling
+        let name_range = strip_affixes("building_Lab_desc", Some("building_"), Some("_desc"))
+            .map(|range| token_start + range.start..token_start + range.end)
+            .unwrap();
+
+        let rename_target = select_rename_target(
+            vec![(building("lab"), name_range)],
+            |_| {
+                vec![location(
+                    "/mod/common/buildings/00_buildings.txt",
+                    DefinitionSource::Mod(PathBuf::from("/mod")),
+                )]
+            },
+            |_| false,
+        )
+        .unwrap();
+
+        let PrepareRenameResponse::RangeWithPlaceholder { range, placeholder } =
+            prepare_rename_response(content, &rename_target)
+        else {
+            panic!("expected a range with a placeholder");
+        };
+        assert_eq!(placeholder, "Lab");
+        assert_eq!(range.start, Position::new(0, 16));
+        assert_eq!(range.end, Position::new(0, 19));
+    }
+
+    #[test]
+    fn test_refuses_base_game_definitions() {
+        let error = select_rename_target(
+            vec![(building("building_in"), 0..11)],
+            |_| {
+                vec![location(
+                    "/stellaris/common/buildings/00_buildings.txt",
+                    DefinitionSource::BaseGame,
+                )]
+            },
+            |_| false,
+        )
+        .err()
+        .unwrap();
+        assert!(error.message.contains("defined in the base game"));
+
+        // A mod file that lives in the game directory counts as well
+        let error = select_rename_target(
+            vec![(building("building_in"), 0..11)],
+            |_| {
+                vec![location(
+                    "/stellaris/common/buildings/00_buildings.txt",
+                    DefinitionSource::Mod(PathBuf::from("/stellaris")),
+                )]
+            },
+            |path| path.starts_with("/stellaris"),
+        )
+        .err()
+        .unwrap();
+        assert!(error.message.contains("defined in the base game"));
+    }
+
+    #[test]
+    fn test_refuses_untyped_targets() {
+        let error = select_rename_target(
+            vec![(building("building_in"), 0..11)],
+            |_| Vec::new(),
+            |_| false,
+        )
+        .err()
+        .unwrap();
+        assert!(error.message.contains("known type"));
+    }
+
+    #[test]
+    fn test_rename_edits_are_deduplicated() {
+        let root = std::env::temp_dir();
+        let mod_file =
+            Url::from_file_path(root.join("mod/common/buildings/00_buildings.txt")).unwrap();
+        let base_file =
+            Url::from_file_path(root.join("stellaris/common/buildings/00_buildings.txt")).unwrap();
+        let locations = vec![
+            lsp_location(&mod_file, 0, 0, 11),
+            // The same definition key found by both the usage search and the index
+            lsp_location(&mod_file, 0, 0, 11),
+            lsp_location(&mod_file, 3, 16, 18),
+            lsp_location(&base_file, 0, 0, 11),
+        ];
+
+        let changes = rename_edits(locations, "building_out", |path| {
+            path.starts_with(root.join("stellaris"))
+        });
+
+        assert_eq!(changes.len(), 1);
+        let edits = &changes[&mod_file];
+        assert_eq!(edits.len(), 2);
+        assert!(edits.iter().all(|edit| edit.new_text == "building_out"));
+    }
+}
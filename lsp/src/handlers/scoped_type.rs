@@ -327,7 +327,11 @@ impl ScopedType {
     pub fn cwt_type_for_matching<'a>(&'a self) -> CwtTypeOrSpecialRef<'a> {
         match &self.cwt_type {
             CwtTypeOrSpecial::CwtType(cwt_type) => match &**cwt_type {
-                CwtType::Simple(simple_type) => CwtTypeOrSpecialRef::Simple(simple_type),
+                // The range is only needed to validate numbers, everything else treats ranged
+                // types like their simple type
+                CwtType::Simple(simple_type) | CwtType::Ranged(simple_type, _) => {
+                    CwtTypeOrSpecialRef::Simple(simple_type)
+                }
                 CwtType::Reference(reference_type) => {
                     CwtTypeOrSpecialRef::Reference(reference_type)
                 }
@@ -5,7 +5,7 @@ use tower_lsp::Client;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 
-use crate::handlers::utils::log_message_sync;
+use crate::handlers::utils::{localisation_path, log_message_sync};
 
 use super::document_cache::DocumentCache;
 
@@ -24,7 +24,10 @@ pub fn semantic_tokens_full(
     );
 
     let documents_guard = documents.read().unwrap();
-    if let Some(content) = documents_guard.get(&uri) {
+    // Localisation files aren't script, so they keep the editor's own highlighting
+    if let Some(content) = documents_guard.get(&uri)
+        && localisation_path(&uri).is_none()
+    {
         let start_time = Instant::now();
 
         // Use document cache for efficient token generation
@@ -73,7 +76,10 @@ pub fn semantic_tokens_range(
     );
 
     let documents_guard = documents.read().unwrap();
-    if let Some(content) = documents_guard.get(&uri) {
+    // Localisation files aren't script, so they keep the editor's own highlighting
+    if let Some(content) = documents_guard.get(&uri)
+        && localisation_path(&uri).is_none()
+    {
         let start_time = Instant::now();
 
         // Use document cache for efficient token generation with range filtering
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 
+use crate::handlers::cache::DefinitionIndex;
 use crate::handlers::diagnostics::generate_diagnostics;
 use crate::handlers::initialization::CacheInitializer;
 use crate::handlers::utils::log_message_sync;
@@ -9,10 +11,25 @@ use tower_lsp::Client;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 
-pub async fn initialize(_params: InitializeParams) -> Result<InitializeResult> {
+pub async fn initialize(
+    watched_files_registration: &AtomicBool,
+    params: InitializeParams,
+) -> Result<InitializeResult> {
+    watched_files_registration.store(
+        supports_watched_files_registration(&params.capabilities),
+        Ordering::Relaxed,
+    );
+
     Ok(InitializeResult {
         capabilities: ServerCapabilities {
-            text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+            text_document_sync: Some(TextDocumentSyncCapability::Options(
+                TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::INCREMENTAL),
+                    save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                    ..Default::default()
+                },
+            )),
             hover_provider: Some(HoverProviderCapability::Simple(true)),
             completion_provider: Some(CompletionOptions {
                 resolve_provider: Some(false),
@@ -34,6 +51,18 @@ pub async fn initialize(_params: InitializeParams) -> Result<InitializeResult> {
             ),
             document_formatting_provider: Some(OneOf::Left(true)),
             document_range_formatting_provider: Some(OneOf::Left(true)),
+            definition_provider: Some(OneOf::Left(true)),
+            references_provider: Some(OneOf::Left(true)),
+            document_symbol_provider: Some(OneOf::Left(true)),
+            workspace_symbol_provider: Some(OneOf::Left(true)),
+            rename_provider: Some(OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            })),
+            code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                ..Default::default()
+            })),
             ..Default::default()
         },
         server_info: Some(ServerInfo {
@@ -46,8 +75,13 @@ pub async fn initialize(_params: InitializeParams) -> Result<InitializeResult> {
 pub async fn initialized(
     client: &Client,
     documents: Arc<RwLock<HashMap<String, String>>>,
+    watched_files_registration: &AtomicBool,
     _params: InitializedParams,
 ) {
+    if watched_files_registration.load(Ordering::Relaxed) {
+        register_file_watchers(client).await;
+    }
+
     let client_clone = client.clone();
     let documents = documents.clone();
 
@@ -55,6 +89,10 @@ pub async fn initialized(
         // Use the unified initialization logic
         match CacheInitializer::initialize_silent() {
             Ok(_result) => {
+                // Definition locations are only needed by navigation features, so build them
+                // after diagnostics are ready
+                DefinitionIndex::initialize_in_background();
+
                 log_message_sync(
                     &client_clone,
                     MessageType::INFO,
@@ -86,6 +124,76 @@ pub async fn initialized(
     });
 }
 
+/// Check if the client supports registering `workspace/didChangeWatchedFiles` at runtime, which
+/// clients that don't must not be asked to do
+fn supports_watched_files_registration(capabilities: &ClientCapabilities) -> bool {
+    capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+        .and_then(|watched_files| watched_files.dynamic_registration)
+        .unwrap_or(false)
+}
+
+/// Ask the client to tell us about script files that change outside of the editor, e.g. from
+/// git or another program
+async fn register_file_watchers(client: &Client) {
+    let watchers = ["txt", "gui", "gfx", "asset", "yml", "dds"]
+        .iter()
+        .map(|extension| FileSystemWatcher {
+            glob_pattern: GlobPattern::String(format!("**/*.{}", extension)),
+            kind: None,
+        })
+        .collect();
+
+    let registration = Registration {
+        id: "watched-files".to_string(),
+        method: "workspace/didChangeWatchedFiles".to_string(),
+        register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions {
+            watchers,
+        })
+        .ok(),
+    };
+
+    if let Err(err) = client.register_capability(vec![registration]).await {
+        log_message_sync(
+            client,
+            MessageType::WARNING,
+            format!("Failed to register file watchers: {}", err),
+        );
+    }
+}
+
 pub async fn shutdown() -> Result<()> {
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supports_watched_files_registration() {
+        let capabilities = |dynamic_registration| ClientCapabilities {
+            workspace: Some(WorkspaceClientCapabilities {
+                did_change_watched_files: Some(DidChangeWatchedFilesClientCapabilities {
+                    dynamic_registration,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert!(supports_watched_files_registration(&capabilities(Some(
+            true
+        ))));
+        assert!(!supports_watched_files_registration(&capabilities(Some(
+            false
+        ))));
+        assert!(!supports_watched_files_registration(&capabilities(None)));
+        assert!(!supports_watched_files_registration(
+            &ClientCapabilities::default()
+        ));
+    }
+}
@@ -15,6 +15,10 @@ pub struct Settings {
     #[arg(long)]
     pub validate_localisation: bool,
 
+    /// Languages that localisation keys must be defined for, without the `l_` prefix
+    #[arg(long, value_delimiter = ',', default_value = "english")]
+    pub localisation_languages: Vec<String>,
+
     /// Report unknown scopes during validation
     #[arg(long)]
     pub report_unknown_scopes: bool,
@@ -25,6 +29,7 @@ impl Default for Settings {
         Self {
             game: "stellaris".to_string(),
             validate_localisation: false,
+            localisation_languages: vec!["english".to_string()],
             report_unknown_scopes: false,
         }
     }
@@ -50,6 +55,11 @@ impl Settings {
 
     /// Get a reference to the global settings
     pub fn global() -> &'static Settings {
+        // Tests don't go through startup, so they run with the defaults
+        #[cfg(test)]
+        return SETTINGS.get_or_init(Settings::default);
+
+        #[cfg(not(test))]
         SETTINGS.get().expect("Settings not initialized")
     }
 
@@ -1,18 +1,26 @@
+use cw_model::is_localisation_file;
 use lasso::Spur;
 use path_slash::PathExt;
-use std::path::Path;
-use tower_lsp::lsp_types::Position;
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types::{Position, TextDocumentContentChangeEvent};
 use tower_lsp::{Client, lsp_types::MessageType};
 use url::Url;
 
+use crate::base_game::game;
 use crate::interner::get_interner;
 
+/// The path of a localisation file, which gets its own checks instead of being parsed as script
+pub fn localisation_path(uri: &str) -> Option<PathBuf> {
+    let path = Url::parse(uri).ok()?.to_file_path().ok()?;
+    is_localisation_file(&path, game::get_localisation_folder()).then_some(path)
+}
+
 /// Log a message synchronously by using block_in_place
 pub fn log_message_sync(_client: &Client, message_type: MessageType, message: String) {
     eprintln!("{:?}: {}", message_type, message);
 }
 
-/// Convert LSP position to byte offset in the document
+/// Convert LSP position, counted in UTF-16 code units, to byte offset in the document
 pub fn position_to_offset(text: &str, position: Position) -> usize {
     let target_line = position.line as usize;
     let target_char = position.character as usize;
@@ -44,17 +52,36 @@ pub fn position_to_offset(text: &str, position: Position) -> usize {
         }
     }
 
-    // Now add the character offset within the target line
+    // Now add the character offset within the target line, which LSP counts in UTF-16 code
+    // units rather than chars
     let target_line_text = lines[target_line];
+    let mut utf16_offset = 0;
     let char_offset = target_line_text
         .char_indices()
-        .nth(target_char)
+        .find(|(_, ch)| {
+            let reached = utf16_offset >= target_char;
+            utf16_offset += ch.len_utf16();
+            reached
+        })
         .map(|(i, _)| i)
         .unwrap_or(target_line_text.len());
 
     offset + char_offset
 }
 
+/// Apply an incremental change from the client to a document's text. A change without a range
+/// replaces the whole document.
+pub fn apply_content_change(text: &mut String, change: TextDocumentContentChangeEvent) {
+    match change.range {
+        Some(range) => {
+            let start = position_to_offset(text, range.start);
+            let end = position_to_offset(text, range.end).max(start);
+            text.replace_range(start..end, &change.text);
+        }
+        None => *text = change.text,
+    }
+}
+
 /// Extract namespace from a file URI relative to a root directory
 /// Examples:
 /// file:///some/path/stellaris/common/buildings/01_buildings.txt, root_dir="/some/path/stellaris" -> Some("game/common/buildings")
@@ -105,6 +132,81 @@ pub fn contains_scripted_argument(identifier: Spur) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tower_lsp::lsp_types::Range;
+
+    #[test]
+    fn test_apply_content_change() {
+        let mut text = "tech_a = {\n\tcost = 10\n}\n".to_string();
+
+        apply_content_change(
+            &mut text,
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(1, 8), Position::new(1, 10))),
+                range_length: None,
+                text: "250".to_string(),
+            },
+        );
+        assert_eq!(text, "tech_a = {\n\tcost = 250\n}\n");
+
+        // Inserting at the very end of the document
+        apply_content_change(
+            &mut text,
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(3, 0), Position::new(3, 0))),
+                range_length: None,
+                text: "tech_b = {}\n".to_string(),
+            },
+        );
+        assert_eq!(text, "tech_a = {\n\tcost = 250\n}\ntech_b = {}\n");
+
+        apply_content_change(
+            &mut text,
+            TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "replaced".to_string(),
+            },
+        );
+        assert_eq!(text, "replaced");
+    }
+
+    #[test]
+    fn test_apply_content_change_after_multibyte_characters() {
+        // `é` is two bytes and one UTF-16 unit, `🚀` is four bytes and two UTF-16 units
+        let mut text = "name = \"é🚀\" cost = 10\n".to_string();
+        let cost_column = "name = \"é🚀\" cost = ".encode_utf16().count() as u32;
+        assert_eq!(cost_column, 20);
+
+        apply_content_change(
+            &mut text,
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new(
+                    Position::new(0, cost_column),
+                    Position::new(0, cost_column + 2),
+                )),
+                range_length: None,
+                text: "250".to_string(),
+            },
+        );
+        assert_eq!(text, "name = \"é🚀\" cost = 250\n");
+    }
+
+    #[test]
+    fn test_position_to_offset_counts_utf16_units() {
+        let text = "a🚀b\n";
+
+        assert_eq!(position_to_offset(text, Position::new(0, 1)), 1);
+        assert_eq!(position_to_offset(text, Position::new(0, 3)), 5);
+        assert_eq!(position_to_offset(text, Position::new(0, 4)), 6);
+    }
+
+    #[test]
+    fn test_localisation_path() {
+        assert!(localisation_path("file:///mod/localisation/english/foo_l_english.yml").is_some());
+        assert!(localisation_path("file:///mod/.github/workflows/ci.yml").is_none());
+        assert!(localisation_path("file:///mod/localisation/readme.yml").is_none());
+        assert!(localisation_path("file:///mod/common/buildings/00_buildings.txt").is_none());
+    }
 
     #[test]
     fn test_extract_namespace_from_uri() {
@@ -0,0 +1,134 @@
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, jsonrpc::Result};
+
+use crate::handlers::cache::DefinitionIndex;
+use crate::handlers::definition::to_lsp_location;
+use crate::handlers::document_symbols::definition_symbol_kind;
+use crate::interner::get_interner;
+
+/// Enough to find what you're looking for, the client re-requests as the query changes
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
+
+pub fn workspace_symbol(
+    _client: &Client,
+    params: WorkspaceSymbolParams,
+) -> Result<Option<Vec<SymbolInformation>>> {
+    if !DefinitionIndex::is_initialized() {
+        return Ok(None);
+    }
+
+    let query = params.query.to_lowercase();
+    let interner = get_interner();
+
+    let symbols =
+        DefinitionIndex::search_definitions(|key| fuzzy_score(&query, key), MAX_WORKSPACE_SYMBOLS)
+            .into_iter()
+            .filter_map(|(namespace, key, location)| {
+                let container_name = match location.type_name {
+                    Some(type_name) => interner.resolve(&type_name).to_string(),
+                    None => interner
+                        .resolve(&namespace)
+                        .trim_start_matches("game/")
+                        .to_string(),
+                };
+
+                #[allow(deprecated)]
+                Some(SymbolInformation {
+                    name: interner.resolve(&key).to_string(),
+                    kind: definition_symbol_kind(location.type_name),
+                    tags: None,
+                    deprecated: None,
+                    location: to_lsp_location(&location)?,
+                    container_name: Some(container_name),
+                })
+            })
+            .collect();
+
+    Ok(Some(symbols))
+}
+
+/// Score how well `candidate` matches a lowercase `query`, where every query character has to
+/// appear in order. Consecutive matches and matches at the start of a word score higher, so
+/// `tl1` finds `tech_lasers_1`. Returns `None` if the candidate doesn't match.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate = candidate.to_lowercase();
+    if candidate == query {
+        return Some(1000);
+    }
+
+    let mut score = 0;
+    let mut query_chars = query.chars().peekable();
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for ch in candidate.chars() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+
+        if ch == wanted {
+            query_chars.next();
+            score += 10;
+            if previous_matched {
+                score += 15;
+            }
+            if previous.is_none_or(|previous| matches!(previous, '_' | '.' | ':' | '@')) {
+                score += 20;
+            }
+            previous_matched = true;
+        } else {
+            score -= 1;
+            previous_matched = false;
+        }
+        previous = Some(ch);
+    }
+
+    if query_chars.peek().is_some() {
+        return None;
+    }
+
+    if candidate.starts_with(query) {
+        score += 100;
+    } else if candidate.contains(query) {
+        score += 50;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_requires_all_characters_in_order() {
+        assert!(fuzzy_score("tl1", "tech_lasers_1").is_some());
+        assert!(fuzzy_score("ls1", "tech_lasers_1").is_some());
+        assert!(fuzzy_score("zz", "tech_lasers_1").is_none());
+        assert!(fuzzy_score("1l", "tech_lasers").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_score_ranking() {
+        let exact = fuzzy_score("tech_lasers", "tech_lasers").unwrap();
+        let prefix = fuzzy_score("tech_lasers", "tech_lasers_2").unwrap();
+        let substring = fuzzy_score("lasers", "tech_lasers_2").unwrap();
+        let scattered = fuzzy_score("lasers", "tech_large_storage_rs").unwrap();
+
+        assert!(exact > prefix);
+        assert!(prefix > substring);
+        assert!(substring > scattered);
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_word_starts() {
+        let word_starts = fuzzy_score("sl", "star_lane").unwrap();
+        let inside_word = fuzzy_score("sl", "sample").unwrap();
+
+        assert!(word_starts > inside_word);
+    }
+}
@@ -1,6 +1,7 @@
 use cw_model::GameMod;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, RwLock};
 use tower_lsp::Client;
 
@@ -10,6 +11,8 @@ pub mod interner;
 pub mod semantic_token_collector;
 
 use handlers::cache::game_data::ModDataCache;
+use handlers::cache::{DefinitionIndex, LocalisationCache, SpriteCache};
+use handlers::diagnostics::{DiagnosticsDebouncer, generate_diagnostics};
 use handlers::document_cache::DocumentCache;
 
 pub struct CwLspServer {
@@ -17,6 +20,9 @@ pub struct CwLspServer {
     documents: Arc<RwLock<HashMap<String, String>>>,
     document_cache: DocumentCache,
     mod_cache: Arc<RwLock<HashMap<PathBuf, GameMod>>>,
+    diagnostics_debouncer: DiagnosticsDebouncer,
+    /// Whether the client lets us register file watchers, taken from its capabilities
+    watched_files_registration: AtomicBool,
 }
 
 impl CwLspServer {
@@ -26,6 +32,8 @@ impl CwLspServer {
             documents: Arc::new(RwLock::new(HashMap::new())),
             document_cache: DocumentCache::new(),
             mod_cache: Arc::new(RwLock::new(HashMap::new())),
+            diagnostics_debouncer: DiagnosticsDebouncer::default(),
+            watched_files_registration: AtomicBool::new(false),
         }
     }
 
@@ -41,5 +49,30 @@ impl CwLspServer {
 
     pub fn merge_mod_data(&self, game_mod: &GameMod) {
         ModDataCache::merge_mod_data(game_mod);
+        DefinitionIndex::index_mod(game_mod);
+        SpriteCache::index_mod(game_mod);
+        if let Some(mod_root) = &game_mod.definition.path {
+            self.load_mod_localisation(mod_root.clone());
+        }
+    }
+
+    /// Load the localisation of a mod in the background, then refresh the diagnostics of all open
+    /// documents, which were generated before the mod's keys were known
+    fn load_mod_localisation(&self, mod_root: PathBuf) {
+        let client = self.client.clone();
+        let documents = self.documents.clone();
+
+        tokio::spawn(async move {
+            let loaded =
+                tokio::task::spawn_blocking(move || LocalisationCache::load_mod(&mod_root)).await;
+            if loaded.is_err() {
+                return;
+            }
+
+            let uris: Vec<String> = documents.read().unwrap().keys().cloned().collect();
+            for uri in uris {
+                generate_diagnostics(&client, &documents, &uri).await;
+            }
+        });
     }
 }
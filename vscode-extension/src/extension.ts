@@ -23,6 +23,7 @@ interface GameConfig {
 	languageId: string;
 	displayName: string;
 	filePatterns: string[];
+	localisationPatterns: string[];
 }
 
 // Game configurations
@@ -30,12 +31,14 @@ const GAME_CONFIGS: Record<GameType, GameConfig> = {
 	[GameType.Stellaris]: {
 		languageId: 'clauswitz',
 		displayName: 'Stellaris',
-		filePatterns: ['**/common/**/*.txt', '**/*.mod', '**/*.gui', '**/*.gfx']
+		filePatterns: ['**/common/**/*.txt', '**/*.mod', '**/*.gui', '**/*.gfx'],
+		localisationPatterns: ['**/localisation/**/*_l_*.yml']
 	},
 	[GameType.Victoria3]: {
 		languageId: 'clauswitz',
 		displayName: 'Victoria 3',
-		filePatterns: ['**/common/**/*.txt', '**/*.mod', '**/*.gui', '**/*.gfx']
+		filePatterns: ['**/common/**/*.txt', '**/*.mod', '**/*.gui', '**/*.gfx'],
+		localisationPatterns: ['**/localization/**/*_l_*.yml']
 	}
 };
 
@@ -147,7 +150,11 @@ async function createClientForGame(gameType: GameType, context: ExtensionContext
 	const clientOptions: LanguageClientOptions = {
 		documentSelector: [
 			{ scheme: 'file', language: 'clauswitz' },
-			...config.filePatterns.map(pattern => ({ scheme: 'file', language: 'plaintext', pattern }))
+			...config.filePatterns.map(pattern => ({ scheme: 'file', language: 'plaintext', pattern })),
+			// Localisation files get their own language so they aren't treated as script, but are
+			// also matched by path in case another extension claims them as YAML
+			{ scheme: 'file', language: 'clauswitz-localisation' },
+			...config.localisationPatterns.map(pattern => ({ scheme: 'file', pattern }))
 		],
 		synchronize: {
 			fileEvents: workspace.createFileSystemWatcher('**/.clientrc')